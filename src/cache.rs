@@ -0,0 +1,274 @@
+//! Opt-in on-disk cache for the REST endpoints that change slowly.
+//!
+//! Only endpoints with a TTL in `ttl_for` are ever cached; everything else goes
+//! to the network every time, cache or no cache. An entry past its TTL is not
+//! thrown away when the API gave it an ETag: the next request sends
+//! `If-None-Match`, and a `304` renews the entry without a body.
+//!
+//! Entries live under `~/.dexpaprika/cache`, one JSON file per request, named
+//! after a hash of the host, path and query parameters.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// How long a response to `path` stays fresh, or `None` for paths that are not
+/// cached at all. Network and DEX lists move on the scale of days, and `/stats`
+/// counts are only ever shown rounded by eye.
+pub fn ttl_for(path: &str) -> Option<Duration> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["networks"] => Some(Duration::from_secs(60 * 60)),
+        ["networks", _, "dexes"] => Some(Duration::from_secs(60 * 60)),
+        ["stats"] => Some(Duration::from_secs(10 * 60)),
+        _ => None,
+    }
+}
+
+/// What the cache contributed to the last response, for the `_meta` block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheStatus {
+    /// Served from disk without touching the network.
+    Hit,
+    /// The API confirmed the stored copy with a `304`.
+    Revalidated,
+    /// Fetched from the network and stored.
+    Miss,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CacheOutcome {
+    pub status: CacheStatus,
+    /// Age of the data when served: zero for a miss.
+    pub age_seconds: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    /// UNIX seconds when the body was last fetched or revalidated.
+    pub stored_at: i64,
+    pub etag: Option<String>,
+    pub body: String,
+}
+
+impl CacheEntry {
+    pub fn age(&self) -> Duration {
+        let age = chrono::Utc::now().timestamp() - self.stored_at;
+        Duration::from_secs(age.max(0) as u64)
+    }
+}
+
+/// The cache slot for one request: where it lives, how long it stays fresh,
+/// and what is in it now.
+#[derive(Debug)]
+pub struct CacheSlot {
+    file: PathBuf,
+    pub ttl: Duration,
+    pub entry: Option<CacheEntry>,
+}
+
+impl CacheSlot {
+    /// The stored entry, if it is still inside its TTL.
+    pub fn fresh(&self) -> Option<&CacheEntry> {
+        self.entry.as_ref().filter(|e| e.age() < self.ttl)
+    }
+
+    /// The stored entry's ETag, for a conditional request.
+    pub fn etag(&self) -> Option<&str> {
+        self.entry.as_ref().and_then(|e| e.etag.as_deref())
+    }
+
+    /// Store a freshly fetched body.
+    pub fn store(&mut self, url: &str, etag: Option<String>, body: String) {
+        let entry = CacheEntry {
+            url: url.to_string(),
+            stored_at: chrono::Utc::now().timestamp(),
+            etag,
+            body,
+        };
+        write_entry(&self.file, &entry);
+        self.entry = Some(entry);
+    }
+
+    /// Mark the stored entry as confirmed by a `304` and hand back its body.
+    pub fn renew(&mut self) -> Option<&CacheEntry> {
+        let entry = self.entry.as_mut()?;
+        entry.stored_at = chrono::Utc::now().timestamp();
+        write_entry(&self.file, entry);
+        self.entry.as_ref()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// The slot for a GET of `base` + `path` with `params`, or `None` when the
+    /// path is not cacheable.
+    pub fn slot(&self, base: &str, path: &str, params: &[(&str, &str)]) -> Option<CacheSlot> {
+        let ttl = ttl_for(path)?;
+        let file = self
+            .dir
+            .join(format!("{:016x}.json", cache_key(base, path, params)));
+        let entry = fs::read_to_string(&file)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok());
+        Some(CacheSlot { file, ttl, entry })
+    }
+}
+
+/// Remove every cached response under `dir`. Returns how many were removed.
+pub fn clear(dir: &Path) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Write through a temporary file and a rename, so a concurrent reader sees
+/// either the old entry or the new one and never half of one. A cache that
+/// cannot be written is not worth failing a command over, so errors are
+/// dropped.
+fn write_entry(file: &Path, entry: &CacheEntry) {
+    let Some(dir) = file.parent() else { return };
+    let Ok(json) = serde_json::to_string(entry) else {
+        return;
+    };
+    let tmp = file.with_extension(format!("tmp{}", std::process::id()));
+    if fs::create_dir_all(dir).is_ok() && fs::write(&tmp, json).is_ok() {
+        let _ = fs::rename(&tmp, file);
+    }
+}
+
+/// FNV-1a over the host, the path and the sorted query parameters, so
+/// parameter order does not split one request into two entries.
+fn cache_key(base: &str, path: &str, params: &[(&str, &str)]) -> u64 {
+    let mut sorted: Vec<&(&str, &str)> = params.iter().collect();
+    sorted.sort();
+    let parts = [base.as_bytes(), path.as_bytes()].into_iter().chain(
+        sorted
+            .into_iter()
+            .flat_map(|(k, v)| [k.as_bytes(), v.as_bytes()]),
+    );
+    fnv1a(parts)
+}
+
+/// FNV-1a over `parts`, each followed by a zero byte so that ("ab", "c") and
+/// ("a", "bc") differ. Stable across runs and toolchains, unlike std's hasher,
+/// which is what a name on disk needs.
+pub fn fnv1a<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for b in part.iter().chain([0u8].iter()) {
+            hash ^= u64::from(*b);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "dexpaprika-cache-{name}-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn only_the_slow_endpoints_have_a_ttl() {
+        assert!(ttl_for("/networks").is_some());
+        assert!(ttl_for("/networks/ethereum/dexes").is_some());
+        assert!(ttl_for("/stats").is_some());
+        assert!(ttl_for("/networks/ethereum/pools/search").is_none());
+        assert!(ttl_for("/networks/ethereum/pools/0xabc/transactions").is_none());
+        assert!(ttl_for("/usage").is_none());
+    }
+
+    #[test]
+    fn parameter_order_does_not_change_the_key() {
+        let a = cache_key(
+            "https://h",
+            "/networks/x/dexes",
+            &[("limit", "10"), ("page", "1")],
+        );
+        let b = cache_key(
+            "https://h",
+            "/networks/x/dexes",
+            &[("page", "1"), ("limit", "10")],
+        );
+        assert_eq!(a, b);
+        let other_page = cache_key(
+            "https://h",
+            "/networks/x/dexes",
+            &[("page", "2"), ("limit", "10")],
+        );
+        assert_ne!(a, other_page);
+        let other_host = cache_key(
+            "http://localhost",
+            "/networks/x/dexes",
+            &[("page", "1"), ("limit", "10")],
+        );
+        assert_ne!(a, other_host);
+    }
+
+    #[test]
+    fn a_stored_entry_is_fresh_until_its_ttl_runs_out() {
+        let dir = temp_dir("fresh");
+        let cache = ResponseCache::new(dir.clone());
+        let mut slot = cache.slot("https://h", "/stats", &[]).unwrap();
+        assert!(slot.fresh().is_none());
+        slot.store("https://h/stats", Some("\"v1\"".into()), "{}".into());
+
+        let slot = cache.slot("https://h", "/stats", &[]).unwrap();
+        assert_eq!(slot.fresh().map(|e| e.body.as_str()), Some("{}"));
+        assert_eq!(slot.etag(), Some("\"v1\""));
+
+        let mut stale = slot;
+        stale.entry.as_mut().unwrap().stored_at -= 3600;
+        assert!(stale.fresh().is_none());
+        assert!(stale.renew().is_some());
+        assert!(stale.fresh().is_some());
+
+        assert_eq!(clear(&dir).unwrap(), 1);
+        assert!(cache
+            .slot("https://h", "/stats", &[])
+            .unwrap()
+            .entry
+            .is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn uncacheable_paths_have_no_slot() {
+        let cache = ResponseCache::new(temp_dir("none"));
+        assert!(cache
+            .slot("https://h", "/search", &[("query", "x")])
+            .is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! Candles built from a pool's transactions, for intervals the OHLCV endpoint
+//! doesn't serve.
+//!
+//! `pool_ohlcv` stops at one minute. Bucketing the swaps themselves gets any
+//! interval down to a second, and splits each bucket's volume by side. The
+//! price is the USD price of one token of the pair, token 0 unless asked
+//! otherwise; a swap buys that token when the pool paid it out, which the API
+//! reports as a negative amount.
+//!
+//! ```
+//! use dexpaprika::candles::build;
+//! use dexpaprika::models::PoolTransaction;
+//!
+//! let swap: PoolTransaction = serde_json::from_str(
+//!     r#"{"created_at": "2026-01-01T00:00:05Z", "amount_0": "-2",
+//!         "volume_0": 2.0, "price_0_usd": 10.0}"#,
+//! )
+//! .unwrap();
+//! let (candles, skipped) = build(&[swap], 10, 0);
+//! assert_eq!(candles[0].time_open, "2026-01-01T00:00:00Z");
+//! assert_eq!(candles[0].buy_volume_usd, 20.0);
+//! assert_eq!(skipped, 0);
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::models::pools::number;
+use crate::models::{PoolTransaction, TradeSide};
+use crate::ohlcv::{parse_time, timestamp};
+
+/// One bucket of swaps. Volumes are in USD, at the price of each swap.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candle {
+    pub time_open: String,
+    pub time_close: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume_usd: f64,
+    pub buy_volume_usd: f64,
+    pub sell_volume_usd: f64,
+    pub buys: usize,
+    pub sells: usize,
+    pub trades: usize,
+}
+
+/// Bucket `txs` into candles `step` seconds wide, priced in token `token`
+/// (0 or 1) of the pair, oldest first. Buckets without a swap are left out.
+/// Returns the candles and how many transactions had no time or price to
+/// place them by.
+pub fn build(txs: &[PoolTransaction], step: i64, token: usize) -> (Vec<Candle>, usize) {
+    let mut swaps: Vec<Swap> = txs.iter().filter_map(|tx| Swap::of(tx, token)).collect();
+    let skipped = txs.len() - swaps.len();
+    swaps.sort_by_key(|s| s.order);
+
+    let mut buckets: BTreeMap<i64, Candle> = BTreeMap::new();
+    for swap in swaps {
+        let open = swap.order.0 - swap.order.0.rem_euclid(step);
+        let candle = buckets.entry(open).or_insert_with(|| Candle {
+            time_open: timestamp(open),
+            time_close: timestamp(open + step),
+            open: swap.price,
+            high: swap.price,
+            low: swap.price,
+            close: swap.price,
+            volume_usd: 0.0,
+            buy_volume_usd: 0.0,
+            sell_volume_usd: 0.0,
+            buys: 0,
+            sells: 0,
+            trades: 0,
+        });
+        candle.high = candle.high.max(swap.price);
+        candle.low = candle.low.min(swap.price);
+        candle.close = swap.price;
+        candle.volume_usd += swap.usd;
+        candle.trades += 1;
+        match swap.side {
+            Some(TradeSide::Buy) => {
+                candle.buys += 1;
+                candle.buy_volume_usd += swap.usd;
+            }
+            Some(TradeSide::Sell) => {
+                candle.sells += 1;
+                candle.sell_volume_usd += swap.usd;
+            }
+            None => {}
+        }
+    }
+    (buckets.into_values().collect(), skipped)
+}
+
+/// What a transaction contributes to a candle.
+struct Swap {
+    /// Seconds, then block, transaction and log index: swaps in the same
+    /// second still land in the order they happened.
+    order: (i64, i64, i64, i64),
+    price: f64,
+    usd: f64,
+    side: Option<TradeSide>,
+}
+
+impl Swap {
+    fn of(tx: &PoolTransaction, token: usize) -> Option<Swap> {
+        let at = tx.created_at.as_deref().and_then(parse_time)?.timestamp();
+        let price = if token == 1 {
+            tx.price_1_usd?
+        } else {
+            tx.price_0_usd?
+        };
+        let extra = |key: &str| {
+            tx.extra
+                .as_ref()
+                .and_then(|e| e.get(key))
+                .and_then(number)
+                .map_or(0, |n| n as i64)
+        };
+        Some(Swap {
+            order: (
+                at,
+                extra("created_at_block_number"),
+                extra("transaction_index"),
+                extra("log_index"),
+            ),
+            price,
+            usd: tx.usd_value(token).unwrap_or(0.0),
+            side: tx.side(token),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap(at: &str, log_index: i64, amount_0: &str, price: f64) -> PoolTransaction {
+        serde_json::from_value(serde_json::json!({
+            "created_at": at,
+            "log_index": log_index,
+            "amount_0": amount_0,
+            "price_0_usd": price,
+            "amount_1": "1",
+            "price_1_usd": 1.0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn swaps_fill_ten_second_buckets_in_the_order_they_happened() {
+        let txs = [
+            // Newest first, as the API lists them; the last two share a second.
+            swap("2026-01-01T00:00:21Z", 0, "1", 4.0),
+            swap("2026-01-01T00:00:03Z", 7, "-2", 3.0),
+            swap("2026-01-01T00:00:03Z", 2, "-1", 1.0),
+        ];
+        let (candles, skipped) = build(&txs, 10, 0);
+        assert_eq!(skipped, 0);
+        assert_eq!(candles.len(), 2);
+        let first = &candles[0];
+        assert_eq!(first.time_open, "2026-01-01T00:00:00Z");
+        assert_eq!(first.time_close, "2026-01-01T00:00:10Z");
+        assert_eq!(
+            (first.open, first.high, first.low, first.close),
+            (1.0, 3.0, 1.0, 3.0)
+        );
+        assert_eq!((first.buys, first.sells, first.trades), (2, 0, 2));
+        assert_eq!(first.buy_volume_usd, 7.0);
+        // The bucket at :10 had no swaps and is left out.
+        let second = &candles[1];
+        assert_eq!(second.time_open, "2026-01-01T00:00:20Z");
+        assert_eq!((second.sells, second.sell_volume_usd), (1, 4.0));
+    }
+
+    #[test]
+    fn token_1_prices_the_other_side_and_unplaceable_swaps_are_counted() {
+        let mut txs = vec![swap("2026-01-01T00:00:03Z", 0, "-2", 3.0)];
+        txs.push(serde_json::from_str(r#"{"amount_0": "1"}"#).unwrap());
+        let (candles, skipped) = build(&txs, 60, 1);
+        assert_eq!(skipped, 1);
+        assert_eq!(candles[0].close, 1.0);
+        // The pool took token 1 in, so token 1 was sold.
+        assert_eq!((candles[0].sells, candles[0].sell_volume_usd), (1, 1.0));
+    }
+}
@@ -0,0 +1,322 @@
+//! Record and replay of HTTP exchanges, for deterministic offline runs.
+//!
+//! `--record <dir>` saves every exchange `ApiClient` makes, REST and streaming
+//! alike, as a pair of files: `<name>.json` with the request and the status and
+//! headers of the answer, and `<name>.body` with the body bytes exactly as they
+//! arrived. `--replay <dir>` answers the same requests from those files and
+//! never opens a connection.
+//!
+//! A request is identified by method, path, query and body; the host is left
+//! out, so a cassette recorded against one host replays under any other. The
+//! same request made twice in a run is stored twice, numbered, so a command
+//! that polls replays the sequence it saw. Past the last recording the last
+//! answer repeats.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, ETAG, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// Response headers worth keeping. Everything the CLI reads off a response is
+/// in this list; dates and server names would only make cassettes noisy.
+const KEPT_HEADERS: [HeaderName; 3] = [CONTENT_TYPE, ETAG, RETRY_AFTER];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Record,
+    Replay,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Exchange {
+    pub method: String,
+    /// Path and query, without the host.
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<String>,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub recorded_at: String,
+}
+
+impl Exchange {
+    pub fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn header_map(&self) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                map.insert(name, value);
+            }
+        }
+        map
+    }
+}
+
+/// One request about to be recorded or replayed.
+pub struct Request<'a> {
+    pub method: &'static str,
+    pub target: &'a str,
+    pub body: Option<&'a str>,
+}
+
+#[derive(Debug)]
+pub struct Cassette {
+    dir: PathBuf,
+    mode: Mode,
+    /// How many times each request has been seen this run.
+    seen: Mutex<HashMap<u64, usize>>,
+}
+
+impl Cassette {
+    /// Record into `dir`, creating it if needed. Existing recordings of the
+    /// same requests are overwritten.
+    pub fn record(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cassette directory {}", dir.display()))?;
+        Ok(Self::new(dir, Mode::Record))
+    }
+
+    /// Replay from `dir`, which must exist.
+    pub fn replay(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            bail!("Cassette directory {} does not exist", dir.display());
+        }
+        Ok(Self::new(dir, Mode::Replay))
+    }
+
+    fn new(dir: &Path, mode: Mode) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            mode,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Store a complete exchange.
+    pub fn save(
+        &self,
+        req: &Request<'_>,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<()> {
+        let mut recorder = self.start(req, status, headers)?;
+        recorder.append(body)
+    }
+
+    /// Store the head of an exchange whose body is still arriving, and hand
+    /// back a writer for the body. Used for streams, so whatever arrived before
+    /// Ctrl+C is on disk.
+    pub fn start(
+        &self,
+        req: &Request<'_>,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Result<BodyRecorder> {
+        let name = self.next_name(req);
+        let exchange = Exchange {
+            method: req.method.to_string(),
+            target: req.target.to_string(),
+            request_body: req.body.map(str::to_string),
+            status: status.as_u16(),
+            headers: KEPT_HEADERS
+                .iter()
+                .filter_map(|h| {
+                    let value = headers.get(h)?.to_str().ok()?;
+                    Some((h.as_str().to_string(), value.to_string()))
+                })
+                .collect(),
+            recorded_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let head = self.dir.join(format!("{name}.json"));
+        fs::write(&head, serde_json::to_string_pretty(&exchange)?)
+            .with_context(|| format!("Failed to write {}", head.display()))?;
+        let body = self.dir.join(format!("{name}.body"));
+        let file =
+            File::create(&body).with_context(|| format!("Failed to write {}", body.display()))?;
+        Ok(BodyRecorder { file })
+    }
+
+    /// The recorded answer to `req`.
+    pub fn load(&self, req: &Request<'_>) -> Result<(Exchange, Vec<u8>)> {
+        let (stem, n) = self.next_slot(req);
+        let Some(name) = (0..=n)
+            .rev()
+            .map(|i| format!("{stem}-{i}"))
+            .find(|name| self.dir.join(format!("{name}.json")).exists())
+        else {
+            bail!(
+                "No recording of {} {} in {}. Record it first with --record.",
+                req.method,
+                req.target,
+                self.dir.display()
+            );
+        };
+        let head = self.dir.join(format!("{name}.json"));
+        let exchange: Exchange = serde_json::from_str(&fs::read_to_string(&head)?)
+            .with_context(|| format!("Failed to parse {}", head.display()))?;
+        let body = fs::read(self.dir.join(format!("{name}.body"))).unwrap_or_default();
+        Ok((exchange, body))
+    }
+
+    fn next_name(&self, req: &Request<'_>) -> String {
+        let (stem, n) = self.next_slot(req);
+        format!("{stem}-{n}")
+    }
+
+    /// File stem for `req` and how many times it came up before this one.
+    fn next_slot(&self, req: &Request<'_>) -> (String, usize) {
+        let hash = crate::cache::fnv1a([
+            req.method.as_bytes(),
+            req.target.as_bytes(),
+            req.body.unwrap_or_default().as_bytes(),
+        ]);
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        let count = seen.entry(hash).or_insert(0);
+        let n = *count;
+        *count += 1;
+        (format!("{}-{hash:016x}", slug(req.target)), n)
+    }
+}
+
+pub struct BodyRecorder {
+    file: File,
+}
+
+impl BodyRecorder {
+    pub fn append(&mut self, chunk: &[u8]) -> Result<()> {
+        self.file.write_all(chunk)?;
+        Ok(self.file.flush()?)
+    }
+}
+
+/// Readable prefix for a cassette file name: the path with everything that is
+/// not a letter or digit folded into `_`, so a directory listing shows which
+/// endpoint each file holds.
+fn slug(target: &str) -> String {
+    let path = target.split('?').next().unwrap_or_default();
+    let mut slug: String = path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    slug = slug.trim_matches('_').to_string();
+    slug.truncate(60);
+    if slug.is_empty() {
+        "root".to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "dexpaprika-cassette-{name}-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ))
+    }
+
+    fn get(target: &str) -> Request<'_> {
+        Request {
+            method: "GET",
+            target,
+            body: None,
+        }
+    }
+
+    #[test]
+    fn a_recorded_exchange_replays_with_its_status_headers_and_body() {
+        let dir = temp_dir("roundtrip");
+        let recorder = Cassette::record(&dir).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"v1\""));
+        headers.insert(
+            "date",
+            HeaderValue::from_static("Sun, 18 Oct 2026 10:00:00 GMT"),
+        );
+        recorder
+            .save(&get("/stats"), StatusCode::OK, &headers, b"{\"pools\":7}")
+            .unwrap();
+
+        let player = Cassette::replay(&dir).unwrap();
+        let (exchange, body) = player.load(&get("/stats")).unwrap();
+        assert_eq!(exchange.status(), StatusCode::OK);
+        assert_eq!(exchange.header_map().get(ETAG).unwrap(), "\"v1\"");
+        assert!(exchange.header_map().get("date").is_none());
+        assert_eq!(body, b"{\"pools\":7}");
+
+        let err = player.load(&get("/networks")).unwrap_err();
+        assert!(err.to_string().contains("No recording of GET /networks"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn repeated_requests_replay_in_order_then_repeat_the_last() {
+        let dir = temp_dir("sequence");
+        let recorder = Cassette::record(&dir).unwrap();
+        let target = "/networks/ethereum/pools/0xabc/transactions?limit=10";
+        for body in [b"first".as_slice(), b"second"] {
+            recorder
+                .save(&get(target), StatusCode::OK, &HeaderMap::new(), body)
+                .unwrap();
+        }
+
+        let player = Cassette::replay(&dir).unwrap();
+        let bodies: Vec<Vec<u8>> = (0..3)
+            .map(|_| player.load(&get(target)).unwrap().1)
+            .collect();
+        assert_eq!(
+            bodies,
+            [b"first".to_vec(), b"second".to_vec(), b"second".to_vec()]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn the_request_body_is_part_of_the_identity() {
+        let dir = temp_dir("body");
+        let recorder = Cassette::record(&dir).unwrap();
+        let post = |body| Request {
+            method: "POST",
+            target: "/sse/prices",
+            body: Some(body),
+        };
+        recorder
+            .save(&post("[1]"), StatusCode::OK, &HeaderMap::new(), b"one")
+            .unwrap();
+        let player = Cassette::replay(&dir).unwrap();
+        assert!(player.load(&post("[2]")).is_err());
+        assert_eq!(player.load(&post("[1]")).unwrap().1, b"one");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_names_start_with_the_endpoint() {
+        assert_eq!(
+            slug("/networks/ethereum/pools/search?limit=5"),
+            "networks_ethereum_pools_search"
+        );
+        assert_eq!(slug("/?x=1"), "root");
+    }
+}
@@ -1,11 +1,41 @@
-use anyhow::{bail, Result};
+use anyhow::Result;
+use futures::StreamExt;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::{HeaderMap, ACCEPT, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
 use reqwest::StatusCode;
 
+use crate::cache::{CacheOutcome, CacheStatus, ResponseCache};
+use crate::cassette::{self, Cassette};
+use crate::error::ApiError;
+use crate::ratelimit::{RateLimiter, DEFAULT_RATE_PER_MINUTE};
+use crate::retry::{self, RetryPolicy};
+
+/// Public REST host. Pro plans are served from `https://api-pro.dexpaprika.com`.
+pub const DEFAULT_API_BASE_URL: &str = "https://api.dexpaprika.com";
+
+/// Public streaming (SSE) host.
+pub const DEFAULT_STREAMING_BASE_URL: &str = "https://streaming.dexpaprika.com";
+
+/// Raw bytes of a streaming response, chunked however they arrived.
+pub type ByteStream = Pin<Box<dyn futures::Stream<Item = Result<Vec<u8>>> + Send>>;
+
 pub struct ApiClient {
     http: reqwest::Client,
     dexpaprika_base: String,
+    streaming_base: String,
     /// Optional API key. `None` means keyless, which is the default and works.
     api_key: Option<String>,
+    retry: RetryPolicy,
+    /// Pace for every request this client sends, shared with every other
+    /// client at the same pace. `None` when disabled.
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// On-disk response cache. `None` unless the user opted in.
+    cache: Option<ResponseCache>,
+    last_cache: Mutex<Option<CacheOutcome>>,
+    /// Where exchanges are recorded to or replayed from, if anywhere.
+    cassette: Option<Cassette>,
 }
 
 impl ApiClient {
@@ -18,7 +48,8 @@ impl ApiClient {
     ///
     /// The host is never inferred from the key. Free keys are served from the
     /// default base and only Pro moves to api-pro.dexpaprika.com; sending a free
-    /// key there returns 403.
+    /// key there returns 403. Pro callers switch hosts explicitly through
+    /// `with_base_urls`.
     pub fn with_api_key(api_key: Option<String>) -> Self {
         let ua = format!(
             "dexpaprika-cli/{} ({}/{})",
@@ -31,77 +62,387 @@ impl ApiClient {
                 .user_agent(&ua)
                 .build()
                 .expect("failed to build HTTP client"),
-            dexpaprika_base: "https://api.dexpaprika.com".to_string(),
+            dexpaprika_base: DEFAULT_API_BASE_URL.to_string(),
+            streaming_base: DEFAULT_STREAMING_BASE_URL.to_string(),
             api_key,
+            retry: RetryPolicy::default(),
+            rate_limiter: Some(RateLimiter::shared(DEFAULT_RATE_PER_MINUTE)),
+            cache: None,
+            last_cache: Mutex::new(None),
+            cassette: None,
+        }
+    }
+
+    /// Pace every REST call and stream connection attempt to `per_minute`
+    /// requests, together with every other client in the process at that
+    /// pace. Zero turns pacing off.
+    pub fn with_rate_limit(mut self, per_minute: u32) -> Self {
+        self.rate_limiter = (per_minute > 0).then(|| RateLimiter::shared(per_minute));
+        self
+    }
+
+    /// Wait for the rate limiter before a REST call or stream connection.
+    async fn throttle(&self) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
         }
     }
 
+    /// Replace the retry policy `dexpaprika_get` uses for 429s, 5xx answers and
+    /// requests that never got an answer.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Point the client at other REST and streaming hosts: api-pro for Pro
+    /// keys, or a local stand-in for tests. Both are joined with absolute paths,
+    /// so a trailing slash is dropped here.
+    pub fn with_base_urls(mut self, api_base: &str, streaming_base: &str) -> Self {
+        self.dexpaprika_base = api_base.trim_end_matches('/').to_string();
+        self.streaming_base = streaming_base.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn api_base_url(&self) -> &str {
+        &self.dexpaprika_base
+    }
+
+    pub fn streaming_base_url(&self) -> &str {
+        &self.streaming_base
+    }
+
+    /// Full URL of a streaming endpoint, e.g. `streaming_url("/sse/prices")`.
+    pub fn streaming_url(&self, path: &str) -> String {
+        format!("{}{}", self.streaming_base, path)
+    }
+
     /// Attach the API key to a request builder, if one is configured.
     ///
-    /// Used by the streaming commands, which build their own requests rather
-    /// than going through `dexpaprika_get`. Streaming authenticates the same way
-    /// as REST: verified on the wire, a bad key there returns 401 and no key
-    /// returns 200.
-    pub fn authorize(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    /// Streaming authenticates the same way as REST: verified on the wire, a
+    /// bad key there returns 401 and no key returns 200.
+    fn authorize(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
         match &self.api_key {
             Some(key) => req.header("Authorization", key),
             None => req,
         }
     }
 
+    /// Keep cacheable responses on disk under `cache`. See `crate::cache` for
+    /// which paths qualify and for how long.
+    pub fn with_cache(mut self, cache: Option<ResponseCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Record every exchange to `cassette`, or answer every request from it,
+    /// depending on its mode.
+    pub fn with_cassette(mut self, cassette: Option<Cassette>) -> Self {
+        self.cassette = cassette;
+        self
+    }
+
+    /// What the cache did for the most recent `dexpaprika_get`, if it was
+    /// involved at all. Commands copy this into their `_meta` block.
+    pub fn cache_outcome(&self) -> Option<CacheOutcome> {
+        *self.last_cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn note_cache(&self, outcome: Option<CacheOutcome>) {
+        *self.last_cache.lock().unwrap_or_else(|e| e.into_inner()) = outcome;
+    }
+
+    /// GET a REST path and decode the JSON body.
+    ///
+    /// A 429, a 5xx or a request that never got an answer is retried under the
+    /// client's `RetryPolicy`, honouring `Retry-After` when the server sends
+    /// one. Every other error status fails on the first answer. Failures come
+    /// back as an `ApiError` inside the `anyhow::Error`.
+    ///
+    /// With a cache configured, a fresh stored copy of a cacheable path is
+    /// returned without a request, and a stale one with an ETag is revalidated
+    /// with `If-None-Match`.
     pub async fn dexpaprika_get<T: serde::de::DeserializeOwned>(
         &self,
         path: &str,
         params: &[(&str, &str)],
     ) -> Result<T> {
-        let url = format!("{}{}", self.dexpaprika_base, path);
-        let mut req = self.http.get(&url);
+        let slot = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.slot(&self.dexpaprika_base, path, params));
+        let Some(mut slot) = slot else {
+            return self.dexpaprika_get_uncached(path, params).await;
+        };
 
-        if let Some(key) = &self.api_key {
-            // The whole value, with no scheme word in front of it.
-            req = req.header("Authorization", key);
+        if let Some(entry) = slot.fresh() {
+            self.note_cache(Some(CacheOutcome {
+                status: CacheStatus::Hit,
+                age_seconds: entry.age().as_secs(),
+            }));
+            return decode(entry.body.as_bytes());
         }
 
-        if !params.is_empty() {
-            req = req.query(params);
+        let url = format!("{}{}", self.dexpaprika_base, path);
+        let answer = self.fetch(path, params, slot.etag()).await?;
+        if answer.status == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = slot.renew() {
+                self.note_cache(Some(CacheOutcome {
+                    status: CacheStatus::Revalidated,
+                    age_seconds: 0,
+                }));
+                return decode(entry.body.as_bytes());
+            }
+        }
+
+        let etag = answer
+            .headers
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let bytes = answer.into_success_body()?;
+        // Decode before storing: a body the CLI cannot read is not worth keeping.
+        let value = decode(&bytes)?;
+        slot.store(&url, etag, String::from_utf8_lossy(&bytes).into_owned());
+        self.note_cache(Some(CacheOutcome {
+            status: CacheStatus::Miss,
+            age_seconds: 0,
+        }));
+        Ok(value)
+    }
+
+    /// `dexpaprika_get` that always goes to the network, for callers whose
+    /// point is the round trip itself, like `status`.
+    pub async fn dexpaprika_get_uncached<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T> {
+        self.note_cache(None);
+        let answer = self.fetch(path, params, None).await?;
+        decode(&answer.into_success_body()?)
+    }
+
+    /// One REST exchange, from the network or from a replayed cassette, and
+    /// recorded when recording.
+    async fn fetch(
+        &self,
+        path: &str,
+        params: &[(&str, &str)],
+        if_none_match: Option<&str>,
+    ) -> Result<Answer> {
+        let target = request_target(path, params);
+        let exchange = cassette::Request {
+            method: "GET",
+            target: &target,
+            body: None,
+        };
+        if let Some(cassette) = self.replaying() {
+            let (recorded, body) = cassette.load(&exchange)?;
+            return Ok(Answer {
+                status: recorded.status(),
+                headers: recorded.header_map(),
+                body,
+            });
         }
 
-        let resp = req.send().await?;
+        let url = format!("{}{}", self.dexpaprika_base, path);
+        let resp = self.send_with_retries(&url, params, if_none_match).await?;
         let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = match resp.bytes().await {
+            Ok(bytes) => bytes.to_vec(),
+            Err(e) if status.is_success() => return Err(ApiError::Network(e).into()),
+            // The status already says what went wrong; a lost error body
+            // should not turn it into a network error.
+            Err(_) => Vec::new(),
+        };
+        if let Some(cassette) = &self.cassette {
+            cassette.save(&exchange, status, &headers, &body)?;
+        }
+        Ok(Answer {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// Open a streaming (SSE) endpoint and return its raw bytes as they
+    /// arrive. `target` is the path and query, e.g.
+    /// `/sse/prices?method=token_price&chain=...`. With a `body` the request is
+    /// a POST carrying that JSON; without, a GET.
+    ///
+    /// Connection attempts are paced like REST calls and fail with the same
+    /// `ApiError`s. They are not retried: a stream that cannot connect is
+    /// reported rather than silently waited on.
+    pub async fn open_stream(&self, target: &str, body: Option<String>) -> Result<ByteStream> {
+        let exchange = cassette::Request {
+            method: if body.is_some() { "POST" } else { "GET" },
+            target,
+            body: body.as_deref(),
+        };
+        if let Some(cassette) = self.replaying() {
+            let (recorded, bytes) = cassette.load(&exchange)?;
+            let status = recorded.status();
+            if !status.is_success() {
+                let body = String::from_utf8_lossy(&bytes).into_owned();
+                return Err(error_for_status(status, &recorded.header_map(), body).into());
+            }
+            return Ok(Box::pin(futures::stream::iter([Ok(bytes)])));
+        }
+
+        let url = self.streaming_url(target);
+        let req = match &body {
+            Some(body) => self
+                .http
+                .post(&url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.clone()),
+            None => self.http.get(&url),
+        };
+        self.throttle().await;
+        let resp = self
+            .authorize(req.header(ACCEPT, "text/event-stream"))
+            .send()
+            .await
+            .map_err(ApiError::Network)?;
 
+        let status = resp.status();
         if !status.is_success() {
+            let headers = resp.headers().clone();
             let body = resp.text().await.unwrap_or_default();
+            if let Some(cassette) = &self.cassette {
+                cassette.save(&exchange, status, &headers, body.as_bytes())?;
+            }
+            return Err(error_for_status(status, &headers, body).into());
+        }
 
-            // Generic deprecation hint. When any error response carries a
-            // "replacement" field, the API is telling us where a removed or
-            // moved endpoint now lives. Surface it so future deprecations
-            // self-document without waiting on a CLI release. This keys on the
-            // field being present for ANY error status, not just 410.
-            if let Some(hint) = deprecation_hint(status, &body) {
-                bail!("{hint}");
+        let mut recorder = match &self.cassette {
+            Some(cassette) => Some(cassette.start(&exchange, status, resp.headers())?),
+            None => None,
+        };
+        Ok(Box::pin(resp.bytes_stream().map(move |chunk| {
+            let chunk = chunk?;
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.append(&chunk)?;
             }
+            Ok(chunk.to_vec())
+        })))
+    }
 
-            match status {
-                StatusCode::NOT_FOUND => {
-                    bail!("Not found. Check the network ID and address. API response: {body}");
-                }
-                s if s.is_server_error() => {
-                    bail!(
-                        "DexPaprika API is temporarily unavailable. Try again shortly. ({status})"
+    /// The cassette to answer from, when replaying.
+    fn replaying(&self) -> Option<&Cassette> {
+        self.cassette
+            .as_ref()
+            .filter(|c| c.mode() == cassette::Mode::Replay)
+    }
+
+    /// Send a GET, retrying transient failures, and return the final answer
+    /// whatever its status.
+    async fn send_with_retries(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+        if_none_match: Option<&str>,
+    ) -> Result<reqwest::Response> {
+        let mut attempt = 0u32;
+
+        loop {
+            let mut req = self.http.get(url);
+
+            if let Some(key) = &self.api_key {
+                // The whole value, with no scheme word in front of it.
+                req = req.header("Authorization", key);
+            }
+
+            if let Some(etag) = if_none_match {
+                req = req.header(IF_NONE_MATCH, etag);
+            }
+
+            if !params.is_empty() {
+                req = req.query(params);
+            }
+
+            // Retries wait their turn too: a retry is a request like any other.
+            self.throttle().await;
+            let can_retry = attempt < self.retry.max_retries;
+            match req.send().await {
+                Ok(resp) if can_retry && retry::is_retryable_status(resp.status()) => {
+                    let status = resp.status();
+                    // A Retry-After longer than the client will wait ends the
+                    // retries; the caller then reports the status as usual.
+                    let Some(wait) = self.retry.delay_for(attempt, resp.headers()) else {
+                        return Ok(resp);
+                    };
+                    attempt += 1;
+                    eprintln!(
+                        "DexPaprika API answered {status}, retrying in {:.1}s ({attempt}/{})",
+                        wait.as_secs_f64(),
+                        self.retry.max_retries
                     );
+                    tokio::time::sleep(wait).await;
                 }
-                _ => {
-                    bail!("DexPaprika API error {status}: {body}");
+                Ok(resp) => return Ok(resp),
+                Err(e) if can_retry && retry::is_retryable_error(&e) => {
+                    let wait = self.retry.backoff(attempt);
+                    attempt += 1;
+                    eprintln!(
+                        "Request to DexPaprika API failed ({e}), retrying in {:.1}s ({attempt}/{})",
+                        wait.as_secs_f64(),
+                        self.retry.max_retries
+                    );
+                    tokio::time::sleep(wait).await;
                 }
+                Err(e) => return Err(ApiError::Network(e).into()),
             }
         }
+    }
+}
+
+/// A REST answer with its body read, whichever way it arrived.
+struct Answer {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl Answer {
+    /// The body of a successful answer, or the `ApiError` for any other status.
+    fn into_success_body(self) -> Result<Vec<u8>> {
+        if !self.status.is_success() {
+            let body = String::from_utf8_lossy(&self.body).into_owned();
+            return Err(error_for_status(self.status, &self.headers, body).into());
+        }
+        Ok(self.body)
+    }
+}
 
-        Ok(resp.json().await?)
+/// Path and encoded query of a REST request, the way cassettes name it.
+fn request_target(path: &str, params: &[(&str, &str)]) -> String {
+    if params.is_empty() {
+        return path.to_string();
     }
+    let mut url = reqwest::Url::parse("http://localhost").expect("static URL");
+    url.query_pairs_mut().extend_pairs(params);
+    format!("{path}?{}", url.query().unwrap_or_default())
+}
+
+fn decode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    Ok(serde_json::from_slice(bytes).map_err(ApiError::Decode)?)
+}
 
-    pub fn http_client(&self) -> &reqwest::Client {
-        &self.http
+/// Turn an error response into an `ApiError`. Shared by REST and the streaming
+/// commands, whose connection attempts fail the same ways.
+pub fn error_for_status(status: StatusCode, headers: &HeaderMap, body: String) -> ApiError {
+    // Generic deprecation hint. When any error response carries a
+    // "replacement" field, the API is telling us where a removed or moved
+    // endpoint now lives. Surface it so future deprecations self-document
+    // without waiting on a CLI release. This keys on the field being present
+    // for ANY error status, not just 410.
+    if let Some(hint) = deprecation_hint(status, &body) {
+        return hint;
     }
+    ApiError::from_status(status, retry::retry_after(headers), body)
 }
 
 /// Build a caller-facing deprecation message from an error response body.
@@ -115,28 +456,251 @@ impl ApiClient {
 /// the caller falls back to its existing status-specific handling. The parse is
 /// deliberately defensive: a non-JSON body or a missing field never masks the
 /// original error.
-fn deprecation_hint(status: StatusCode, body: &str) -> Option<String> {
+fn deprecation_hint(status: StatusCode, body: &str) -> Option<ApiError> {
     let value: serde_json::Value = serde_json::from_str(body).ok()?;
     let replacement = value.get("replacement")?.as_str()?;
 
-    match value.get("message").and_then(|m| m.as_str()) {
-        Some(message) => Some(format!(
-            "This endpoint was removed. Use {replacement} instead. ({status}) API says: {message}"
-        )),
-        None => Some(format!(
-            "This endpoint was removed. Use {replacement} instead. ({status})"
-        )),
-    }
+    Some(ApiError::Deprecated {
+        status,
+        replacement: replacement.to_string(),
+        message: value
+            .get("message")
+            .and_then(|m| m.as_str())
+            .map(str::to_string),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_server::{serve, serve_and_record};
+
+    /// A client for a stand-in server, unpaced so tests don't wait on each
+    /// other's requests.
+    fn local_client(base: &str) -> ApiClient {
+        ApiClient::with_api_key(None)
+            .with_base_urls(base, base)
+            .with_rate_limit(0)
+    }
+
+    fn fast_retries(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            base_delay: std::time::Duration::from_millis(1),
+            max_delay: std::time::Duration::from_millis(5),
+        }
+    }
+
+    const BAD_GATEWAY: &str =
+        "HTTP/1.1 502 Bad Gateway\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const RATE_LIMITED: &str = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const NOT_FOUND: &str =
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 11\r\nConnection: close\r\n\r\n{\"pools\":7}";
+
+    #[tokio::test]
+    async fn a_transient_502_and_429_are_retried_until_the_answer_arrives() {
+        let base = serve(vec![BAD_GATEWAY, RATE_LIMITED, OK]);
+        let client = local_client(&base).with_retry_policy(fast_retries(2));
+        let value: serde_json::Value = client.dexpaprika_get("/stats", &[]).await.unwrap();
+        assert_eq!(value["pools"], 7);
+    }
+
+    #[tokio::test]
+    async fn a_404_is_not_retried() {
+        // Only one response is queued: a retry would hang on accept and then
+        // fail to connect, rather than report the 404.
+        let base = serve(vec![NOT_FOUND]);
+        let client = local_client(&base).with_retry_policy(fast_retries(3));
+        let err = client
+            .dexpaprika_get::<serde_json::Value>("/stats", &[])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Not found"), "{err}");
+        assert_eq!(
+            crate::error::find_api_error(&err).map(ApiError::code),
+            Some("not_found")
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_stop_at_the_configured_count() {
+        let base = serve(vec![BAD_GATEWAY, BAD_GATEWAY]);
+        let client = local_client(&base).with_retry_policy(fast_retries(1));
+        let err = client
+            .dexpaprika_get::<serde_json::Value>("/stats", &[])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("temporarily unavailable"), "{err}");
+        assert_eq!(
+            crate::error::find_api_error(&err).map(ApiError::exit_code),
+            Some(8)
+        );
+    }
+
+    const OK_WITH_ETAG: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nETag: \"v1\"\r\nContent-Length: 11\r\nConnection: close\r\n\r\n{\"pools\":7}";
+    const NOT_MODIFIED: &str =
+        "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n";
+
+    #[tokio::test]
+    async fn a_cached_path_is_served_from_disk_then_revalidated_by_etag() {
+        let dir = std::env::temp_dir().join(format!(
+            "dexpaprika-client-cache-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let (base, requests) = serve_and_record(vec![OK_WITH_ETAG, NOT_MODIFIED]);
+        let client = local_client(&base).with_cache(Some(ResponseCache::new(dir.clone())));
+        let status = |client: &ApiClient| client.cache_outcome().map(|o| o.status);
+
+        let value: serde_json::Value = client.dexpaprika_get("/stats", &[]).await.unwrap();
+        assert_eq!(value["pools"], 7);
+        assert_eq!(status(&client), Some(CacheStatus::Miss));
+        assert!(!requests.recv().unwrap().contains("if-none-match"));
+
+        let value: serde_json::Value = client.dexpaprika_get("/stats", &[]).await.unwrap();
+        assert_eq!(value["pools"], 7);
+        assert_eq!(status(&client), Some(CacheStatus::Hit));
+
+        // Age the stored entry past its TTL.
+        let file = std::fs::read_dir(&dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let mut entry: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+        entry["stored_at"] = 0.into();
+        std::fs::write(&file, entry.to_string()).unwrap();
+
+        let value: serde_json::Value = client.dexpaprika_get("/stats", &[]).await.unwrap();
+        assert_eq!(value["pools"], 7);
+        assert_eq!(status(&client), Some(CacheStatus::Revalidated));
+        assert!(requests.recv().unwrap().contains("if-none-match: \"v1\""));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn the_uncached_get_never_reads_the_cache() {
+        let dir = std::env::temp_dir().join(format!(
+            "dexpaprika-client-nocache-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let base = serve(vec![OK, OK]);
+        let client = local_client(&base).with_cache(Some(ResponseCache::new(dir.clone())));
+        let _: serde_json::Value = client.dexpaprika_get("/stats", &[]).await.unwrap();
+        let _: serde_json::Value = client.dexpaprika_get_uncached("/stats", &[]).await.unwrap();
+        assert!(client.cache_outcome().is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn cassette_dir(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "dexpaprika-client-{name}-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ))
+    }
+
+    /// A base URL nothing listens on, so any request that reaches the network
+    /// fails.
+    fn dead_base() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    #[tokio::test]
+    async fn a_recorded_rest_call_replays_without_the_network() {
+        let dir = cassette_dir("rest");
+        let base = serve(vec![OK, NOT_FOUND]);
+        let recording = local_client(&base).with_cassette(Some(Cassette::record(&dir).unwrap()));
+        let params = [("limit", "5"), ("sort", "desc")];
+        let _: serde_json::Value = recording.dexpaprika_get("/stats", &params).await.unwrap();
+        assert!(recording
+            .dexpaprika_get::<serde_json::Value>("/networks/nope/dexes", &[])
+            .await
+            .is_err());
+
+        let dead = dead_base();
+        let replaying = local_client(&dead)
+            .with_retry_policy(fast_retries(0))
+            .with_cassette(Some(Cassette::replay(&dir).unwrap()));
+        let value: serde_json::Value = replaying.dexpaprika_get("/stats", &params).await.unwrap();
+        assert_eq!(value["pools"], 7);
+        let err = replaying
+            .dexpaprika_get::<serde_json::Value>("/networks/nope/dexes", &[])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            crate::error::find_api_error(&err).map(ApiError::code),
+            Some("not_found")
+        );
+        let err = replaying
+            .dexpaprika_get::<serde_json::Value>("/stats", &[("limit", "6")])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No recording"), "{err}");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn a_recorded_stream_replays_its_bytes() {
+        const SSE: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\nevent: ping\ndata: {}\n\nevent: token_price\ndata: {\"a\":1}\n\n";
+        let dir = cassette_dir("sse");
+        let base = serve(vec![SSE]);
+        let recording = local_client(&base).with_cassette(Some(Cassette::record(&dir).unwrap()));
+        let body = Some("[{\"chain\":\"ethereum\"}]".to_string());
+        let live: Vec<u8> = recording
+            .open_stream("/sse/prices", body.clone())
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .concat()
+            .await;
+
+        let dead = dead_base();
+        let replaying = local_client(&dead).with_cassette(Some(Cassette::replay(&dir).unwrap()));
+        let replayed: Vec<u8> = replaying
+            .open_stream("/sse/prices", body)
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .concat()
+            .await;
+        assert_eq!(replayed, live);
+        assert!(String::from_utf8(replayed).unwrap().contains("token_price"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn base_urls_default_to_the_public_hosts() {
+        let client = ApiClient::with_api_key(None);
+        assert_eq!(client.api_base_url(), "https://api.dexpaprika.com");
+        assert_eq!(
+            client.streaming_url("/sse/prices"),
+            "https://streaming.dexpaprika.com/sse/prices"
+        );
+    }
+
+    #[test]
+    fn base_url_overrides_drop_a_trailing_slash() {
+        let client = ApiClient::with_api_key(None)
+            .with_base_urls("http://127.0.0.1:8080/", "http://127.0.0.1:8081/");
+        assert_eq!(client.api_base_url(), "http://127.0.0.1:8080");
+        assert_eq!(
+            client.streaming_url("/sse/reserves"),
+            "http://127.0.0.1:8081/sse/reserves"
+        );
+    }
 
     #[test]
     fn deprecation_hint_surfaces_replacement_and_message() {
         let body = r#"{"code":410,"message":"endpoint removed","replacement":"/networks/{network}/pools/search"}"#;
-        let hint = deprecation_hint(StatusCode::GONE, body).expect("expected a hint");
+        let hint = deprecation_hint(StatusCode::GONE, body)
+            .expect("expected a hint")
+            .to_string();
         assert!(hint.contains("/networks/{network}/pools/search"));
         assert!(hint.contains("endpoint removed"));
         assert!(hint.contains("410"));
@@ -145,7 +709,9 @@ mod tests {
     #[test]
     fn deprecation_hint_works_without_message_field() {
         let body = r#"{"replacement":"/search"}"#;
-        let hint = deprecation_hint(StatusCode::GONE, body).expect("expected a hint");
+        let hint = deprecation_hint(StatusCode::GONE, body)
+            .expect("expected a hint")
+            .to_string();
         assert!(hint.contains("/search"));
         assert!(!hint.contains("API says"));
     }
@@ -154,7 +720,9 @@ mod tests {
     fn deprecation_hint_fires_on_any_error_status() {
         // Not just 410: any error whose body carries "replacement" self-documents.
         let body = r#"{"message":"moved","replacement":"/v2/thing"}"#;
-        let hint = deprecation_hint(StatusCode::BAD_REQUEST, body).expect("expected a hint");
+        let hint = deprecation_hint(StatusCode::BAD_REQUEST, body)
+            .expect("expected a hint")
+            .to_string();
         assert!(hint.contains("/v2/thing"));
     }
 
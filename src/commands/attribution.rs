@@ -35,7 +35,7 @@ pub fn execute(output: OutputFormat, raw: bool) -> Result<()> {
             println!("  Need higher rate limits, SLA, or enterprise support?");
             println!("  Contact: support@coinpaprika.com");
         }
-        OutputFormat::Json => {
+        _ => {
             let data = AttributionData {
                 name: "DexPaprika".into(),
                 url: "https://dexpaprika.com".into(),
@@ -44,11 +44,14 @@ pub fn execute(output: OutputFormat, raw: bool) -> Result<()> {
                 markdown: "[Powered by DexPaprika](https://dexpaprika.com)".into(),
                 badge: "https://img.shields.io/badge/data-DexPaprika-blue".into(),
             };
-            crate::output::print_json_wrapped(
-                &data,
-                crate::output::ResponseMeta::dexpaprika("/attribution"),
-                raw,
-            )?;
+            let meta = crate::output::ResponseMeta::dexpaprika("/attribution");
+            match output {
+                OutputFormat::Ndjson => crate::output::ndjson::print_rows(&[data], meta, raw)?,
+                OutputFormat::Csv | OutputFormat::Tsv => {
+                    crate::output::delimited::print_delimited(&[data], output)?
+                }
+                _ => crate::output::print_json_wrapped(&data, meta, raw)?,
+            }
         }
     }
     Ok(())
@@ -0,0 +1,16 @@
+//! `dexpaprika-cli cache` — manage the on-disk response cache.
+
+use anyhow::Result;
+
+use crate::{cache, config};
+
+/// Remove every cached response. Works whether or not the cache is switched on.
+pub fn clear() -> Result<()> {
+    let dir = config::cache_dir()?;
+    let removed = cache::clear(&dir)?;
+    println!(
+        "Removed {removed} cached response(s) from {}",
+        dir.display()
+    );
+    Ok(())
+}
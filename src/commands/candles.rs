@@ -0,0 +1,115 @@
+//! `candles`: OHLCV built from a pool's transactions, at any interval.
+//!
+//! Every page of transactions in the range is fetched before the first candle
+//! can be drawn, so a busy pool over a long range costs many requests. The
+//! crawl stops at `--max-pages` like `--all` does, and says which candles it
+//! left incomplete.
+
+use anyhow::{bail, Result};
+use chrono::{Duration, Utc};
+use dexpaprika::candles;
+use dexpaprika::ohlcv::{interval_seconds, parse_time_flag};
+use dexpaprika::pages::{paginate, CursorPage};
+use dexpaprika::query::TransactionsQuery;
+use dexpaprika::ApiClient;
+use futures::StreamExt;
+
+use crate::commands::trades::resolve_base;
+use crate::output::view::View;
+use crate::output::{OutputFormat, ResponseMeta};
+
+/// The longest range the transactions endpoint serves.
+const MAX_RANGE_DAYS: i64 = 7;
+
+pub async fn execute_candles(
+    client: &ApiClient,
+    network: &str,
+    pool_address: &str,
+    from: &str,
+    to: Option<&str>,
+    interval: &str,
+    base: &str,
+    max_pages: usize,
+    view: &View,
+    output: OutputFormat,
+    raw: bool,
+) -> Result<()> {
+    let Some(step) = interval_seconds(interval) else {
+        bail!("Invalid --interval \"{interval}\". Use a number and s, m, h or d, like 10s or 5m.");
+    };
+    let from_at = parse_time_flag("--from", from)?;
+    let to_at = match to {
+        Some(to) => parse_time_flag("--to", to)?,
+        None => Utc::now(),
+    };
+    if to_at <= from_at {
+        bail!("--to must come after --from.");
+    }
+    if to_at - from_at > Duration::days(MAX_RANGE_DAYS) {
+        bail!(
+            "The transactions endpoint covers at most {MAX_RANGE_DAYS} days. \
+             Narrow --from..--to."
+        );
+    }
+
+    let query = TransactionsQuery::new()
+        .limit(TransactionsQuery::MAX_LIMIT)
+        .from(from_at.timestamp())
+        .to(to_at.timestamp());
+    let mut pages = paginate(None, |cursor| {
+        let query = query.clone().cursor(cursor.as_deref());
+        async move {
+            client
+                .pool_transactions(network, pool_address, &query)
+                .await
+        }
+    })
+    .take(max_pages);
+    let (mut txs, mut fetched, mut more) = (Vec::new(), 0, false);
+    while let Some(page) = pages.next().await {
+        let page = page?;
+        fetched += 1;
+        more = page.next_cursor().is_some();
+        txs.extend(page.into_items());
+    }
+    if more {
+        // Pages run newest first, so the oldest transactions are the ones left.
+        let oldest = txs
+            .iter()
+            .filter_map(|tx| tx.created_at.as_deref())
+            .min()
+            .unwrap_or("-");
+        eprintln!(
+            "Warning: stopped after {fetched} page(s) with transactions left before {oldest}, \
+             so the candles before it are missing or short. Raise --max-pages or move --from \
+             closer."
+        );
+    }
+
+    let token = match resolve_base(&txs, base)? {
+        Some(token) => token,
+        // No transactions, no candles to price.
+        None if txs.is_empty() => 0,
+        None => bail!("--base {base}: these transactions don't name their tokens. Use 0 or 1."),
+    };
+    let (mut rows, skipped) = candles::build(&txs, step, token);
+    if skipped > 0 {
+        eprintln!("Warning: left out {skipped} transaction(s) without a time or a USD price.");
+    }
+    view.apply(&mut rows)?;
+    let meta = ResponseMeta::dexpaprika(&format!("/pool/{network}/{pool_address}/transactions"));
+    match output {
+        OutputFormat::Table => {
+            view.print_table(&rows, crate::output::pools::print_candles_table)?;
+            crate::output::print_dexpaprika_footer();
+        }
+        OutputFormat::Json => crate::output::print_json_wrapped(&view.project(&rows)?, meta, raw)?,
+        OutputFormat::Ndjson => {
+            crate::output::ndjson::print_rows(&view.project(&rows)?, meta, raw)?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&view.project(&rows)?, output)?
+        }
+    }
+    Ok(())
+}
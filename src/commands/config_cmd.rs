@@ -5,17 +5,22 @@
 
 use anyhow::{bail, Result};
 
-use crate::client::ApiClient;
 use crate::config;
+use dexpaprika::client::ApiClient;
 
 /// Show which key is in use, where it came from, and what the API makes of it.
-pub async fn show(cli_key: Option<&str>) -> Result<()> {
+///
+/// `client` is the one the command line resolved, so the check below goes to
+/// whichever host the other commands would use.
+pub async fn show(client: &ApiClient, cli_key: Option<&str>) -> Result<()> {
     let key = config::resolve_api_key(cli_key);
     println!("Source:      {}", config::key_source(cli_key));
     match &key {
         Some(k) => println!("Key:         {}", config::mask_key(k)),
         None => println!("Key:         none, running keyless"),
     }
+    println!("API host:    {}", client.api_base_url());
+    println!("Streaming:   {}", client.streaming_base_url());
     println!("Config file: {}", config::config_path()?.display());
     println!();
 
@@ -23,7 +28,6 @@ pub async fn show(cli_key: Option<&str>) -> Result<()> {
     // endpoints an unreadable key is ignored rather than rejected: the call
     // returns 200 with real data while quietly serving the keyless tier, so a
     // broken key looks exactly like a working one.
-    let client = ApiClient::with_api_key(key.clone());
     match client
         .dexpaprika_get::<serde_json::Value>("/usage", &[])
         .await
@@ -34,6 +38,11 @@ pub async fn show(cli_key: Option<&str>) -> Result<()> {
                 .and_then(|p| p.as_str())
                 .unwrap_or("unknown");
             println!("API reports: plan \"{plan}\"");
+            if let Some(k) = &key {
+                // Keeps the client-side pace in step with the plan. Best
+                // effort: an unwritable config must not fail a read-only show.
+                let _ = config::record_plan(k, plan);
+            }
             if key.is_some() && plan == "keyless" {
                 println!();
                 println!("A key is configured but the API still sees an anonymous caller,");
@@ -54,13 +63,17 @@ pub async fn show(cli_key: Option<&str>) -> Result<()> {
 }
 
 /// Validate a key against /usage, then store it with 0600 permissions.
-pub async fn set_key(key: &str) -> Result<()> {
+///
+/// The check runs against the hosts `client` was resolved with, so a Pro key is
+/// validated on api-pro when that is where it will be used.
+pub async fn set_key(client: &ApiClient, key: &str) -> Result<()> {
     let Some(clean) = config::sanitize_key(key) else {
         bail!("That does not look like a usable key: it is empty or contains a newline.");
     };
 
     println!("Validating...");
-    let client = ApiClient::with_api_key(Some(clean.clone()));
+    let client = ApiClient::with_api_key(Some(clean.clone()))
+        .with_base_urls(client.api_base_url(), client.streaming_base_url());
     match client
         .dexpaprika_get::<serde_json::Value>("/usage", &[])
         .await
@@ -76,7 +89,7 @@ pub async fn set_key(key: &str) -> Result<()> {
                      Nothing was saved."
                 );
             }
-            config::save_api_key(&clean)?;
+            config::save_api_key(&clean, plan)?;
             println!("Key validated. The API reports plan \"{plan}\".");
         }
         Err(err) => {
@@ -97,13 +110,15 @@ pub async fn set_key(key: &str) -> Result<()> {
     Ok(())
 }
 
-/// Forget the stored key. Leaves the environment variable alone.
+/// Forget the stored key. Leaves the environment variable and any stored host
+/// overrides alone.
 pub fn delete() -> Result<()> {
-    if !config::config_exists() {
+    let stored_key = config::load_config().ok().and_then(|c| c.api_key);
+    if !config::config_exists() || stored_key.is_none() {
         println!("Nothing stored. Already keyless.");
         return Ok(());
     }
-    config::delete_config()?;
+    config::delete_api_key()?;
     println!("Stored key removed. The CLI is keyless again.");
     println!();
     println!(
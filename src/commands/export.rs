@@ -0,0 +1,475 @@
+//! `transactions-export`: a pool's whole transaction history, into a file.
+//!
+//! The transactions endpoint takes `from`/`to` ranges of at most seven days,
+//! so the export walks back from `--until` a window at a time and follows the
+//! cursors inside each one. Rows are written as their page arrives, and after
+//! every page a checkpoint next to the file records how far the crawl got:
+//! run the same command again after Ctrl+C, a dropped connection or a spent
+//! quota, and it carries on from there.
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, Utc};
+use dexpaprika::models::PoolTransaction;
+use dexpaprika::ohlcv::parse_time_flag;
+use dexpaprika::pages::CursorPage;
+use dexpaprika::query::TransactionsQuery;
+use dexpaprika::ApiClient;
+use serde::{Deserialize, Serialize};
+
+use crate::output::delimited::Delimited;
+use crate::output::{ndjson, OutputFormat, ResponseMeta};
+
+/// The widest `from`/`to` range the endpoint takes.
+const WINDOW_DAYS: i64 = 7;
+
+/// How far an export got, saved after every page.
+#[derive(Debug, Serialize, Deserialize)]
+struct Checkpoint {
+    network: String,
+    pool: String,
+    since: i64,
+    until: i64,
+    /// The end of the window being walked; windows run back from `until`.
+    window_to: i64,
+    /// The next page inside that window, if it has one.
+    cursor: Option<String>,
+    rows: usize,
+    requests: usize,
+    /// Length of the file when this was saved. A resume cuts the file back
+    /// to it, dropping any rows written after the last save.
+    bytes: u64,
+    /// The CSV or TSV header already in the file.
+    columns: Option<Vec<String>>,
+    /// Ids of the last page written, so a resume that is served it again
+    /// skips them.
+    last_ids: Vec<String>,
+}
+
+impl Checkpoint {
+    fn load(path: &Path) -> Result<Option<Checkpoint>> {
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(Some(serde_json::from_str(&json).with_context(|| {
+                format!("{} is not an export checkpoint", path.display())
+            })?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Write through a temporary file and a rename, so an interruption
+    /// leaves the previous checkpoint rather than half of a new one.
+    fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)
+            .and_then(|()| fs::rename(&tmp, path))
+            .with_context(|| format!("Failed to save the checkpoint {}", path.display()))
+    }
+}
+
+/// Which format to write: `--output` when it names one that can be appended
+/// to, otherwise the file's extension.
+fn export_format(output: OutputFormat, out: &Path) -> Result<OutputFormat> {
+    match output {
+        OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Ndjson => return Ok(output),
+        OutputFormat::Json => bail!(
+            "An export is written a page at a time, which JSON can't be. \
+             Use --output csv, tsv or ndjson."
+        ),
+        OutputFormat::Table => {}
+    }
+    match out.extension().and_then(|e| e.to_str()) {
+        Some("csv") => Ok(OutputFormat::Csv),
+        Some("tsv") => Ok(OutputFormat::Tsv),
+        Some("ndjson" | "jsonl") => Ok(OutputFormat::Ndjson),
+        _ => bail!(
+            "Pick the format with --output csv, tsv or ndjson, or a .csv, .tsv or .ndjson file."
+        ),
+    }
+}
+
+pub async fn execute_transactions_export(
+    client: &ApiClient,
+    network: &str,
+    pool_address: &str,
+    since: &str,
+    until: Option<&str>,
+    out: &Path,
+    checkpoint: Option<PathBuf>,
+    output: OutputFormat,
+    raw: bool,
+) -> Result<()> {
+    let format = export_format(output, out)?;
+    let since_at = parse_time_flag("--since", since)?.timestamp();
+    let until_at = until
+        .map(|u| parse_time_flag("--until", u).map(|t| t.timestamp()))
+        .transpose()?;
+    let checkpoint_path = checkpoint.unwrap_or_else(|| {
+        let mut name = out.as_os_str().to_owned();
+        name.push(".checkpoint");
+        PathBuf::from(name)
+    });
+
+    let (mut state, mut file) = match Checkpoint::load(&checkpoint_path)? {
+        Some(state) => {
+            if state.network != network
+                || !state.pool.eq_ignore_ascii_case(pool_address)
+                || state.since != since_at
+                || until_at.is_some_and(|u| u != state.until)
+            {
+                bail!(
+                    "{} belongs to another export ({} {} from {}). Delete it to start over, \
+                     or pick another --out.",
+                    checkpoint_path.display(),
+                    state.network,
+                    state.pool,
+                    state.since
+                );
+            }
+            let mut file = OpenOptions::new()
+                .write(true)
+                .open(out)
+                .with_context(|| format!("Failed to reopen {} to resume", out.display()))?;
+            file.set_len(state.bytes)?;
+            file.seek(SeekFrom::End(0))?;
+            eprintln!(
+                "Resuming: {} transaction(s) already in {}.",
+                state.rows,
+                out.display()
+            );
+            (state, file)
+        }
+        None => {
+            if out.exists() {
+                bail!(
+                    "{} already exists and there is no checkpoint to resume it from. \
+                     Remove it, or pick another --out.",
+                    out.display()
+                );
+            }
+            let until = until_at.unwrap_or_else(|| Utc::now().timestamp());
+            if until <= since_at {
+                bail!("--until must come after --since.");
+            }
+            let file =
+                File::create(out).with_context(|| format!("Failed to create {}", out.display()))?;
+            let state = Checkpoint {
+                network: network.to_string(),
+                pool: pool_address.to_string(),
+                since: since_at,
+                until,
+                window_to: until,
+                cursor: None,
+                rows: 0,
+                requests: 0,
+                bytes: 0,
+                columns: None,
+                last_ids: Vec::new(),
+            };
+            (state, file)
+        }
+    };
+
+    let mut writer = BufWriter::new(&mut file);
+    if state.bytes == 0 && matches!(format, OutputFormat::Ndjson) && !raw {
+        let meta =
+            ResponseMeta::dexpaprika(&format!("/pool/{network}/{pool_address}/transactions"));
+        ndjson::write_meta(&mut writer, &meta)?;
+        writer.flush()?;
+        state.bytes = writer.get_ref().metadata()?.len();
+    }
+    state.save(&checkpoint_path)?;
+    let mut csv = Delimited::new(format).with_columns(state.columns.clone());
+    let mut seen: HashSet<String> = state.last_ids.iter().cloned().collect();
+
+    let window = Duration::days(WINDOW_DAYS).num_seconds();
+    while state.window_to > state.since {
+        let window_from = (state.window_to - window).max(state.since);
+        let query = TransactionsQuery::new()
+            .limit(TransactionsQuery::MAX_LIMIT)
+            .from(window_from)
+            .to(state.window_to)
+            .cursor(state.cursor.as_deref());
+        let page = tokio::select! {
+            page = client.pool_transactions(network, pool_address, &query) => page,
+            _ = tokio::signal::ctrl_c() => bail!(
+                "Interrupted after {} transaction(s). Run the same command again to resume \
+                 from {}.",
+                state.rows,
+                checkpoint_path.display()
+            ),
+        };
+        let page = page.with_context(|| {
+            format!(
+                "Export stopped; run the same command again to resume from {}",
+                checkpoint_path.display()
+            )
+        })?;
+        state.requests += 1;
+        let next = CursorPage::next_cursor(&page).map(str::to_string);
+        let fresh: Vec<PoolTransaction> = page
+            .transactions
+            .into_iter()
+            .filter(|tx| tx.id.as_ref().is_none_or(|id| seen.insert(id.clone())))
+            .collect();
+
+        match format {
+            OutputFormat::Ndjson => ndjson::write_rows(&mut writer, &fresh)?,
+            _ => csv.write(&mut writer, &fresh)?,
+        }
+        writer.flush()?;
+        state.rows += fresh.len();
+        state.bytes = writer.get_ref().metadata()?.len();
+        state.columns = csv.columns().map(<[String]>::to_vec);
+        state.last_ids = fresh.iter().filter_map(|tx| tx.id.clone()).collect();
+        match next {
+            Some(cursor) => state.cursor = Some(cursor),
+            None => {
+                // Windows don't overlap, so no id from this one turns up again.
+                seen.clear();
+                state.last_ids.clear();
+                state.cursor = None;
+                state.window_to = window_from;
+                eprintln!(
+                    "Back to {}: {} transaction(s) so far.",
+                    chrono::DateTime::from_timestamp(window_from, 0)
+                        .map(|t| t.to_rfc3339())
+                        .unwrap_or_default(),
+                    state.rows
+                );
+            }
+        }
+        state.save(&checkpoint_path)?;
+    }
+
+    let _ = fs::remove_file(&checkpoint_path);
+    eprintln!(
+        "Exported {} transaction(s) to {} in {} request(s).",
+        state.rows,
+        out.display(),
+        state.requests
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server;
+
+    #[test]
+    fn the_format_comes_from_the_flag_then_the_extension() {
+        let out = Path::new("trades.ndjson");
+        assert!(matches!(
+            export_format(OutputFormat::Csv, out),
+            Ok(OutputFormat::Csv)
+        ));
+        assert!(matches!(
+            export_format(OutputFormat::Table, out),
+            Ok(OutputFormat::Ndjson)
+        ));
+        assert!(export_format(OutputFormat::Json, out).is_err());
+        assert!(export_format(OutputFormat::Table, Path::new("trades")).is_err());
+    }
+
+    const DAY: i64 = 86_400;
+    const UNTIL: i64 = 1_767_225_600;
+
+    /// A stand-in for the transactions endpoint: `respond` gets each request's
+    /// target and returns the JSON body.
+    fn serve(
+        respond: impl Fn(&str) -> String + Send + 'static,
+    ) -> (String, std::sync::mpsc::Receiver<String>) {
+        test_server::serve_with(move |request| {
+            Some(test_server::json(&respond(test_server::target(request))))
+        })
+    }
+
+    fn client(base: &str) -> ApiClient {
+        ApiClient::with_api_key(None)
+            .with_base_urls(base, base)
+            .with_rate_limit(0)
+            .with_retry_policy(dexpaprika::retry::RetryPolicy::with_max_retries(0))
+    }
+
+    fn param(target: &str, name: &str) -> Option<String> {
+        let query = target.split_once('?')?.1;
+        query.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            (key == name).then(|| value.to_string())
+        })
+    }
+
+    fn page(ids: &[&str], next: Option<&str>) -> String {
+        let transactions: Vec<_> = ids
+            .iter()
+            .map(|id| serde_json::json!({ "id": id }))
+            .collect();
+        serde_json::json!({ "transactions": transactions, "next_cursor": next }).to_string()
+    }
+
+    /// A file under the temp dir that no other test run uses.
+    fn scratch(name: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .subsec_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("dexpaprika-export-{}-{nanos}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    fn ids_in(out: &Path) -> Vec<String> {
+        fs::read_to_string(out)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let row: serde_json::Value = serde_json::from_str(line).unwrap();
+                row["id"].as_str().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn windows_are_walked_back_from_until_following_the_cursors_in_each() {
+        let (base, requests) = serve(|target| {
+            let to: i64 = param(target, "to").unwrap().parse().unwrap();
+            match (to, param(target, "cursor").as_deref()) {
+                (UNTIL, None) => page(&["a", "b"], Some("p2")),
+                (UNTIL, Some("p2")) => page(&["c"], None),
+                _ => page(&["d"], None),
+            }
+        });
+        let out = scratch("trades.ndjson");
+        let since = (UNTIL - 10 * DAY).to_string();
+        let until = UNTIL.to_string();
+        execute_transactions_export(
+            &client(&base),
+            "ethereum",
+            "0xpool",
+            &since,
+            Some(&until),
+            &out,
+            None,
+            OutputFormat::Table,
+            true,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(ids_in(&out), ["a", "b", "c", "d"]);
+        let windows: Vec<_> = requests
+            .try_iter()
+            .map(|request| {
+                let t = test_server::target(&request);
+                (
+                    param(t, "from").unwrap().parse::<i64>().unwrap(),
+                    param(t, "to").unwrap().parse::<i64>().unwrap(),
+                )
+            })
+            .collect();
+        let seven_back = UNTIL - 7 * DAY;
+        assert_eq!(
+            windows,
+            [
+                (seven_back, UNTIL),
+                (seven_back, UNTIL),
+                (UNTIL - 10 * DAY, seven_back)
+            ]
+        );
+        let mut checkpoint = out.as_os_str().to_owned();
+        checkpoint.push(".checkpoint");
+        assert!(!Path::new(&checkpoint).exists());
+    }
+
+    #[tokio::test]
+    async fn a_resume_cuts_the_file_back_and_skips_rows_it_already_has() {
+        let (base, requests) = serve(|_| page(&["b", "c"], None));
+        let out = scratch("trades.ndjson");
+        let kept = "{\"id\":\"a\"}\n{\"id\":\"b\"}\n";
+        // A row written after the last save, which the resume must drop.
+        fs::write(&out, format!("{kept}{{\"id\":\"late\"}}\n")).unwrap();
+        let checkpoint = scratch("export.checkpoint");
+        Checkpoint {
+            network: "ethereum".into(),
+            pool: "0xPool".into(),
+            since: UNTIL - DAY,
+            until: UNTIL,
+            window_to: UNTIL,
+            cursor: Some("p2".into()),
+            rows: 2,
+            requests: 1,
+            bytes: kept.len() as u64,
+            columns: None,
+            last_ids: vec!["a".into(), "b".into()],
+        }
+        .save(&checkpoint)
+        .unwrap();
+
+        execute_transactions_export(
+            &client(&base),
+            "ethereum",
+            "0xpool",
+            &(UNTIL - DAY).to_string(),
+            None,
+            &out,
+            Some(checkpoint.clone()),
+            OutputFormat::Ndjson,
+            true,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(ids_in(&out), ["a", "b", "c"]);
+        let request = requests.try_recv().unwrap();
+        assert_eq!(
+            param(test_server::target(&request), "cursor").as_deref(),
+            Some("p2")
+        );
+        assert!(!checkpoint.exists());
+    }
+
+    #[tokio::test]
+    async fn a_checkpoint_from_another_export_is_refused() {
+        let out = scratch("trades.ndjson");
+        fs::write(&out, "").unwrap();
+        let checkpoint = scratch("export.checkpoint");
+        Checkpoint {
+            network: "ethereum".into(),
+            pool: "0xother".into(),
+            since: UNTIL - DAY,
+            until: UNTIL,
+            window_to: UNTIL,
+            cursor: None,
+            rows: 0,
+            requests: 0,
+            bytes: 0,
+            columns: None,
+            last_ids: Vec::new(),
+        }
+        .save(&checkpoint)
+        .unwrap();
+
+        let err = execute_transactions_export(
+            &client("http://127.0.0.1:9"),
+            "ethereum",
+            "0xpool",
+            &(UNTIL - DAY).to_string(),
+            None,
+            &out,
+            Some(checkpoint.clone()),
+            OutputFormat::Ndjson,
+            true,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.to_string().contains("belongs to another export"));
+        assert!(checkpoint.exists());
+    }
+}
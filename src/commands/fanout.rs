@@ -0,0 +1,297 @@
+//! `all` and `--networks a,b,c` for the search-backed list commands: one
+//! search per network, merged into a single list.
+
+use std::cmp::Ordering;
+use std::future::Future;
+
+use anyhow::{bail, Result};
+use dexpaprika::models::{PoolSearchItem, TokenSearchItem};
+use dexpaprika::ApiClient;
+use futures::StreamExt;
+use serde::Serialize;
+
+use crate::commands::paging::PageArgs;
+use crate::output::view::View;
+use crate::output::{OutputFormat, ResponseMeta};
+
+/// How many networks are searched at once. Requests still go through the
+/// shared token bucket; this only keeps `all` from opening a connection per
+/// network just to queue them behind it.
+pub const MAX_CONCURRENT_NETWORKS: usize = 4;
+
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct NetworkArgs {
+    /// Network ID (e.g., ethereum, solana), or `all` for every network
+    #[arg(required_unless_present = "networks", conflicts_with = "networks")]
+    pub network: Option<String>,
+
+    /// Search several networks and merge the results: a comma-separated list
+    /// of network IDs, or `all`
+    #[arg(long, value_name = "A,B,C", value_delimiter = ',')]
+    pub networks: Option<Vec<String>>,
+}
+
+impl NetworkArgs {
+    /// The networks to search, in the order given. `all` anywhere in the list
+    /// stands for every network `/networks` returns.
+    pub async fn resolve(&self, client: &ApiClient) -> Result<Vec<String>> {
+        let named: Vec<&str> = match (&self.network, &self.networks) {
+            (Some(network), _) => vec![network.trim()],
+            (None, Some(list)) => list.iter().map(|n| n.trim()).collect(),
+            (None, None) => Vec::new(),
+        };
+        if named.iter().any(|n| n.eq_ignore_ascii_case("all")) {
+            let networks = client.networks().await?;
+            return Ok(networks.into_iter().map(|n| n.id).collect());
+        }
+        let mut networks: Vec<String> = Vec::new();
+        for name in named.into_iter().filter(|n| !n.is_empty()) {
+            if !networks.iter().any(|n| n == name) {
+                networks.push(name.to_string());
+            }
+        }
+        if networks.is_empty() {
+            bail!("No network given. Pass a network ID, `all`, or --networks a,b,c.");
+        }
+        Ok(networks)
+    }
+}
+
+/// Cursors belong to one network's result set, so there is nothing sensible
+/// to follow once several are merged.
+pub fn ensure_single_page(cursor: Option<&str>, pages: PageArgs) -> Result<()> {
+    if cursor.is_some() || pages.crawling() {
+        bail!(
+            "--cursor, --all, --max-pages and --matches page through a single network. \
+             Pick one network to use them."
+        );
+    }
+    Ok(())
+}
+
+/// A search row that records the chain it came from.
+pub trait ChainRow {
+    fn chain_mut(&mut self) -> &mut Option<String>;
+}
+
+impl ChainRow for PoolSearchItem {
+    fn chain_mut(&mut self) -> &mut Option<String> {
+        &mut self.chain
+    }
+}
+
+impl ChainRow for TokenSearchItem {
+    fn chain_mut(&mut self) -> &mut Option<String> {
+        &mut self.chain
+    }
+}
+
+/// Run `fetch` for every network, `MAX_CONCURRENT_NETWORKS` at a time, and
+/// return each network's rows in the order the networks were given.
+///
+/// A network that fails is reported on stderr and left out, so one chain
+/// without search support doesn't sink `all`. Only when every network fails
+/// is that an error.
+pub async fn fetch_all<T, F, Fut>(networks: &[String], fetch: F) -> Result<Vec<(String, Vec<T>)>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    let results: Vec<(String, Result<Vec<T>>)> = futures::stream::iter(networks.iter().cloned())
+        .map(|network| {
+            let rows = fetch(network.clone());
+            async move { (network, rows.await) }
+        })
+        .buffered(MAX_CONCURRENT_NETWORKS)
+        .collect()
+        .await;
+
+    if results.iter().all(|(_, rows)| rows.is_err()) {
+        if let Some((_, Err(e))) = results.into_iter().next() {
+            return Err(e);
+        }
+        return Ok(Vec::new());
+    }
+    let mut merged = Vec::with_capacity(results.len());
+    for (network, rows) in results {
+        match rows {
+            Ok(rows) => merged.push((network, rows)),
+            Err(e) => eprintln!("Skipping {network}: {e:#}"),
+        }
+    }
+    Ok(merged)
+}
+
+/// Tag every row with its network, merge, sort on `key` (a field of the
+/// serialized row) and keep the first `limit`.
+///
+/// Each network already returned its own top `limit` on the same field, so
+/// the first `limit` of the merge are the top `limit` overall. Rows without
+/// a value for `key` go last in either direction.
+pub fn merge<T: ChainRow + Serialize>(
+    per_network: Vec<(String, Vec<T>)>,
+    key: &str,
+    descending: bool,
+    limit: usize,
+) -> Result<Vec<T>> {
+    let mut keyed = Vec::new();
+    for (network, rows) in per_network {
+        for mut row in rows {
+            row.chain_mut().get_or_insert_with(|| network.clone());
+            let value = serde_json::to_value(&row)?
+                .get(key)
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            keyed.push((value, row));
+        }
+    }
+    keyed.sort_by(|(a, _), (b, _)| compare_field(a, b, descending));
+    Ok(keyed.into_iter().take(limit).map(|(_, row)| row).collect())
+}
+
+/// Order two field values: numbers numerically, everything else by its text
+/// (RFC 3339 timestamps sort correctly that way), nulls last.
+fn compare_field(a: &serde_json::Value, b: &serde_json::Value, descending: bool) -> Ordering {
+    use serde_json::Value;
+    let ordering = match (a, b) {
+        (Value::Null, Value::Null) => return Ordering::Equal,
+        (Value::Null, _) => return Ordering::Greater,
+        (_, Value::Null) => return Ordering::Less,
+        (Value::Number(x), Value::Number(y)) => {
+            let (x, y) = (x.as_f64().unwrap_or(0.0), y.as_f64().unwrap_or(0.0));
+            x.partial_cmp(&y).unwrap_or(Ordering::Equal)
+        }
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        _ => a.to_string().cmp(&b.to_string()),
+    };
+    if descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+#[derive(Serialize)]
+struct Merged<'a, T> {
+    results: &'a [T],
+    networks: &'a [String],
+}
+
+/// Search every network in `networks`, merge the rows on `key` and print
+/// them. JSON keeps the `results` key of a single search, next to the list of
+/// networks that answered.
+pub async fn search_across<T, F, Fut>(
+    networks: &[String],
+    fetch: F,
+    key: &str,
+    descending: bool,
+    limit: usize,
+    table: fn(&[T]),
+    view: &View,
+    output: OutputFormat,
+    raw: bool,
+) -> Result<()>
+where
+    T: ChainRow + Serialize,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    let per_network = fetch_all(networks, fetch).await?;
+    let answered: Vec<String> = per_network.iter().map(|(n, _)| n.clone()).collect();
+    // Filter and re-sort after the merge has tagged each row's chain, so that
+    // --where and --sort-local can use it, and before the cut to `limit`.
+    let mut rows = merge(per_network, key, descending, usize::MAX)?;
+    view.apply(&mut rows)?;
+    rows.truncate(limit);
+    match output {
+        OutputFormat::Table => {
+            view.print_table(&rows, table)?;
+            crate::output::print_dexpaprika_footer();
+        }
+        OutputFormat::Json => crate::output::print_json_wrapped(
+            &Merged {
+                results: &view.project(&rows)?,
+                networks: &answered,
+            },
+            ResponseMeta::dexpaprika("/networks"),
+            raw,
+        )?,
+        OutputFormat::Ndjson => {
+            let rows = view.project(&rows)?;
+            crate::output::ndjson::print_rows(&rows, ResponseMeta::dexpaprika("/networks"), raw)?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&view.project(&rows)?, output)?
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(id: &str, chain: Option<&str>, volume: Option<f64>) -> PoolSearchItem {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "chain": chain,
+            "volume_usd_24h": volume,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn merge_tags_chains_and_resorts_across_networks() {
+        let per_network = vec![
+            (
+                "ethereum".to_string(),
+                vec![pool("a", None, Some(10.0)), pool("b", None, None)],
+            ),
+            (
+                "base".to_string(),
+                vec![
+                    pool("c", Some("base"), Some(30.0)),
+                    pool("d", None, Some(20.0)),
+                ],
+            ),
+        ];
+        let rows = merge(per_network, "volume_usd_24h", true, 3).unwrap();
+        let ids: Vec<_> = rows.iter().map(|r| r.id.as_deref().unwrap()).collect();
+        assert_eq!(ids, ["c", "d", "a"]);
+        assert_eq!(rows[2].chain.as_deref(), Some("ethereum"));
+    }
+
+    #[test]
+    fn missing_values_sort_last_ascending_too() {
+        let per_network = vec![(
+            "ethereum".to_string(),
+            vec![
+                pool("a", None, None),
+                pool("b", None, Some(5.0)),
+                pool("c", None, Some(1.0)),
+            ],
+        )];
+        let rows = merge(per_network, "volume_usd_24h", false, 10).unwrap();
+        let ids: Vec<_> = rows.iter().map(|r| r.id.as_deref().unwrap()).collect();
+        assert_eq!(ids, ["c", "b", "a"]);
+    }
+
+    #[tokio::test]
+    async fn a_failing_network_is_skipped_unless_all_fail() {
+        let networks = vec!["ethereum".to_string(), "nowhere".to_string()];
+        let merged = fetch_all(&networks, |network| async move {
+            if network == "nowhere" {
+                anyhow::bail!("404");
+            }
+            Ok(vec![1])
+        })
+        .await
+        .unwrap();
+        assert_eq!(merged, [("ethereum".to_string(), vec![1])]);
+
+        let failed = fetch_all(&networks, |_| async {
+            Err::<Vec<u8>, _>(anyhow::anyhow!("down"))
+        });
+        assert!(failed.await.is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! `transactions --follow`: `tail -f` for a pool, by polling.
+//!
+//! Every `--every` seconds the newest page is fetched and the transactions not
+//! seen before are printed, oldest first, one per line. When a whole page is
+//! new, older pages are fetched too, up to `MAX_CATCH_UP_PAGES`, so a burst
+//! between two polls isn't cut short. It runs until Ctrl+C. The SSE streams do
+//! the same without polling, on the plans that include them.
+//!
+//! A JSON document can't grow forever, so `--output json` is NDJSON here: the
+//! `_meta` line unless `--raw`, then an object per transaction. `--query` runs
+//! on each transaction in turn and prints its results a line each.
+
+use std::collections::{HashSet, VecDeque};
+use std::io;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use dexpaprika::models::PoolTransaction;
+use dexpaprika::pages::CursorPage;
+use dexpaprika::query::TransactionsQuery;
+use dexpaprika::ApiClient;
+
+use crate::commands::trades::TradeFilter;
+use crate::output::context;
+use crate::output::delimited::Delimited;
+use crate::output::pools::Trade;
+use crate::output::query::Query;
+use crate::output::view::View;
+use crate::output::{ndjson, OutputFormat, ResponseMeta};
+
+/// Pages fetched in one poll before giving up on reaching the last one seen.
+const MAX_CATCH_UP_PAGES: usize = 5;
+
+/// Ids remembered to tell new transactions from old. Far more than a poll
+/// ever returns, and a bound on what a long tail keeps in memory.
+const REMEMBERED_IDS: usize = 10_000;
+
+/// The transactions printed so far, the oldest forgotten first.
+#[derive(Default)]
+struct Seen {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl Seen {
+    fn contains(&self, tx: &PoolTransaction) -> bool {
+        self.ids.contains(&key(tx))
+    }
+
+    /// Remember `tx`; false if it was already there.
+    fn insert(&mut self, tx: &PoolTransaction) -> bool {
+        let key = key(tx);
+        if !self.ids.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back(key);
+        if self.order.len() > REMEMBERED_IDS {
+            if let Some(old) = self.order.pop_front() {
+                self.ids.remove(&old);
+            }
+        }
+        true
+    }
+}
+
+/// A transaction's id, or the whole row for one that has none.
+fn key(tx: &PoolTransaction) -> String {
+    tx.id
+        .clone()
+        .unwrap_or_else(|| serde_json::to_string(tx).unwrap_or_default())
+}
+
+/// `--sort-local` orders a list it has in full, which a tail never has.
+pub fn ensure_can_follow(view: &View) -> Result<()> {
+    if view.sorts() {
+        bail!("--follow prints transactions as they happen, so it can't --sort-local them.");
+    }
+    Ok(())
+}
+
+pub async fn follow_transactions(
+    client: &ApiClient,
+    network: &str,
+    pool_address: &str,
+    limit: usize,
+    every: u64,
+    trades: &TradeFilter,
+    view: &View,
+    output: OutputFormat,
+    raw: bool,
+) -> Result<()> {
+    let jq = context::query();
+    // A query sees each transaction on its own, so it gets no `_meta` line.
+    if matches!(output, OutputFormat::Json | OutputFormat::Ndjson) && !raw && jq.is_none() {
+        let meta =
+            ResponseMeta::dexpaprika(&format!("/pool/{network}/{pool_address}/transactions"));
+        ndjson::write_meta(&mut io::stdout().lock(), &meta)?;
+    }
+    let query = TransactionsQuery::new().limit(limit);
+    let mut seen = Seen::default();
+    let mut csv = Delimited::new(output);
+    // The first poll prints the newest page and no more.
+    let mut pages = 1;
+    loop {
+        match poll(client, network, pool_address, &query, &seen, pages).await {
+            Ok((mut fresh, caught_up)) => {
+                if !caught_up && !seen.ids.is_empty() {
+                    eprintln!(
+                        "Warning: more than {} transactions since the last poll, so some \
+                         were skipped. Poll more often with --every, or raise --limit.",
+                        fresh.len()
+                    );
+                }
+                // Pages run newest first; a tail reads oldest first.
+                fresh.reverse();
+                fresh.retain(|tx| seen.insert(tx));
+                let mut fresh = trades.trades(fresh)?;
+                view.retain(&mut fresh)?;
+                print(&fresh, view, output, jq.as_ref(), &mut csv)?;
+            }
+            Err(e) => eprintln!("Warning: poll failed, trying again: {e:#}"),
+        }
+        pages = MAX_CATCH_UP_PAGES;
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(every)) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+/// The transactions newer than any in `seen`, newest first, from at most
+/// `pages` pages, and whether they reach back to one already seen.
+async fn poll(
+    client: &ApiClient,
+    network: &str,
+    pool_address: &str,
+    query: &TransactionsQuery,
+    seen: &Seen,
+    pages: usize,
+) -> Result<(Vec<PoolTransaction>, bool)> {
+    let mut fresh = Vec::new();
+    let mut cursor: Option<String> = None;
+    for _ in 0..pages {
+        let query = query.clone().cursor(cursor.as_deref());
+        let page = client
+            .pool_transactions(network, pool_address, &query)
+            .await?;
+        cursor = CursorPage::next_cursor(&page).map(str::to_string);
+        let (total, before) = (page.transactions.len(), fresh.len());
+        fresh.extend(
+            page.transactions
+                .into_iter()
+                .filter(|tx| !seen.contains(tx)),
+        );
+        // A page with something already seen on it reaches the last poll.
+        if fresh.len() - before < total || cursor.is_none() {
+            return Ok((fresh, true));
+        }
+    }
+    Ok((fresh, false))
+}
+
+fn print(
+    rows: &[Trade],
+    view: &View,
+    output: OutputFormat,
+    jq: Option<&Query>,
+    csv: &mut Delimited,
+) -> Result<()> {
+    match output {
+        OutputFormat::Table if view.fields.is_none() => {
+            for trade in rows {
+                crate::output::pools::print_transaction_line(trade);
+            }
+        }
+        OutputFormat::Table => {
+            let fields = view.fields.as_deref().unwrap_or_default();
+            for row in view.project(rows)? {
+                let cells: Vec<String> = fields
+                    .iter()
+                    .map(|f| row.get(f).map(|v| v.text()).unwrap_or_default())
+                    .collect();
+                println!("{}", cells.join("  "));
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            ndjson::write_lines(&mut io::stdout().lock(), &view.project(rows)?, jq)?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            csv.write(&mut io::stdout().lock(), &view.project(rows)?)?
+        }
+    }
+    Ok(())
+}
@@ -1,13 +1,22 @@
 pub mod attribution;
+pub mod cache_cmd;
+pub mod candles;
 pub mod config_cmd;
+pub mod export;
+pub mod fanout;
+pub mod follow;
 pub mod networks;
 pub mod onboard;
+pub mod paging;
+pub mod participants;
 pub mod pools;
 pub mod search;
-pub(crate) mod search_mapping;
+pub mod sparkline;
 pub mod stats;
 pub mod status;
 pub mod stream;
 pub mod stream_reserves;
 pub mod tokens;
+pub mod trades;
 pub mod version;
+pub mod view;
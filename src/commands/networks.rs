@@ -1,44 +1,30 @@
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use dexpaprika::ApiClient;
 
-use crate::client::ApiClient;
-use crate::output::OutputFormat;
+use crate::output::view::View;
+use crate::output::{OutputFormat, ResponseMeta};
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct Network {
-    pub id: String,
-    pub display_name: Option<String>,
-    pub volume_usd_24h: Option<f64>,
-    pub txns_24h: Option<i64>,
-    pub pools_count: Option<i64>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct DexesResponse {
-    pub dexes: Vec<Dex>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct Dex {
-    pub dex_id: Option<String>,
-    pub dex_name: Option<String>,
-    pub chain: Option<String>,
-    pub protocol: Option<String>,
-    pub volume_usd_24h: Option<f64>,
-    pub txns_24h: Option<i64>,
-    pub pools_count: Option<i64>,
-}
-
-pub async fn execute_networks(client: &ApiClient, output: OutputFormat, raw: bool) -> Result<()> {
-    let networks: Vec<Network> = client.dexpaprika_get("/networks", &[]).await?;
+pub async fn execute_networks(
+    client: &ApiClient,
+    view: &View,
+    output: OutputFormat,
+    raw: bool,
+) -> Result<()> {
+    let mut networks = client.networks().await?;
+    view.apply(&mut networks)?;
+    let meta = ResponseMeta::dexpaprika("/networks").with_cache(client.cache_outcome());
     match output {
-        OutputFormat::Table => crate::output::networks::print_networks_table(&networks),
+        OutputFormat::Table => {
+            view.print_table(&networks, crate::output::networks::print_networks_table)?
+        }
         OutputFormat::Json => {
-            crate::output::print_json_wrapped(
-                &networks,
-                crate::output::ResponseMeta::dexpaprika("/networks"),
-                raw,
-            )?;
+            crate::output::print_json_wrapped(&view.project(&networks)?, meta, raw)?
+        }
+        OutputFormat::Ndjson => {
+            crate::output::ndjson::print_rows(&view.project(&networks)?, meta, raw)?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&view.project(&networks)?, output)?
         }
     }
     Ok(())
@@ -49,26 +35,24 @@ pub async fn execute_dexes(
     network: &str,
     limit: usize,
     page: usize,
+    view: &View,
     output: OutputFormat,
     raw: bool,
 ) -> Result<()> {
-    let limit_str = limit.to_string();
-    let page_str = page.to_string();
-    let resp: DexesResponse = client
-        .dexpaprika_get(
-            &format!("/networks/{network}/dexes"),
-            &[("limit", &limit_str), ("page", &page_str)],
-        )
-        .await?;
-    let dexes = resp.dexes;
+    let mut dexes = client.dexes(network, limit, page).await?;
+    view.apply(&mut dexes)?;
+    let meta =
+        ResponseMeta::dexpaprika(&format!("/network/{network}")).with_cache(client.cache_outcome());
     match output {
-        OutputFormat::Table => crate::output::networks::print_dexes_table(&dexes),
-        OutputFormat::Json => {
-            crate::output::print_json_wrapped(
-                &dexes,
-                crate::output::ResponseMeta::dexpaprika(&format!("/network/{network}")),
-                raw,
-            )?;
+        OutputFormat::Table => {
+            view.print_table(&dexes, crate::output::networks::print_dexes_table)?
+        }
+        OutputFormat::Json => crate::output::print_json_wrapped(&view.project(&dexes)?, meta, raw)?,
+        OutputFormat::Ndjson => {
+            crate::output::ndjson::print_rows(&view.project(&dexes)?, meta, raw)?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&view.project(&dexes)?, output)?
         }
     }
     Ok(())
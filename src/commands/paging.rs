@@ -0,0 +1,126 @@
+//! `--all`, `--max-pages` and `--matches` for the cursor-paginated list
+//! commands.
+
+use std::future::Future;
+use std::io::Write;
+
+use anyhow::Result;
+use dexpaprika::pages::{paginate, CursorPage};
+use futures::StreamExt;
+use serde::Serialize;
+
+use crate::output::rows::RowWriter;
+
+/// Ceiling on `--all` without `--max-pages`. At 100 rows a page that is ten
+/// thousand rows, and every page is a request against the monthly credits.
+pub const ALL_PAGES_CAP: usize = 100;
+
+#[derive(clap::Args, Debug, Clone, Copy, Default)]
+pub struct PageArgs {
+    /// Follow next_cursor to the last page, writing rows as they arrive.
+    /// Stops at 100 pages unless --max-pages says otherwise.
+    #[arg(long)]
+    pub all: bool,
+
+    /// Follow next_cursor for at most N pages, the first one included
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_pages: Option<u32>,
+
+    /// Follow next_cursor until N rows have matched --where (or N rows in all,
+    /// without it), within the same page limits as --all
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub matches: Option<u32>,
+}
+
+/// A crawl stopped by Ctrl+C before its last page.
+#[derive(Debug)]
+pub struct Interrupted {
+    pages: usize,
+    next: Option<String>,
+}
+
+impl std::fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Interrupted after {} page(s)", self.pages)?;
+        match &self.next {
+            Some(next) => write!(f, ". Resume with --cursor {next}."),
+            None => write!(f, "."),
+        }
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+impl PageArgs {
+    /// Whether the command should crawl rather than print a single page.
+    pub fn crawling(&self) -> bool {
+        self.all || self.max_pages.is_some() || self.matches.is_some()
+    }
+
+    pub fn cap(&self) -> usize {
+        self.max_pages.map_or(ALL_PAGES_CAP, |n| n as usize)
+    }
+}
+
+/// Fetch pages from `first_cursor` on, handing each to `writer` as it
+/// arrives, until the last page, the page cap, `--matches` or Ctrl+C, then
+/// close the output with the cursor of the first page not fetched, so a later
+/// `--cursor` can resume. Matches beyond `--matches` on the last page fetched
+/// are not printed, and resuming from the cursor skips them. A page that fails
+/// still closes the output, with the cursor of that page, before the error is
+/// returned, and so does Ctrl+C, with an `Interrupted` error: either way the
+/// exit code tells a script the crawl is incomplete.
+pub async fn crawl<P, F, Fut, W>(
+    args: PageArgs,
+    first_cursor: Option<String>,
+    fetch: F,
+    mut writer: RowWriter<P::Item, W>,
+) -> Result<()>
+where
+    P: CursorPage + Send,
+    P::Item: Serialize,
+    F: FnMut(Option<String>) -> Fut + Send,
+    Fut: Future<Output = Result<P>> + Send,
+    W: Write,
+{
+    let cap = args.cap();
+    if let Some(matches) = args.matches {
+        writer.stop_after(matches as usize);
+    }
+    let mut pages = paginate(first_cursor.clone(), fetch);
+    let mut fetched = 0;
+    let mut next = first_cursor;
+    while fetched < cap {
+        tokio::select! {
+            page = pages.next() => {
+                let Some(page) = page else {
+                    return writer.finish(None);
+                };
+                let page = match page {
+                    Ok(page) => page,
+                    Err(e) => return Err(writer.fail(next.as_deref(), e)),
+                };
+                next = page.next_cursor().map(str::to_string);
+                writer.page(page.into_items())?;
+                fetched += 1;
+                if writer.is_full() {
+                    return writer.finish(next.as_deref());
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                let interrupted = Interrupted {
+                    pages: fetched,
+                    next: next.clone(),
+                };
+                return Err(writer.fail(next.as_deref(), interrupted.into()));
+            }
+        }
+    }
+    if next.is_some() && args.max_pages.is_none() {
+        eprintln!(
+            "Stopped after {ALL_PAGES_CAP} pages, the limit without --max-pages. \
+             Pass --max-pages to go further, or --cursor to resume from here."
+        );
+    }
+    writer.finish(next.as_deref())
+}
@@ -0,0 +1,217 @@
+//! `transactions --aggregate address`: a pool's recent trades, a row per
+//! address.
+//!
+//! The transactions carry who made them only in their untyped fields, under
+//! one of `PARTICIPANT_FIELDS`. Grouping on that shows which wallets drive a
+//! pool: how often they traded, how much they bought and sold, and what they
+//! took out of it on balance. The totals cover the pages read, the newest
+//! `--limit` transactions unless `--all` or `--max-pages` reads further back.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use dexpaprika::models::pools::PARTICIPANT_FIELDS;
+use dexpaprika::models::TradeSide;
+use dexpaprika::pages::{paginate, CursorPage};
+use dexpaprika::query::TransactionsQuery;
+use dexpaprika::ApiClient;
+use futures::StreamExt;
+
+use crate::commands::paging::PageArgs;
+use crate::commands::trades::TradeFilter;
+use crate::output::pools::{Participant, Trade};
+use crate::output::view::View;
+use crate::output::{OutputFormat, Pagination, ResponseMeta};
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum AggregateBy {
+    /// The address that made each swap
+    Address,
+}
+
+/// A row per address, the busiest by USD volume first. Returns the rows and
+/// how many trades named no address.
+pub fn by_address(trades: &[Trade]) -> (Vec<Participant>, usize) {
+    let mut rows: Vec<Participant> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut anonymous = 0;
+    for trade in trades {
+        let tx = &trade.tx;
+        let Some(address) = tx.participant() else {
+            anonymous += 1;
+            continue;
+        };
+        let i = *index.entry(address).or_insert_with(|| {
+            rows.push(Participant {
+                address: address.to_string(),
+                trades: 0,
+                buys: 0,
+                sells: 0,
+                volume_usd: 0.0,
+                bought_usd: 0.0,
+                sold_usd: 0.0,
+                token_0_symbol: None,
+                net_token_0: 0.0,
+                token_1_symbol: None,
+                net_token_1: 0.0,
+                first_seen: None,
+                last_seen: None,
+            });
+            rows.len() - 1
+        });
+        let row = &mut rows[i];
+        let usd = trade.usd_value.unwrap_or(0.0);
+        row.trades += 1;
+        row.volume_usd += usd;
+        match trade.side {
+            Some(TradeSide::Buy) => {
+                row.buys += 1;
+                row.bought_usd += usd;
+            }
+            Some(TradeSide::Sell) => {
+                row.sells += 1;
+                row.sold_usd += usd;
+            }
+            None => {}
+        }
+        // The pool's amounts are signed from its side; the address got the
+        // opposite.
+        row.net_token_0 -= tx.amount(0).unwrap_or(0.0);
+        row.net_token_1 -= tx.amount(1).unwrap_or(0.0);
+        if row.token_0_symbol.is_none() {
+            row.token_0_symbol = tx.token_0_symbol.clone();
+        }
+        if row.token_1_symbol.is_none() {
+            row.token_1_symbol = tx.token_1_symbol.clone();
+        }
+        // RFC 3339 in UTC, as the API writes it, sorts as text.
+        if let Some(at) = &tx.created_at {
+            if row.first_seen.as_ref().is_none_or(|first| at < first) {
+                row.first_seen = Some(at.clone());
+            }
+            if row.last_seen.as_ref().is_none_or(|last| at > last) {
+                row.last_seen = Some(at.clone());
+            }
+        }
+    }
+    rows.sort_by(|a, b| {
+        b.volume_usd
+            .total_cmp(&a.volume_usd)
+            .then(b.trades.cmp(&a.trades))
+    });
+    (rows, anonymous)
+}
+
+pub async fn execute_aggregate(
+    client: &ApiClient,
+    network: &str,
+    pool_address: &str,
+    query: TransactionsQuery,
+    pages: PageArgs,
+    trades: &TradeFilter,
+    view: &View,
+    output: OutputFormat,
+    raw: bool,
+) -> Result<()> {
+    let cap = if pages.crawling() { pages.cap() } else { 1 };
+    let mut stream = paginate(query.cursor.clone(), |cursor| {
+        let query = query.clone().cursor(cursor.as_deref());
+        async move {
+            trades.page(
+                client
+                    .pool_transactions(network, pool_address, &query)
+                    .await?,
+            )
+        }
+    })
+    .take(cap);
+    let (mut kept, mut fetched, mut next) = (Vec::new(), 0, None);
+    while let Some(page) = stream.next().await {
+        let page = page?;
+        fetched += 1;
+        next = page.next_cursor().map(str::to_string);
+        kept.extend(page.into_items());
+    }
+
+    let (mut rows, anonymous) = by_address(&kept);
+    if anonymous > 0 {
+        eprintln!(
+            "Warning: left out {anonymous} transaction(s) that name no address in any of {}.",
+            PARTICIPANT_FIELDS.join(", ")
+        );
+    }
+    if next.is_some() {
+        eprintln!(
+            "Note: these totals cover {} trade(s) from the newest {fetched} page(s). \
+             Read further back with --all or --max-pages.",
+            kept.len()
+        );
+    }
+    view.apply(&mut rows)?;
+    let meta = ResponseMeta::dexpaprika(&format!("/pool/{network}/{pool_address}/transactions"))
+        .with_pagination(Pagination {
+            pages: fetched,
+            rows: kept.len(),
+            next_cursor: next,
+        });
+    match output {
+        OutputFormat::Table => {
+            view.print_table(&rows, crate::output::pools::print_participants_table)?;
+            crate::output::print_dexpaprika_footer();
+        }
+        OutputFormat::Json => crate::output::print_json_wrapped(&view.project(&rows)?, meta, raw)?,
+        OutputFormat::Ndjson => {
+            crate::output::ndjson::print_rows(&view.project(&rows)?, meta, raw)?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&view.project(&rows)?, output)?
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(sender: &str, at: &str, amount_0: f64, side: Option<TradeSide>) -> Trade {
+        Trade {
+            tx: serde_json::from_value(serde_json::json!({
+                "sender": sender,
+                "created_at": at,
+                "token_0_symbol": "WETH",
+                "amount_0": amount_0.to_string(),
+                "amount_1": (-100.0 * amount_0).to_string(),
+            }))
+            .unwrap(),
+            side,
+            usd_value: Some(100.0 * amount_0.abs()),
+        }
+    }
+
+    #[test]
+    fn trades_group_by_address_with_the_busiest_first() {
+        let trades = [
+            trade("0xa", "2026-01-01T00:00:09Z", -1.0, Some(TradeSide::Buy)),
+            trade("0xb", "2026-01-01T00:00:05Z", -5.0, Some(TradeSide::Buy)),
+            trade("0xa", "2026-01-01T00:00:01Z", 3.0, Some(TradeSide::Sell)),
+            trade("", "2026-01-01T00:00:00Z", 1.0, None),
+        ];
+        let (rows, anonymous) = by_address(&trades);
+        assert_eq!(anonymous, 1);
+        let addresses: Vec<_> = rows.iter().map(|r| r.address.as_str()).collect();
+        assert_eq!(addresses, ["0xb", "0xa"]);
+        let a = &rows[1];
+        assert_eq!((a.trades, a.buys, a.sells), (2, 1, 1));
+        assert_eq!(
+            (a.bought_usd, a.sold_usd, a.volume_usd),
+            (100.0, 300.0, 400.0)
+        );
+        // Took 1 WETH out and put 3 in.
+        assert_eq!(a.net_token_0, -2.0);
+        assert_eq!(a.net_token_1, 200.0);
+        assert_eq!(a.token_0_symbol.as_deref(), Some("WETH"));
+        assert_eq!(a.first_seen.as_deref(), Some("2026-01-01T00:00:01Z"));
+        assert_eq!(a.last_seen.as_deref(), Some("2026-01-01T00:00:09Z"));
+    }
+}
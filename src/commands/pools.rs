@@ -1,196 +1,130 @@
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
-
-use crate::client::ApiClient;
-use crate::output::OutputFormat;
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct PoolToken {
-    /// Token contract address (DexPaprika uses "id" for address in pool tokens)
-    pub id: Option<String>,
-    pub name: Option<String>,
-    pub symbol: Option<String>,
-    #[serde(flatten)]
-    pub extra: Option<std::collections::HashMap<String, serde_json::Value>>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct PoolDetailPeriod {
-    pub last_price_usd_change: Option<f64>,
-    pub volume_usd: Option<f64>,
-    pub buys: Option<i64>,
-    pub sells: Option<i64>,
-    pub txns: Option<i64>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct PoolDetailPriceStats {
-    pub high: Option<f64>,
-    pub low: Option<f64>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct PoolDetail {
-    pub id: Option<String>,
-    pub chain: Option<String>,
-    pub dex_id: Option<String>,
-    pub dex_name: Option<String>,
-    pub factory_id: Option<String>,
-    #[serde(default)]
-    pub fee: Option<serde_json::Value>,
-    pub created_at: Option<String>,
-    pub created_at_block_number: Option<i64>,
-    pub last_price: Option<f64>,
-    pub last_price_usd: Option<f64>,
-    pub price_time: Option<String>,
-    pub price_stats: Option<PoolDetailPriceStats>,
-    pub token_reserves: Option<serde_json::Value>,
-    pub tokens: Option<Vec<PoolToken>>,
-    #[serde(rename = "24h")]
-    pub h24: Option<PoolDetailPeriod>,
-    #[serde(rename = "6h")]
-    pub h6: Option<PoolDetailPeriod>,
-    #[serde(rename = "1h")]
-    pub h1: Option<PoolDetailPeriod>,
-    #[serde(rename = "30m")]
-    pub m30: Option<PoolDetailPeriod>,
-    #[serde(rename = "15m")]
-    pub m15: Option<PoolDetailPeriod>,
-    #[serde(rename = "5m")]
-    pub m5: Option<PoolDetailPeriod>,
-}
-
-/// Wrapper for paginated transaction responses
-#[derive(Debug, Deserialize, Serialize)]
-pub struct TransactionsResponse {
-    pub transactions: Vec<PoolTransaction>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct PoolTransaction {
-    pub id: Option<String>,
-    pub chain: Option<String>,
-    pub token_0: Option<String>,
-    pub token_0_symbol: Option<String>,
-    pub token_1: Option<String>,
-    pub token_1_symbol: Option<String>,
-    pub amount_0: Option<serde_json::Value>,
-    pub amount_1: Option<serde_json::Value>,
-    pub volume_0: Option<f64>,
-    pub volume_1: Option<f64>,
-    pub price_0_usd: Option<f64>,
-    pub price_1_usd: Option<f64>,
-    pub created_at: Option<String>,
-    #[serde(flatten)]
-    pub extra: Option<std::collections::HashMap<String, serde_json::Value>>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct PoolOhlcv {
-    pub time_open: Option<String>,
-    pub time_close: Option<String>,
-    pub open: Option<f64>,
-    pub high: Option<f64>,
-    pub low: Option<f64>,
-    pub close: Option<f64>,
-    pub volume: Option<f64>,
-}
-
-/// Unified response for the cursor-paginated `/networks/{network}/pools/search`
-/// endpoint. Backs both the pool list and the pool filter commands.
-#[derive(Debug, Deserialize, Serialize)]
-pub struct PoolSearchResponse {
-    #[serde(default)]
-    pub results: Vec<PoolSearchItem>,
-    pub has_next_page: Option<bool>,
-    pub next_cursor: Option<String>,
-}
-
-/// A single result item from `/networks/{network}/pools/search`.
-#[derive(Debug, Deserialize, Serialize)]
-pub struct PoolSearchItem {
-    /// Pool address (the search endpoint returns it under "id", not "address").
-    pub id: Option<String>,
-    pub chain: Option<String>,
-    pub dex_id: Option<String>,
-    pub dex_name: Option<String>,
-    #[serde(default)]
-    pub fee: Option<serde_json::Value>,
-    pub created_at: Option<String>,
-    pub created_at_block_number: Option<i64>,
-    pub volume_usd_24h: Option<f64>,
-    pub volume_usd_7d: Option<f64>,
-    pub volume_usd_30d: Option<f64>,
-    pub liquidity_usd: Option<f64>,
-    pub transactions_24h: Option<i64>,
-    pub price_usd: Option<f64>,
-    pub price_change_percentage_5m: Option<f64>,
-    pub price_change_percentage_1h: Option<f64>,
-    pub price_change_percentage_6h: Option<f64>,
-    pub price_change_percentage_24h: Option<f64>,
-    pub tokens: Option<Vec<PoolToken>>,
-}
+use dexpaprika::indicators::Indicator;
+use dexpaprika::models::{PoolOhlcv, PoolSearchItem};
+use dexpaprika::ohlcv::{self, Gap};
+use dexpaprika::pages::CursorPage;
+use dexpaprika::query::{OhlcvQuery, PoolSearchQuery, PriceChangeBounds, TransactionsQuery};
+use dexpaprika::search_mapping::{map_pool_sort_field, pool_sort_fallback};
+use dexpaprika::ApiClient;
+
+use crate::commands::fanout::{self, NetworkArgs};
+use crate::commands::follow;
+use crate::commands::paging::{self, PageArgs};
+use crate::commands::participants::{self, AggregateBy};
+use crate::commands::sparkline;
+use crate::commands::trades::TradeFilter;
+use crate::commands::view::warn_sort_fallback;
+use crate::output::pools::{IndicatorCandle, IndicatorValues};
+use crate::output::rows::RowWriter;
+use crate::output::view::View;
+use crate::output::{OutputFormat, ResponseMeta};
+
+/// Candles `pool-ohlcv` asks for when neither --limit nor --end says.
+const DEFAULT_OHLCV_LIMIT: usize = 50;
+
+/// Print one page of `/networks/{network}/pools/search`, or crawl from it when
+/// `pages` asks for more. Shared by every command backed by pool search.
+pub(crate) async fn list_pool_search(
+    client: &ApiClient,
+    network: &str,
+    query: PoolSearchQuery,
+    pages: PageArgs,
+    table: fn(&[PoolSearchItem]),
+    view: &View,
+    output: OutputFormat,
+    raw: bool,
+) -> Result<()> {
+    warn_sort_fallback(query.order_by.as_deref().and_then(pool_sort_fallback));
+    let meta = ResponseMeta::dexpaprika(&format!("/networks/{network}/pools/search"));
+    if pages.crawling() {
+        let writer = RowWriter::new(output, raw, meta, table).with_view(view.clone());
+        return paging::crawl(
+            pages,
+            query.cursor.clone(),
+            |cursor| {
+                let query = query.clone().cursor(cursor.as_deref());
+                async move { client.pool_search(network, &query).await }
+            },
+            writer,
+        )
+        .await;
+    }
 
-/// The four price-change windows that `/networks/{network}/pools/search` accepts
-/// as bounds, carried together so the CLI flags cannot get transposed on the way
-/// through. Values are percentages and negatives are ordinary input: a max of
-/// -20 means "down 20% or more".
-///
-/// Only the three short windows are pools-only. tokens/search takes the 24h
-/// bounds as well, and silently ignores the 6h, 1h and 5m ones. Checked on
-/// 2026-08-07 against an unfiltered baseline, because an unknown bound comes
-/// back as HTTP 200 with a full result set: on ethereum tokens/search,
-/// `price_change_percentage_24h_min=20` returned 36.6, 33.2, 1251.0 where the
-/// baseline had -0.07, 0.24, -0.01, while `price_change_percentage_6h_min=20`
-/// returned the baseline unchanged.
-#[derive(Debug, Default, Clone, Copy)]
-pub struct PriceChangeBounds {
-    pub price_change_24h_min: Option<f64>,
-    pub price_change_24h_max: Option<f64>,
-    pub price_change_6h_min: Option<f64>,
-    pub price_change_6h_max: Option<f64>,
-    pub price_change_1h_min: Option<f64>,
-    pub price_change_1h_max: Option<f64>,
-    pub price_change_5m_min: Option<f64>,
-    pub price_change_5m_max: Option<f64>,
+    let mut resp = client.pool_search(network, &query).await?;
+    view.apply(&mut resp.results)?;
+    match output {
+        OutputFormat::Table => {
+            view.print_table(&resp.results, table)?;
+            crate::output::print_list_footer(resp.has_next_page, resp.next_cursor.as_deref());
+        }
+        OutputFormat::Json => {
+            crate::output::print_json_wrapped(&view.project_in(&resp, "results")?, meta, raw)?
+        }
+        OutputFormat::Ndjson => crate::output::ndjson::print_page(
+            &view.project(&resp.results)?,
+            meta,
+            CursorPage::next_cursor(&resp),
+            raw,
+        )?,
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&view.project(&resp.results)?, output)?;
+            crate::output::eprint_more_results_hint(
+                resp.has_next_page,
+                resp.next_cursor.as_deref(),
+            );
+        }
+    }
+    Ok(())
 }
 
-/// Pair each set bound with the query parameter name the API expects.
-///
-/// This lives outside `execute_pool_filter` so the eight names can be pinned by
-/// a test. Nothing at runtime can catch a typo in them: pools/search answers 200
-/// and hands back a full unfiltered set for a parameter it does not recognise,
-/// so `price_change_percentage_1hr_min` looks exactly like a working query. The
-/// live wire is the authority for what these names are, the tests below are the
-/// lock that stops them drifting afterwards.
-///
-/// f64's Display gives the shortest round-trip form, so a bound of -20.0 goes
-/// out as "-20".
-pub fn price_change_params(bounds: &PriceChangeBounds) -> Vec<(&'static str, String)> {
-    [
-        (
-            "price_change_percentage_24h_min",
-            bounds.price_change_24h_min,
-        ),
-        (
-            "price_change_percentage_24h_max",
-            bounds.price_change_24h_max,
-        ),
-        ("price_change_percentage_6h_min", bounds.price_change_6h_min),
-        ("price_change_percentage_6h_max", bounds.price_change_6h_max),
-        ("price_change_percentage_1h_min", bounds.price_change_1h_min),
-        ("price_change_percentage_1h_max", bounds.price_change_1h_max),
-        ("price_change_percentage_5m_min", bounds.price_change_5m_min),
-        ("price_change_percentage_5m_max", bounds.price_change_5m_max),
-    ]
-    .into_iter()
-    .filter_map(|(name, value)| value.map(|v| (name, v.to_string())))
-    .collect()
+/// `list_pool_search` on one network, or the same search merged across
+/// several.
+async fn search_pools(
+    client: &ApiClient,
+    networks: &NetworkArgs,
+    query: PoolSearchQuery,
+    pages: PageArgs,
+    table: fn(&[PoolSearchItem]),
+    view: &View,
+    output: OutputFormat,
+    raw: bool,
+) -> Result<()> {
+    let networks = networks.resolve(client).await?;
+    if let [network] = networks.as_slice() {
+        return list_pool_search(client, network, query, pages, table, view, output, raw).await;
+    }
+    fanout::ensure_single_page(query.cursor.as_deref(), pages)?;
+    warn_sort_fallback(query.order_by.as_deref().and_then(pool_sort_fallback));
+    // Pool rows call the transaction count by a longer name than the sort field.
+    let key = match map_pool_sort_field(query.order_by.as_deref().unwrap_or_default()) {
+        "txns_24h" => "transactions_24h",
+        field => field,
+    };
+    let descending = !query
+        .sort
+        .as_deref()
+        .is_some_and(|s| s.eq_ignore_ascii_case("asc"));
+    let limit = query.limit.unwrap_or(usize::MAX);
+    fanout::search_across(
+        &networks,
+        |network| {
+            let query = query.clone();
+            async move { Ok(client.pool_search(&network, &query).await?.results) }
+        },
+        key,
+        descending,
+        limit,
+        table,
+        view,
+        output,
+        raw,
+    )
+    .await
 }
 
 pub async fn execute_pool_filter(
     client: &ApiClient,
-    network: &str,
+    networks: &NetworkArgs,
     volume_24h_min: Option<f64>,
     volume_24h_max: Option<f64>,
     volume_7d_min: Option<f64>,
@@ -204,110 +138,85 @@ pub async fn execute_pool_filter(
     sort_by: &str,
     sort_dir: &str,
     limit: usize,
-    _page: usize,
+    cursor: Option<&str>,
+    pages: PageArgs,
+    view: &View,
     output: OutputFormat,
     raw: bool,
 ) -> Result<()> {
-    let limit_str = limit.to_string();
-    let order_by = crate::commands::search_mapping::map_pool_sort_field(sort_by);
     // Search is cursor-paginated: no "page" param. "order_by" is the sort field,
-    // "sort" the direction. Legacy filter param names are mapped to canonical.
-    let mut params: Vec<(&str, String)> = vec![
-        ("limit", limit_str),
-        ("order_by", order_by.to_string()),
-        ("sort", sort_dir.to_string()),
-    ];
-    if let Some(v) = volume_24h_min {
-        params.push(("volume_usd_24h_min", v.to_string()));
-    }
-    if let Some(v) = volume_24h_max {
-        params.push(("volume_usd_24h_max", v.to_string()));
-    }
-    if let Some(v) = volume_7d_min {
-        params.push(("volume_usd_7d_min", v.to_string()));
-    }
-    if let Some(v) = volume_7d_max {
-        params.push(("volume_usd_7d_max", v.to_string()));
-    }
-    if let Some(v) = liquidity_usd_min {
-        params.push(("liquidity_usd_min", v.to_string()));
-    }
-    if let Some(v) = liquidity_usd_max {
-        params.push(("liquidity_usd_max", v.to_string()));
-    }
-    if let Some(v) = txns_24h_min {
-        params.push(("txns_24h_min", v.to_string()));
-    }
-    params.extend(price_change_params(&price_change));
-    if let Some(v) = created_after {
-        params.push(("created_after", v.to_string()));
-    }
-    if let Some(v) = created_before {
-        params.push(("created_before", v.to_string()));
-    }
-
-    let param_refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-    let resp: PoolSearchResponse = client
-        .dexpaprika_get(&format!("/networks/{network}/pools/search"), &param_refs)
-        .await?;
-
-    match output {
-        OutputFormat::Table => {
-            crate::output::pools::print_pool_filter_table(&resp.results);
-            crate::output::print_more_results_hint(resp.has_next_page, resp.next_cursor.as_deref());
-        }
-        OutputFormat::Json => {
-            crate::output::print_json_wrapped(
-                &resp,
-                crate::output::ResponseMeta::dexpaprika(&format!(
-                    "/networks/{network}/pools/search"
-                )),
-                raw,
-            )?;
-        }
-    }
-    Ok(())
+    // "sort" the direction. The builder maps legacy names to canonical.
+    let query = PoolSearchQuery::new()
+        .limit(limit)
+        .order_by(sort_by)
+        .sort(sort_dir)
+        .volume_24h_min(volume_24h_min)
+        .volume_24h_max(volume_24h_max)
+        .volume_7d_min(volume_7d_min)
+        .volume_7d_max(volume_7d_max)
+        .liquidity_usd_min(liquidity_usd_min)
+        .liquidity_usd_max(liquidity_usd_max)
+        .txns_24h_min(txns_24h_min)
+        .price_change(price_change)
+        .created_after(created_after)
+        .created_before(created_before)
+        .cursor(cursor);
+    search_pools(
+        client,
+        networks,
+        query,
+        pages,
+        crate::output::pools::print_pool_filter_table,
+        view,
+        output,
+        raw,
+    )
+    .await
 }
 
 pub async fn execute_pools(
     client: &ApiClient,
-    network: &str,
+    networks: &NetworkArgs,
     limit: usize,
-    _page: usize,
+    cursor: Option<&str>,
+    pages: PageArgs,
     order_by: &str,
     sort: &str,
+    with_sparkline: bool,
+    view: &View,
     output: OutputFormat,
     raw: bool,
 ) -> Result<()> {
-    let limit_str = limit.to_string();
-    let order_by = crate::commands::search_mapping::map_pool_sort_field(order_by);
-    // Search is cursor-paginated: drop "page", map the sort field to canonical.
-    let resp: PoolSearchResponse = client
-        .dexpaprika_get(
-            &format!("/networks/{network}/pools/search"),
-            &[
-                ("limit", limit_str.as_str()),
-                ("order_by", order_by),
-                ("sort", sort),
-            ],
-        )
-        .await?;
-    match output {
-        OutputFormat::Table => {
-            crate::output::pools::print_pool_search_table(&resp.results);
-            crate::output::print_more_results_hint(resp.has_next_page, resp.next_cursor.as_deref());
-        }
-        OutputFormat::Json => {
-            crate::output::print_json_wrapped(
-                &resp,
-                crate::output::ResponseMeta::dexpaprika(&format!(
-                    "/networks/{network}/pools/search"
-                )),
-                raw,
-            )?;
-        }
+    // Search is cursor-paginated: no page number, map the sort field to canonical.
+    let query = PoolSearchQuery::new()
+        .limit(limit)
+        .order_by(order_by)
+        .sort(sort)
+        .cursor(cursor);
+    if with_sparkline {
+        let networks = networks.resolve(client).await?;
+        sparkline::ensure_one_table(output, pages, networks.len())?;
+        let network = &networks[0];
+        warn_sort_fallback(query.order_by.as_deref().and_then(pool_sort_fallback));
+        let mut resp = client.pool_search(network, &query).await?;
+        view.apply(&mut resp.results)?;
+        let trends = sparkline::pool_trends(client, network, &resp.results).await;
+        let table = view.table(&resp.results, crate::output::pools::pool_search_table)?;
+        sparkline::print_with_trends(table, trends);
+        crate::output::print_list_footer(resp.has_next_page, resp.next_cursor.as_deref());
+        return Ok(());
     }
-    Ok(())
+    search_pools(
+        client,
+        networks,
+        query,
+        pages,
+        crate::output::pools::print_pool_search_table,
+        view,
+        output,
+        raw,
+    )
+    .await
 }
 
 pub async fn execute_pool_detail(
@@ -318,24 +227,14 @@ pub async fn execute_pool_detail(
     output: OutputFormat,
     raw: bool,
 ) -> Result<()> {
-    let mut params: Vec<(&str, &str)> = Vec::new();
-    if inversed {
-        params.push(("inversed", "true"));
-    }
-    let pool: PoolDetail = client
-        .dexpaprika_get(
-            &format!("/networks/{network}/pools/{pool_address}"),
-            &params,
-        )
-        .await?;
+    let pool = client.pool(network, pool_address, inversed).await?;
+    let meta = ResponseMeta::dexpaprika(&format!("/pool/{network}/{pool_address}"));
     match output {
         OutputFormat::Table => crate::output::pools::print_pool_detail(&pool),
-        OutputFormat::Json => {
-            crate::output::print_json_wrapped(
-                &pool,
-                crate::output::ResponseMeta::dexpaprika(&format!("/pool/{network}/{pool_address}")),
-                raw,
-            )?;
+        OutputFormat::Json => crate::output::print_json_wrapped(&pool, meta, raw)?,
+        OutputFormat::Ndjson => crate::output::ndjson::print_rows(&[pool], meta, raw)?,
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&[pool], output)?
         }
     }
     Ok(())
@@ -357,41 +256,30 @@ pub async fn execute_dex_pools(
     dex: &str,
     limit: usize,
     cursor: Option<&str>,
+    pages: PageArgs,
     order_by: &str,
     sort: &str,
+    view: &View,
     output: OutputFormat,
     raw: bool,
 ) -> Result<()> {
-    let limit_str = limit.to_string();
-    let order_by = crate::commands::search_mapping::map_pool_sort_field(order_by);
-    let mut params: Vec<(&str, &str)> = vec![
-        ("limit", limit_str.as_str()),
-        ("dex_name", dex),
-        ("order_by", order_by),
-        ("sort", sort),
-    ];
-    if let Some(c) = cursor {
-        params.push(("cursor", c));
-    }
-    let resp: PoolSearchResponse = client
-        .dexpaprika_get(&format!("/networks/{network}/pools/search"), &params)
-        .await?;
-    match output {
-        OutputFormat::Table => {
-            crate::output::pools::print_pool_search_table(&resp.results);
-            crate::output::print_more_results_hint(resp.has_next_page, resp.next_cursor.as_deref());
-        }
-        OutputFormat::Json => {
-            crate::output::print_json_wrapped(
-                &resp,
-                crate::output::ResponseMeta::dexpaprika(&format!(
-                    "/networks/{network}/pools/search"
-                )),
-                raw,
-            )?;
-        }
-    }
-    Ok(())
+    let query = PoolSearchQuery::new()
+        .limit(limit)
+        .dex_name(dex)
+        .order_by(order_by)
+        .sort(sort)
+        .cursor(cursor);
+    list_pool_search(
+        client,
+        network,
+        query,
+        pages,
+        crate::output::pools::print_pool_search_table,
+        view,
+        output,
+        raw,
+    )
+    .await
 }
 
 pub async fn execute_transactions(
@@ -400,41 +288,101 @@ pub async fn execute_transactions(
     pool_address: &str,
     limit: usize,
     cursor: Option<&str>,
+    pages: PageArgs,
     from: Option<i64>,
     to: Option<i64>,
+    follow: Option<u64>,
+    aggregate: Option<AggregateBy>,
+    trades: &TradeFilter,
+    view: &View,
     output: OutputFormat,
     raw: bool,
 ) -> Result<()> {
-    let limit_str = limit.to_string();
-    let from_str = from.map(|f| f.to_string());
-    let to_str = to.map(|t| t.to_string());
-    let mut params: Vec<(&str, &str)> = vec![("limit", &limit_str)];
-    if let Some(c) = cursor {
-        params.push(("cursor", c));
-    }
-    if let Some(ref f) = from_str {
-        params.push(("from", f));
+    if let Some(every) = follow {
+        follow::ensure_can_follow(view)?;
+        return follow::follow_transactions(
+            client,
+            network,
+            pool_address,
+            limit,
+            every,
+            trades,
+            view,
+            output,
+            raw,
+        )
+        .await;
     }
-    if let Some(ref t) = to_str {
-        params.push(("to", t));
+    let query = TransactionsQuery::new()
+        .limit(limit)
+        .cursor(cursor)
+        .from(from)
+        .to(to);
+    if let Some(AggregateBy::Address) = aggregate {
+        return participants::execute_aggregate(
+            client,
+            network,
+            pool_address,
+            query,
+            pages,
+            trades,
+            view,
+            output,
+            raw,
+        )
+        .await;
     }
-    let resp: TransactionsResponse = client
-        .dexpaprika_get(
-            &format!("/networks/{network}/pools/{pool_address}/transactions"),
-            &params,
+    let meta = ResponseMeta::dexpaprika(&format!("/pool/{network}/{pool_address}/transactions"));
+    if pages.crawling() {
+        let writer = RowWriter::new(
+            output,
+            raw,
+            meta,
+            crate::output::pools::print_transactions_table,
+        )
+        .with_view(view.clone());
+        return paging::crawl(
+            pages,
+            query.cursor.clone(),
+            |cursor| {
+                let query = query.clone().cursor(cursor.as_deref());
+                async move {
+                    trades.page(
+                        client
+                            .pool_transactions(network, pool_address, &query)
+                            .await?,
+                    )
+                }
+            },
+            writer,
         )
+        .await;
+    }
+
+    let resp = client
+        .pool_transactions(network, pool_address, &query)
         .await?;
-    let txs = resp.transactions;
+    let next_cursor = CursorPage::next_cursor(&resp).map(str::to_string);
+    let has_next_page = Some(next_cursor.is_some());
+    let mut txs = trades.trades(resp.transactions)?;
+    view.apply(&mut txs)?;
     match output {
-        OutputFormat::Table => crate::output::pools::print_transactions_table(&txs),
+        OutputFormat::Table => {
+            view.print_table(&txs, crate::output::pools::print_transactions_table)?;
+            crate::output::print_list_footer(has_next_page, next_cursor.as_deref());
+        }
         OutputFormat::Json => {
-            crate::output::print_json_wrapped(
-                &txs,
-                crate::output::ResponseMeta::dexpaprika(&format!(
-                    "/pool/{network}/{pool_address}/transactions"
-                )),
-                raw,
-            )?;
+            crate::output::print_json_page(&view.project(&txs)?, meta, next_cursor.as_deref(), raw)?
+        }
+        OutputFormat::Ndjson => crate::output::ndjson::print_page(
+            &view.project(&txs)?,
+            meta,
+            next_cursor.as_deref(),
+            raw,
+        )?,
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&view.project(&txs)?, output)?;
+            crate::output::eprint_more_results_hint(has_next_page, next_cursor.as_deref());
         }
     }
     Ok(())
@@ -447,209 +395,172 @@ pub async fn execute_ohlcv(
     start: &str,
     end: Option<&str>,
     interval: &str,
-    limit: usize,
+    limit: Option<usize>,
     inversed: bool,
+    chart: bool,
+    indicators: &[Indicator],
+    view: &View,
     output: OutputFormat,
     raw: bool,
 ) -> Result<()> {
+    if chart && !matches!(output, OutputFormat::Table) {
+        anyhow::bail!("--chart draws on the terminal. Drop --output, or --query, to see it.");
+    }
     // Validate start date format
     let is_unix = start.chars().all(|c| c.is_ascii_digit());
     let is_date =
         start.len() == 10 && start.chars().nth(4) == Some('-') && start.chars().nth(7) == Some('-');
     let is_rfc3339 = start.contains('T');
     if !is_unix && !is_date && !is_rfc3339 {
-        anyhow::bail!(
-            "Invalid --start format: \"{start}\". Use yyyy-mm-dd, unix timestamp, or RFC3339."
-        );
+        return Err(ohlcv::invalid_time("--start", start));
     }
 
-    let limit_str = limit.to_string();
-    let mut params: Vec<(&str, &str)> = vec![
-        ("start", start),
-        ("interval", interval),
-        ("limit", &limit_str),
-    ];
-    if let Some(e) = end {
-        params.push(("end", e));
-    }
-    if inversed {
-        params.push(("inversed", "true"));
+    let from = ohlcv::parse_time(start);
+    let step = ohlcv::interval_seconds(interval);
+    let range = match (end, from, step) {
+        (Some(end), Some(from), Some(step)) => {
+            let to = ohlcv::parse_time_flag("--end", end)?;
+            if to < from {
+                anyhow::bail!("--end {end} is before --start {start}.");
+            }
+            Some(ohlcv::points_between(from, to, step))
+        }
+        _ => None,
+    };
+    // Without --end, --limit candles from --start; with it, the whole range
+    // unless --limit stops short of it.
+    let points = match (limit, range) {
+        (Some(limit), Some(range)) => limit.min(range),
+        (limit, range) => limit.or(range).unwrap_or(DEFAULT_OHLCV_LIMIT),
+    };
+
+    let query = OhlcvQuery::new(start)
+        .end(end)
+        .interval(interval)
+        .inversed(inversed);
+    let (mut data, gaps) = if points > ohlcv::MAX_POINTS {
+        let (Some(from), Some(step)) = (from, step) else {
+            anyhow::bail!(
+                "Fetching more than {} candles splits the range into requests, which needs \
+                 an --interval such as 1h or 24h and a --start of yyyy-mm-dd, unix timestamp, \
+                 or RFC3339 with an offset.",
+                ohlcv::MAX_POINTS
+            );
+        };
+        let series =
+            ohlcv::fetch_range(client, network, pool_address, &query, from, step, points).await?;
+        (series.candles, series.gaps)
+    } else {
+        let data = client
+            .pool_ohlcv(network, pool_address, &query.limit(points))
+            .await?;
+        let gaps = match (from, step) {
+            (Some(from), Some(step)) => {
+                let first = ohlcv::align(from, step);
+                ohlcv::gaps(&data, first, ohlcv::last_open(first, step, points), step)
+            }
+            _ => Vec::new(),
+        };
+        (data, gaps)
+    };
+    warn_gaps(&gaps);
+    let meta =
+        ResponseMeta::dexpaprika(&format!("/pool/{network}/{pool_address}/ohlcv")).with_gaps(&gaps);
+    if !indicators.is_empty() {
+        let mut rows = with_indicators(data, indicators);
+        view.apply(&mut rows)?;
+        match output {
+            OutputFormat::Table => {
+                let table = view.table(&rows, crate::output::pools::indicator_table)?;
+                println!("{}", crate::output::table_style::render(table));
+                crate::output::print_dexpaprika_footer();
+            }
+            OutputFormat::Json => {
+                crate::output::print_json_wrapped(&view.project(&rows)?, meta, raw)?
+            }
+            OutputFormat::Ndjson => {
+                crate::output::ndjson::print_rows(&view.project(&rows)?, meta, raw)?
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                crate::output::delimited::print_delimited(&view.project(&rows)?, output)?
+            }
+        }
+        return Ok(());
     }
-
-    let data: Vec<PoolOhlcv> = client
-        .dexpaprika_get(
-            &format!("/networks/{network}/pools/{pool_address}/ohlcv"),
-            &params,
-        )
-        .await?;
+    view.apply(&mut data)?;
     match output {
-        OutputFormat::Table => crate::output::pools::print_pool_ohlcv_table(&data),
-        OutputFormat::Json => {
-            crate::output::print_json_wrapped(
-                &data,
-                crate::output::ResponseMeta::dexpaprika(&format!(
-                    "/pool/{network}/{pool_address}/ohlcv"
-                )),
-                raw,
-            )?;
+        OutputFormat::Table if chart => {
+            let width = crate::output::chart::terminal_width();
+            println!("{}", crate::output::chart::candlestick(&data, width));
+            crate::output::print_dexpaprika_footer();
+        }
+        OutputFormat::Table => {
+            view.print_table(&data, crate::output::pools::print_pool_ohlcv_table)?
+        }
+        OutputFormat::Json => crate::output::print_json_wrapped(&view.project(&data)?, meta, raw)?,
+        OutputFormat::Ndjson => {
+            crate::output::ndjson::print_rows(&view.project(&data)?, meta, raw)?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&view.project(&data)?, output)?
         }
     }
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    /// One result object, copied verbatim from a live
-    /// `/networks/ethereum/pools/search?limit=2&dex_name=curve` response
-    /// captured on 2026-08-05. Field names come off the wire, not from docs.
-    const LIVE_DEX_POOLS_SAMPLE: &str = r#"{
-      "results": [
-        {
-          "id": "0x4f493b7de8aac7d55f71853688b1f7c8f0243c85",
-          "dex_id": "curve",
-          "dex_name": "Curve",
-          "chain": "ethereum",
-          "volume_usd_24h": 15883391.558251368,
-          "created_at": "2025-01-25T17:20:47Z",
-          "created_at_block_number": 21702976,
-          "transactions_24h": 289,
-          "price_usd": 0.9995787501356217,
-          "price_change_percentage_5m": null,
-          "price_change_percentage_1h": 0.02422482089565938,
-          "price_change_percentage_6h": 0.009802157529374174,
-          "price_change_percentage_24h": 0.007018797950998323,
-          "fee": null,
-          "volume_usd_7d": 31781851.73428885,
-          "volume_usd_30d": 136889876.39037386,
-          "liquidity_usd": 7407910.088430515,
-          "tokens": [
-            {"id": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", "chain": "ethereum", "has_image": true}
-          ]
-        }
-      ],
-      "has_next_page": true,
-      "next_cursor": "eyJjaGFpbiI6ImV0aGVyZXVtIn0",
-      "query": {"network": "ethereum", "limit": 2, "dex_name": "curve", "order_by": "volume_usd_24h"}
-    }"#;
-
-    #[test]
-    fn dex_pools_payload_decodes_from_the_search_envelope() {
-        let resp: PoolSearchResponse =
-            serde_json::from_str(LIVE_DEX_POOLS_SAMPLE).expect("live sample must decode");
-
-        // The envelope is results/has_next_page/next_cursor, not pools/page_info.
-        assert_eq!(resp.results.len(), 1);
-        assert_eq!(resp.has_next_page, Some(true));
-        assert_eq!(
-            resp.next_cursor.as_deref(),
-            Some("eyJjaGFpbiI6ImV0aGVyZXVtIn0")
-        );
-
-        // The 24h volume field is volume_usd_24h. A bare volume_usd would decode
-        // as None here, which is exactly the silent breakage this test guards.
-        let pool = &resp.results[0];
-        assert_eq!(pool.volume_usd_24h, Some(15883391.558251368));
-        assert_eq!(pool.dex_id.as_deref(), Some("curve"));
-        assert_eq!(
-            pool.id.as_deref(),
-            Some("0x4f493b7de8aac7d55f71853688b1f7c8f0243c85")
-        );
-        assert_eq!(pool.transactions_24h, Some(289));
+/// Name the stretches of the series with no candles, which a chart or an
+/// indicator would otherwise run straight across.
+fn warn_gaps(gaps: &[Gap]) {
+    const LISTED: usize = 3;
+    if gaps.is_empty() {
+        return;
     }
-
-    #[test]
-    fn dex_pools_default_sort_maps_to_the_canonical_search_field() {
-        // The dex-pools --order-by default is still the legacy "volume_usd";
-        // pools/search rejects it, so it has to be mapped before it goes out.
-        assert_eq!(
-            crate::commands::search_mapping::map_pool_sort_field("volume_usd"),
-            "volume_usd_24h"
-        );
+    let missing: usize = gaps.iter().map(|g| g.missing).sum();
+    let mut listed: Vec<String> = gaps
+        .iter()
+        .take(LISTED)
+        .map(|g| format!("{} to {} ({})", g.from, g.to, g.missing))
+        .collect();
+    if gaps.len() > LISTED {
+        listed.push(format!("and {} more", gaps.len() - LISTED));
     }
+    eprintln!(
+        "Warning: {missing} candle(s) missing in {} gap(s): {}",
+        gaps.len(),
+        listed.join(", ")
+    );
+}
 
-    /// Every bound set, each to a different value, so a transposed pair shows up
-    /// as a wrong value next to the right name.
-    fn all_bounds() -> PriceChangeBounds {
-        PriceChangeBounds {
-            price_change_24h_min: Some(1.0),
-            price_change_24h_max: Some(2.0),
-            price_change_6h_min: Some(3.0),
-            price_change_6h_max: Some(4.0),
-            price_change_1h_min: Some(5.0),
-            price_change_1h_max: Some(6.0),
-            price_change_5m_min: Some(7.0),
-            price_change_5m_max: Some(8.0),
+/// The candles, oldest first, each with the value of every indicator at it.
+/// An indicator that needs more candles than there are is all `None`, and
+/// says so on stderr.
+fn with_indicators(mut data: Vec<PoolOhlcv>, indicators: &[Indicator]) -> Vec<IndicatorCandle> {
+    data.sort_by(|a, b| a.time_open.cmp(&b.time_open));
+    for indicator in indicators {
+        if data.len() < indicator.min_candles() {
+            eprintln!(
+                "Warning: {indicator} needs {} candles and there are only {}, so {} stays empty. \
+                 Raise --limit or move --start back.",
+                indicator.min_candles(),
+                data.len(),
+                indicator.columns().join(", ")
+            );
         }
     }
-
-    #[test]
-    fn price_change_params_carry_the_names_the_api_expects() {
-        let params = price_change_params(&all_bounds());
-        let pairs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        assert_eq!(
-            pairs,
-            vec![
-                ("price_change_percentage_24h_min", "1"),
-                ("price_change_percentage_24h_max", "2"),
-                ("price_change_percentage_6h_min", "3"),
-                ("price_change_percentage_6h_max", "4"),
-                ("price_change_percentage_1h_min", "5"),
-                ("price_change_percentage_1h_max", "6"),
-                ("price_change_percentage_5m_min", "7"),
-                ("price_change_percentage_5m_max", "8"),
-            ]
-        );
-    }
-
-    /// The same eight names one layer further out, read off the URL reqwest
-    /// would actually send. Verified against api.dexpaprika.com on 2026-08-07:
-    /// each of these bounds changes the result set, and a misspelling of any of
-    /// them returns the unfiltered baseline at HTTP 200.
-    #[test]
-    fn price_change_bounds_reach_the_query_string() {
-        let params = price_change_params(&all_bounds());
-        let refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let request = reqwest::Client::new()
-            .get("https://api.dexpaprika.com/networks/ethereum/pools/search")
-            .query(&refs)
-            .build()
-            .expect("the request should build");
-
-        assert_eq!(
-            request.url().query().expect("a query string"),
-            "price_change_percentage_24h_min=1&price_change_percentage_24h_max=2\
-             &price_change_percentage_6h_min=3&price_change_percentage_6h_max=4\
-             &price_change_percentage_1h_min=5&price_change_percentage_1h_max=6\
-             &price_change_percentage_5m_min=7&price_change_percentage_5m_max=8"
-        );
-    }
-
-    #[test]
-    fn negative_bounds_survive_url_encoding() {
-        // "down 20% or more over 24h" has to leave as -20, not as %2D20 or 20.
-        let bounds = PriceChangeBounds {
-            price_change_24h_max: Some(-20.0),
-            price_change_5m_min: Some(-1.5),
-            ..PriceChangeBounds::default()
-        };
-        let params = price_change_params(&bounds);
-        let refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let request = reqwest::Client::new()
-            .get("https://api.dexpaprika.com/networks/ethereum/pools/search")
-            .query(&refs)
-            .build()
-            .expect("the request should build");
-
-        assert_eq!(
-            request.url().query().expect("a query string"),
-            "price_change_percentage_24h_max=-20&price_change_percentage_5m_min=-1.5"
-        );
-    }
-
-    #[test]
-    fn unset_bounds_send_nothing() {
-        assert!(price_change_params(&PriceChangeBounds::default()).is_empty());
-    }
+    let series: Vec<(String, Vec<Option<f64>>)> = indicators
+        .iter()
+        .flat_map(|i| i.columns().into_iter().zip(i.compute(&data)))
+        .collect();
+    data.into_iter()
+        .enumerate()
+        .map(|(at, candle)| IndicatorCandle {
+            candle,
+            values: IndicatorValues(
+                series
+                    .iter()
+                    .map(|(column, values)| (column.clone(), values[at]))
+                    .collect(),
+            ),
+        })
+        .collect()
 }
@@ -0,0 +1,140 @@
+//! `--sparkline` on `pools` and `top-tokens`: a trend column drawn from a
+//! small OHLCV fetch per row.
+//!
+//! That is one request per row (two for a token, whose trend comes from its
+//! deepest pool), so the column is limited to one page of one network and the
+//! table output it is drawn in.
+
+use std::future::Future;
+
+use anyhow::{bail, Result};
+use chrono::{Duration, Utc};
+use dexpaprika::models::{PoolSearchItem, TokenSearchItem};
+use dexpaprika::query::{OhlcvQuery, PoolSearchQuery};
+use dexpaprika::ApiClient;
+use futures::StreamExt;
+use tabled::Table;
+
+use crate::commands::paging::PageArgs;
+use crate::output::chart::sparkline;
+use crate::output::{push_column, table_style, OutputFormat};
+
+/// Header of the trend column: hourly closes over the last day.
+pub const COLUMN: &str = "Trend (24h)";
+
+/// Rows whose candles are fetched at once. The shared rate limiter still
+/// paces the requests; this only bounds the open connections.
+const MAX_CONCURRENT_ROWS: usize = 4;
+
+/// Refuse the combinations a per-row fetch can't serve.
+pub fn ensure_one_table(output: OutputFormat, pages: PageArgs, networks: usize) -> Result<()> {
+    if !matches!(output, OutputFormat::Table) {
+        bail!("--sparkline adds a table column. Drop --output, or --query, to see it.");
+    }
+    if pages.crawling() || networks > 1 {
+        bail!(
+            "--sparkline fetches candles for every row, so it draws one page of one network. \
+             Drop --all, --max-pages and --matches, and pick one network."
+        );
+    }
+    Ok(())
+}
+
+/// A trend for each pool, in order.
+pub async fn pool_trends(
+    client: &ApiClient,
+    network: &str,
+    pools: &[PoolSearchItem],
+) -> Vec<String> {
+    let ids: Vec<String> = pools
+        .iter()
+        .map(|p| p.id.clone().unwrap_or_default())
+        .collect();
+    collect(ids, |id| async move {
+        closes(client, network, &id, false).await
+    })
+    .await
+}
+
+/// A trend for each token, in order, from the pool with the most liquidity
+/// that holds it. The candles are priced in that pool's first token, so they
+/// are inverted when the token is the second.
+pub async fn token_trends(
+    client: &ApiClient,
+    network: &str,
+    tokens: &[TokenSearchItem],
+) -> Vec<String> {
+    let addresses: Vec<String> = tokens
+        .iter()
+        .map(|t| t.address.clone().unwrap_or_default())
+        .collect();
+    collect(addresses, |address| async move {
+        let query = PoolSearchQuery::new()
+            .token_address(address.as_str())
+            .order_by("liquidity_usd")
+            .limit(1);
+        let Some(pool) = client.pool_search(network, &query).await?.results.pop() else {
+            return Ok(Vec::new());
+        };
+        let first = pool
+            .tokens
+            .as_ref()
+            .and_then(|tokens| tokens.first())
+            .and_then(|t| t.id.as_deref());
+        let inversed = first.is_some_and(|id| !id.eq_ignore_ascii_case(&address));
+        closes(
+            client,
+            network,
+            pool.id.as_deref().unwrap_or_default(),
+            inversed,
+        )
+        .await
+    })
+    .await
+}
+
+/// The hourly closes of `pool` over the last day.
+async fn closes(client: &ApiClient, network: &str, pool: &str, inversed: bool) -> Result<Vec<f64>> {
+    let start = (Utc::now() - Duration::hours(24)).timestamp().to_string();
+    let query = OhlcvQuery::new(start)
+        .interval("1h")
+        .limit(24)
+        .inversed(inversed);
+    let mut candles = client.pool_ohlcv(network, pool, &query).await?;
+    candles.sort_by(|a, b| a.time_open.cmp(&b.time_open));
+    Ok(candles.iter().filter_map(|c| c.close).collect())
+}
+
+/// Print `table` with the trends as its last column.
+pub fn print_with_trends(table: Table, trends: Vec<String>) {
+    println!(
+        "{}",
+        table_style::render(push_column(table, COLUMN, trends))
+    );
+}
+
+/// Draw each series. A row whose fetch failed gets `-` rather than sinking the
+/// table; one warning on stderr says how many and why.
+async fn collect<F, Fut>(keys: Vec<String>, fetch: F) -> Vec<String>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<f64>>>,
+{
+    let results: Vec<Result<Vec<f64>>> = futures::stream::iter(keys)
+        .map(fetch)
+        .buffered(MAX_CONCURRENT_ROWS)
+        .collect()
+        .await;
+    let failed: Vec<&anyhow::Error> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+    if let Some(first) = failed.first() {
+        eprintln!(
+            "Warning: no trend for {} of {} rows: {first:#}",
+            failed.len(),
+            results.len()
+        );
+    }
+    results
+        .iter()
+        .map(|r| r.as_deref().map(sparkline).unwrap_or_else(|_| "-".into()))
+        .collect()
+}
@@ -2,8 +2,8 @@ use anyhow::Result;
 use serde::Serialize;
 use std::time::Instant;
 
-use crate::client::ApiClient;
 use crate::output::OutputFormat;
+use dexpaprika::client::ApiClient;
 
 #[derive(Debug, Serialize)]
 pub struct StatusResult {
@@ -17,8 +17,9 @@ pub struct ApiStatus {
 }
 
 pub async fn execute_status(client: &ApiClient, output: OutputFormat, raw: bool) -> Result<()> {
+    // Uncached: a health check answered from disk would say nothing about the API.
     let dp_start = Instant::now();
-    let dp_result: Result<serde_json::Value> = client.dexpaprika_get("/stats", &[]).await;
+    let dp_result: Result<serde_json::Value> = client.dexpaprika_get_uncached("/stats", &[]).await;
     let dp_time = dp_start.elapsed().as_millis();
 
     let result = StatusResult {
@@ -41,6 +42,14 @@ pub async fn execute_status(client: &ApiClient, output: OutputFormat, raw: bool)
                 raw,
             )?;
         }
+        OutputFormat::Ndjson => crate::output::ndjson::print_rows(
+            &[&result],
+            crate::output::ResponseMeta::dexpaprika("/status"),
+            raw,
+        )?,
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&[result], output)?
+        }
     }
     Ok(())
 }
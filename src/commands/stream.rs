@@ -1,57 +1,11 @@
 use anyhow::{bail, Context, Result};
+use dexpaprika::stream::{EventStream, PriceSubscription, StreamEvent, MAX_SUBSCRIPTIONS_PER_POST};
+use dexpaprika::ApiClient;
 use futures::StreamExt;
-use reqwest_eventsource::{Event, EventSource};
-use serde::{Deserialize, Serialize};
 
-use crate::client::ApiClient;
+use crate::output::delimited::Delimited;
 use crate::output::OutputFormat;
 
-/// Raw `token_price` SSE payload. Wire field names are mapped to readable
-/// CLI struct names via serde renames.
-#[derive(Debug, Deserialize)]
-pub struct RawStreamEvent {
-    pub address: String,
-    pub chain: String,
-    #[serde(rename = "price")]
-    pub price_usd: String,
-    pub timestamp: i64,
-    #[serde(rename = "timestamp_price")]
-    pub price_timestamp: i64,
-}
-
-/// CLI-facing event with readable field names.
-#[derive(Debug, Serialize)]
-pub struct StreamEvent {
-    pub address: String,
-    pub chain: String,
-    pub price_usd: String,
-    pub timestamp: i64,
-    pub price_timestamp: i64,
-}
-
-impl From<RawStreamEvent> for StreamEvent {
-    fn from(raw: RawStreamEvent) -> Self {
-        Self {
-            address: raw.address,
-            chain: raw.chain,
-            price_usd: raw.price_usd,
-            timestamp: raw.timestamp,
-            price_timestamp: raw.price_timestamp,
-        }
-    }
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct StreamToken {
-    chain: String,
-    address: String,
-    method: String,
-}
-
-/// Hard cap on subscriptions in a POST body. The server enforces the same
-/// value and rejects oversized batches with HTTP 400.
-const MAX_SUBSCRIPTIONS_PER_POST: usize = 25;
-
 pub async fn execute(
     client: &ApiClient,
     network: Option<&str>,
@@ -86,53 +40,8 @@ async fn stream_single(
     limit: Option<usize>,
     output: OutputFormat,
 ) -> Result<()> {
-    let url = format!(
-        "https://streaming.dexpaprika.com/sse/prices?method=token_price&chain={network}&address={address}"
-    );
-
-    // EventSource::get cannot carry headers, so build the request first and let
-    // the client attach the key when one is configured.
-    let mut es = EventSource::new(client.authorize(client.http_client().get(&url)))?;
-    let mut count = 0usize;
-
-    loop {
-        tokio::select! {
-            event = es.next() => {
-                match event {
-                    Some(Ok(Event::Message(msg))) => {
-                        // Skip ping/warning/error events; only handle token_price.
-                        // EventSource has already parsed the event type for us.
-                        if msg.event != "token_price" {
-                            continue;
-                        }
-                        match serde_json::from_str::<RawStreamEvent>(&msg.data) {
-                            Ok(raw) => {
-                                let data = StreamEvent::from(raw);
-                                crate::output::stream::print_stream_event(&data, output);
-                                count += 1;
-                                if let Some(lim) = limit {
-                                    if count >= lim { break; }
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("Parse error: {e}");
-                            }
-                        }
-                    }
-                    Some(Ok(Event::Open)) => {}
-                    Some(Err(e)) => {
-                        bail!("Stream error: {e}");
-                    }
-                    None => break,
-                }
-            }
-            _ = tokio::signal::ctrl_c() => {
-                break;
-            }
-        }
-    }
-
-    Ok(())
+    let events = client.price_stream(network, address).await?;
+    print_token_prices(events, limit, output).await
 }
 
 async fn stream_multi(
@@ -157,20 +66,13 @@ async fn stream_multi(
         bail!("Token list in {file_path} is empty. Add at least one token.");
     }
 
-    let tokens: Vec<StreamToken> = user_tokens
+    let tokens: Vec<PriceSubscription> = user_tokens
         .iter()
-        .map(|t| StreamToken {
-            chain: t
-                .get("chain")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string(),
-            address: t
-                .get("address")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string(),
-            method: "token_price".to_string(),
+        .map(|t| {
+            PriceSubscription::new(
+                t.get("chain").and_then(|v| v.as_str()).unwrap_or(""),
+                t.get("address").and_then(|v| v.as_str()).unwrap_or(""),
+            )
         })
         .collect();
 
@@ -192,87 +94,69 @@ async fn stream_multi(
         );
     }
 
-    let body = serde_json::to_string(&tokens)?;
-
-    let resp = client
-        .authorize(
-            client
-                .http_client()
-                .post("https://streaming.dexpaprika.com/sse/prices")
-                .header("Accept", "text/event-stream")
-                .header("Content-Type", "application/json"),
-        )
-        .body(body)
-        .send()
-        .await?;
-
-    if !resp.status().is_success() {
-        let status = resp.status();
-        let body = resp.text().await.unwrap_or_default();
-        bail!("Stream POST error {status}: {body}");
-    }
+    let events = client.price_stream_batch(&tokens).await?;
+    print_token_prices(events, limit, output).await
+}
 
-    let mut stream = resp.bytes_stream();
+async fn print_token_prices(
+    events: EventStream<StreamEvent>,
+    limit: Option<usize>,
+    output: OutputFormat,
+) -> Result<()> {
     let mut count = 0usize;
-    let mut buffer = String::new();
+    let mut csv = Delimited::new(output);
+    for_each_event(events, |event| {
+        crate::output::stream::print_stream_event(&event, output, &mut csv)?;
+        count += 1;
+        Ok(limit.is_none_or(|lim| count < lim))
+    })
+    .await
+}
 
+/// Feed each event on `events` to `on_event` until the stream ends, Ctrl+C,
+/// or `on_event` returns false or an error.
+pub(crate) async fn for_each_event<T>(
+    mut events: EventStream<T>,
+    mut on_event: impl FnMut(T) -> Result<bool>,
+) -> Result<()> {
     loop {
         tokio::select! {
-            chunk = stream.next() => {
-                match chunk {
-                    Some(Ok(bytes)) => {
-                        buffer.push_str(&String::from_utf8_lossy(&bytes));
-                        // SSE messages are separated by a blank line. Buffer one
-                        // message at a time, then dispatch on the parsed event
-                        // type. Both `event:`/`data:` line orderings are valid
-                        // and the server has emitted either during rollout, so
-                        // a naive line-by-line parser would silently mis-dispatch.
-                        while let Some(boundary) = buffer.find("\n\n") {
-                            let message = buffer[..boundary].to_string();
-                            buffer.drain(..boundary + 2);
-                            if let Some(event) = parse_token_price_message(&message) {
-                                crate::output::stream::print_stream_event(&event, output);
-                                count += 1;
-                                if let Some(lim) = limit {
-                                    if count >= lim { return Ok(()); }
-                                }
-                            }
+            event = events.next() => {
+                match event {
+                    Some(Ok(event)) => {
+                        if !on_event(event)? {
+                            return Ok(());
                         }
                     }
-                    Some(Err(e)) => {
-                        bail!("Stream error: {e}");
-                    }
-                    None => break,
+                    Some(Err(e)) => return Err(e),
+                    None => return Ok(()),
                 }
             }
             _ = tokio::signal::ctrl_c() => {
-                break;
+                return Ok(());
             }
         }
     }
-
-    Ok(())
 }
 
-/// Parse one buffered SSE message into a `StreamEvent`. Returns `None` for
-/// non-`token_price` events (ping, warning, error) or malformed payloads.
-fn parse_token_price_message(message: &str) -> Option<StreamEvent> {
-    let mut event_name: Option<&str> = None;
-    let mut data: Option<&str> = None;
-
-    for line in message.lines() {
-        if let Some(rest) = line.strip_prefix("event:") {
-            event_name = Some(rest.trim());
-        } else if let Some(rest) = line.strip_prefix("data:") {
-            data = Some(rest.trim_start());
-        }
-    }
-
-    if event_name != Some("token_price") {
-        return None;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn a_failed_write_ends_the_stream_with_its_error() {
+        let events: EventStream<u32> = Box::pin(futures::stream::iter((1..=5).map(Ok)));
+        let mut seen = Vec::new();
+        let err = for_each_event(events, |event| {
+            seen.push(event);
+            if event == 2 {
+                anyhow::bail!("Broken pipe");
+            }
+            Ok(true)
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Broken pipe");
+        assert_eq!(seen, [1, 2]);
     }
-    let data = data?;
-    serde_json::from_str::<RawStreamEvent>(data)
-        .ok()
-        .map(StreamEvent::from)
 }
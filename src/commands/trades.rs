@@ -0,0 +1,172 @@
+//! `--min-usd`, `--max-usd`, `--side` and `--base` for `transactions`.
+//!
+//! The API reports each swap as two signed amounts, two volumes and two USD
+//! prices. Counted on one token of the pair, the base, that is a side and a
+//! USD value: a swap that took the base out of the pool bought it, and its
+//! value is the base volume at the base price. Both become fields of the row,
+//! `side` and `usd_value`, so `--where`, `--fields` and `--sort-local` reach
+//! them too.
+
+use anyhow::{bail, Result};
+use dexpaprika::models::{PoolTransaction, TradeSide, TransactionsResponse};
+use dexpaprika::pages::CursorPage;
+
+use crate::output::pools::Trade;
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum SideArg {
+    Buy,
+    Sell,
+}
+
+impl From<SideArg> for TradeSide {
+    fn from(side: SideArg) -> Self {
+        match side {
+            SideArg::Buy => TradeSide::Buy,
+            SideArg::Sell => TradeSide::Sell,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct TradeArgs {
+    /// Keep only swaps worth at least this much, in USD on the --base side
+    #[arg(long, value_name = "USD")]
+    pub min_usd: Option<f64>,
+
+    /// Keep only swaps worth at most this much, in USD on the --base side
+    #[arg(long, value_name = "USD")]
+    pub max_usd: Option<f64>,
+
+    /// Keep only swaps that bought, or sold, the --base token
+    #[arg(long, value_enum)]
+    pub side: Option<SideArg>,
+
+    /// The token that sides and USD values are counted on: 0, 1, or the
+    /// symbol or address of one of the pool's tokens
+    #[arg(long, value_name = "TOKEN", default_value = "0")]
+    pub base: String,
+}
+
+impl TradeArgs {
+    pub fn resolve(self) -> Result<TradeFilter> {
+        if let (Some(min), Some(max)) = (self.min_usd, self.max_usd) {
+            if min > max {
+                bail!("--min-usd {min} is above --max-usd {max}.");
+            }
+        }
+        Ok(TradeFilter {
+            min_usd: self.min_usd,
+            max_usd: self.max_usd,
+            side: self.side.map(TradeSide::from),
+            base: self.base,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TradeFilter {
+    min_usd: Option<f64>,
+    max_usd: Option<f64>,
+    side: Option<TradeSide>,
+    base: String,
+}
+
+impl TradeFilter {
+    /// Price `txs` on the base token and keep the ones within the bounds. A
+    /// swap without a USD value or a side fails any bound on it.
+    pub fn trades(&self, txs: Vec<PoolTransaction>) -> Result<Vec<Trade>> {
+        let token = resolve_base(&txs, &self.base)?;
+        let mut trades: Vec<Trade> = txs
+            .into_iter()
+            .map(|tx| Trade {
+                side: token.and_then(|t| tx.side(t)),
+                usd_value: token.and_then(|t| tx.usd_value(t)),
+                tx,
+            })
+            .collect();
+        trades.retain(|trade| self.accepts(trade));
+        Ok(trades)
+    }
+
+    fn accepts(&self, trade: &Trade) -> bool {
+        let value = trade.usd_value;
+        self.min_usd
+            .is_none_or(|min| value.is_some_and(|v| v >= min))
+            && self
+                .max_usd
+                .is_none_or(|max| value.is_some_and(|v| v <= max))
+            && self.side.is_none_or(|side| trade.side == Some(side))
+    }
+
+    /// One page of transactions as trades, for `paging::crawl`.
+    pub fn page(&self, page: TransactionsResponse) -> Result<TradePage> {
+        let next = CursorPage::next_cursor(&page).map(str::to_string);
+        Ok(TradePage {
+            trades: self.trades(page.transactions)?,
+            next,
+        })
+    }
+}
+
+/// Where `base`, a `--base` value, sits in the pair of `txs`, transactions
+/// of one pool. `None` when it names a token and none of `txs` names theirs.
+pub fn resolve_base(txs: &[PoolTransaction], base: &str) -> Result<Option<usize>> {
+    if let Some(token) = txs.iter().find_map(|tx| tx.token_index(base)) {
+        return Ok(Some(token));
+    }
+    let named = txs
+        .iter()
+        .find(|tx| tx.token_0_symbol.is_some() || tx.token_1_symbol.is_some());
+    match named {
+        Some(tx) => bail!(
+            "--base {base} is neither token of this pool ({}, {}).",
+            tx.token_0_symbol.as_deref().unwrap_or("?"),
+            tx.token_1_symbol.as_deref().unwrap_or("?")
+        ),
+        None => Ok(None),
+    }
+}
+
+/// A page of transactions, priced and filtered.
+pub struct TradePage {
+    trades: Vec<Trade>,
+    next: Option<String>,
+}
+
+impl CursorPage for TradePage {
+    type Item = Trade;
+
+    fn next_cursor(&self) -> Option<&str> {
+        self.next.as_deref()
+    }
+
+    fn into_items(self) -> Vec<Trade> {
+        self.trades
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_is_an_index_a_symbol_or_an_address() {
+        let txs: Vec<PoolTransaction> = vec![serde_json::from_value(serde_json::json!({
+            "token_0": "0xc02a",
+            "token_0_symbol": "WETH",
+            "token_1": "0xa0b8",
+            "token_1_symbol": "USDC",
+        }))
+        .unwrap()];
+        assert_eq!(resolve_base(&txs, "1").unwrap(), Some(1));
+        assert_eq!(resolve_base(&txs, "usdc").unwrap(), Some(1));
+        assert_eq!(resolve_base(&txs, "0xC02A").unwrap(), Some(0));
+        let err = resolve_base(&txs, "DAI").unwrap_err();
+        assert!(err.to_string().contains("(WETH, USDC)"), "{err}");
+        let unnamed: Vec<PoolTransaction> =
+            vec![serde_json::from_value(serde_json::json!({})).unwrap()];
+        assert_eq!(resolve_base(&unnamed, "DAI").unwrap(), None);
+        assert_eq!(resolve_base(&unnamed, "1").unwrap(), Some(1));
+    }
+}
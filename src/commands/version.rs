@@ -111,6 +111,14 @@ pub async fn execute(output: OutputFormat, raw: bool) -> Result<()> {
                 raw,
             )?;
         }
+        OutputFormat::Ndjson => crate::output::ndjson::print_rows(
+            &[&check],
+            crate::output::ResponseMeta::dexpaprika("/version"),
+            raw,
+        )?,
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            crate::output::delimited::print_delimited(&[check], output)?
+        }
     }
 
     Ok(())
@@ -0,0 +1,56 @@
+//! `--fields`, `--where` and `--sort-local` for the list commands, and the
+//! field presets saved in the config.
+
+use crate::output::filter::Filter;
+use crate::output::view::{LocalSort, View};
+
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct ViewArgs {
+    /// Fields to show, comma-separated and named as in --output json. Sets the
+    /// table columns and the keys kept in json, ndjson, csv and tsv. A dotted
+    /// path such as tokens.0.symbol reaches into a nested value
+    #[arg(long, value_name = "A,B,C", value_delimiter = ',')]
+    pub fields: Option<Vec<String>>,
+
+    /// Keep only the rows this expression accepts, checked here rather than by
+    /// the API, e.g. 'dex_id == "uniswap_v3" && liquidity_usd > 1e6'. See
+    /// the README for the operators
+    #[arg(long = "where", value_name = "EXPR", value_parser = Filter::parse)]
+    pub filter: Option<Filter>,
+
+    /// Re-sort the fetched rows on any field, after --where. Descending
+    /// unless :asc is given. A crawl prints nothing until its last page is in
+    #[arg(long, value_name = "FIELD[:asc|desc]", value_parser = LocalSort::parse)]
+    pub sort_local: Option<LocalSort>,
+}
+
+impl ViewArgs {
+    /// `--fields`, or else the preset saved for `command` in the config.
+    pub fn resolve(self, command: &str) -> View {
+        let fields = crate::config::resolve_fields(self.fields, command)
+            .map(|fields| {
+                fields
+                    .into_iter()
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|fields| !fields.is_empty());
+        View {
+            fields,
+            filter: self.filter,
+            sort: self.sort_local,
+        }
+    }
+}
+
+/// Say so when the API can't order by the requested sort field and falls back
+/// to its default, rather than print rows in an order nobody asked for.
+pub fn warn_sort_fallback(reason: Option<String>) {
+    if let Some(reason) = reason {
+        eprintln!(
+            "Warning: {reason}, so the rows come back by volume_usd_24h. \
+             --sort-local re-sorts them on any field they have."
+        );
+    }
+}
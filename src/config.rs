@@ -1,23 +1,73 @@
-//! Optional API key: where it comes from, and where it is stored.
+//! Optional API key and host overrides: where they come from, and where they
+//! are stored.
 //!
 //! Keyless is and stays the default. Everything here is inert until somebody
 //! sets a key, and a missing config file is a normal state rather than an error.
+//! The same goes for the hosts: with nothing configured the CLI talks to the
+//! public REST and streaming endpoints.
 //!
 //! Shape ported from `coinpaprika-cli` so the two behave identically, with one
 //! addition: keys are sanitised, because a key carrying a newline would corrupt
 //! the outbound header rather than fail cleanly.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::output::table_style::TableStyle;
+
 /// Environment variable consulted when no `--api-key` is passed.
 pub const API_KEY_ENV_VAR: &str = "DEXPAPRIKA_API_KEY";
 
+/// Environment variable consulted when no `--api-url` is passed.
+pub const API_URL_ENV_VAR: &str = "DEXPAPRIKA_API_URL";
+
+/// Environment variable consulted when no `--streaming-url` is passed.
+pub const STREAMING_URL_ENV_VAR: &str = "DEXPAPRIKA_STREAMING_URL";
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Config {
     pub api_key: Option<String>,
+    /// REST host override, e.g. `https://api-pro.dexpaprika.com` for Pro.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_base_url: Option<String>,
+    /// Streaming host override.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub streaming_base_url: Option<String>,
+    /// Retries after a 429, a 5xx or a dropped connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Client-side pace in requests per minute; 0 turns pacing off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_minute: Option<u32>,
+    /// Cache slow-moving responses on disk. Off unless set; `--no-cache` wins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<bool>,
+    /// Plan `/usage` reported for the stored key when it was last checked.
+    /// Only trusted while `api_key` is still the key in use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+    /// `--fields` presets, by command name: `{"pools": ["id", "liquidity_usd"]}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<HashMap<String, Vec<String>>>,
+    /// Default `--table-style`: `rounded`, `ascii`, `plain`, `markdown` or `html`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_style: Option<TableStyle>,
+}
+
+impl Config {
+    /// Whether deleting the key would still leave something worth keeping.
+    fn has_settings_besides_the_key(&self) -> bool {
+        self.api_base_url.is_some()
+            || self.streaming_base_url.is_some()
+            || self.retries.is_some()
+            || self.rate_limit_per_minute.is_some()
+            || self.cache.is_some()
+            || self.fields.is_some()
+            || self.table_style.is_some()
+    }
 }
 
 pub fn config_dir() -> Result<PathBuf> {
@@ -29,6 +79,11 @@ pub fn config_path() -> Result<PathBuf> {
     Ok(config_dir()?.join("config.json"))
 }
 
+/// Where the response cache keeps its entries.
+pub fn cache_dir() -> Result<PathBuf> {
+    Ok(config_dir()?.join("cache"))
+}
+
 pub fn config_exists() -> bool {
     config_path().map(|p| p.exists()).unwrap_or(false)
 }
@@ -44,7 +99,30 @@ pub fn load_config() -> Result<Config> {
     serde_json::from_str(&contents).context("Failed to parse config file")
 }
 
-pub fn save_api_key(key: &str) -> Result<()> {
+/// Store a key along with the plan `/usage` reported for it.
+pub fn save_api_key(key: &str, plan: &str) -> Result<()> {
+    // Only the key and its plan change. Settings somebody put in the file by
+    // hand have to survive a set-key.
+    let mut config = load_config()?;
+    config.api_key = Some(key.to_string());
+    config.plan = Some(plan.to_string());
+    save_config(&config)
+}
+
+/// Refresh the recorded plan for the stored key, when `key` is that key. A
+/// key from the flag or the environment is never written to disk.
+pub fn record_plan(key: &str, plan: &str) -> Result<()> {
+    let mut config = load_config()?;
+    if config.api_key.as_deref().and_then(sanitize_key).as_deref() != Some(key)
+        || config.plan.as_deref() == Some(plan)
+    {
+        return Ok(());
+    }
+    config.plan = Some(plan.to_string());
+    save_config(&config)
+}
+
+fn save_config(config: &Config) -> Result<()> {
     let dir = config_dir()?;
     fs::create_dir_all(&dir)?;
 
@@ -54,10 +132,7 @@ pub fn save_api_key(key: &str) -> Result<()> {
         fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
     }
 
-    let config = Config {
-        api_key: Some(key.to_string()),
-    };
-    let json = serde_json::to_string_pretty(&config)?;
+    let json = serde_json::to_string_pretty(config)?;
     let path = config_path()?;
 
     // 0600 from creation rather than chmod afterwards: a world-readable window,
@@ -81,12 +156,22 @@ pub fn save_api_key(key: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn delete_config() -> Result<()> {
-    let dir = config_dir()?;
-    if dir.exists() {
-        fs::remove_dir_all(&dir)?;
+/// Forget the stored key. Any other settings in the same file are kept; when
+/// nothing else is left the file goes too. A file that no longer parses is
+/// removed outright, since nothing in it is usable anyway.
+pub fn delete_api_key() -> Result<()> {
+    let path = config_path()?;
+    if !path.exists() {
+        return Ok(());
+    }
+    match load_config() {
+        Ok(mut config) if config.has_settings_besides_the_key() => {
+            config.api_key = None;
+            config.plan = None;
+            save_config(&config)
+        }
+        _ => Ok(fs::remove_file(&path)?),
     }
-    Ok(())
 }
 
 /// Trim a key and reject anything that could break out of an HTTP header.
@@ -148,6 +233,132 @@ pub fn key_source(cli_key: Option<&str>) -> &'static str {
     "not set (running keyless)"
 }
 
+/// Check a host override and put it in the form the client joins paths onto.
+///
+/// Blank means "not set" and falls through to the next source, like a blank
+/// key does. Anything else has to be an absolute http(s) URL: a typo in a host
+/// should fail loudly here rather than as a DNS error on every command.
+pub fn normalize_base_url(raw: &str) -> Result<Option<String>> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    let url =
+        reqwest::Url::parse(trimmed).with_context(|| format!("`{trimmed}` is not a valid URL"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("`{trimmed}` must be an http:// or https:// URL");
+    }
+    Ok(Some(trimmed.trim_end_matches('/').to_string()))
+}
+
+/// Resolve one host: flag, then the environment, then the config file, then
+/// the built-in default.
+fn resolve_base_url(
+    cli_url: Option<&str>,
+    env_var: &str,
+    stored: impl FnOnce(Config) -> Option<String>,
+    default: &str,
+) -> Result<String> {
+    if let Some(url) = cli_url.map(normalize_base_url).transpose()?.flatten() {
+        return Ok(url);
+    }
+    if let Ok(value) = std::env::var(env_var) {
+        if let Some(url) = normalize_base_url(&value)
+            .with_context(|| format!("{env_var} is set to an unusable value"))?
+        {
+            return Ok(url);
+        }
+    }
+    if let Some(value) = load_config().ok().and_then(stored) {
+        if let Some(url) = normalize_base_url(&value)
+            .context("The host in ~/.dexpaprika/config.json is unusable")?
+        {
+            return Ok(url);
+        }
+    }
+    Ok(default.to_string())
+}
+
+/// Resolve the REST host: `--api-url`, then DEXPAPRIKA_API_URL, then the config
+/// file, then the public API.
+pub fn resolve_api_base_url(cli_url: Option<&str>) -> Result<String> {
+    resolve_base_url(
+        cli_url,
+        API_URL_ENV_VAR,
+        |c| c.api_base_url,
+        dexpaprika::client::DEFAULT_API_BASE_URL,
+    )
+}
+
+/// Resolve the streaming host: `--streaming-url`, then DEXPAPRIKA_STREAMING_URL,
+/// then the config file, then the public streaming host.
+pub fn resolve_streaming_base_url(cli_url: Option<&str>) -> Result<String> {
+    resolve_base_url(
+        cli_url,
+        STREAMING_URL_ENV_VAR,
+        |c| c.streaming_base_url,
+        dexpaprika::client::DEFAULT_STREAMING_BASE_URL,
+    )
+}
+
+/// Resolve the retry count: `--retries`, then the config file, then the
+/// default.
+pub fn resolve_retries(cli_retries: Option<u32>) -> u32 {
+    cli_retries
+        .or_else(|| load_config().ok().and_then(|c| c.retries))
+        .unwrap_or(dexpaprika::retry::DEFAULT_MAX_RETRIES)
+}
+
+/// Resolve the client-side pace: `--rate-limit`, then the config file, then
+/// the plan recorded for `api_key`, then the default.
+///
+/// The recorded plan only counts while the stored key is the one in use: a
+/// different key from the flag or the environment may be on a different plan.
+pub fn resolve_rate_limit(cli_rate: Option<u32>, api_key: Option<&str>) -> u32 {
+    if let Some(rate) = cli_rate {
+        return rate;
+    }
+    let config = load_config().unwrap_or_default();
+    if let Some(rate) = config.rate_limit_per_minute {
+        return rate;
+    }
+    let stored_key = config.api_key.as_deref().and_then(sanitize_key);
+    match config.plan.as_deref() {
+        Some(plan) if api_key.is_some() && stored_key.as_deref() == api_key => {
+            dexpaprika::ratelimit::plan_rate_per_minute(plan)
+        }
+        _ => dexpaprika::ratelimit::DEFAULT_RATE_PER_MINUTE,
+    }
+}
+
+/// Whether the response cache is on: `--no-cache` turns it off, `--cache` on,
+/// and otherwise the config file decides. Off by default.
+pub fn resolve_cache(cli_cache: bool, cli_no_cache: bool) -> bool {
+    if cli_no_cache {
+        return false;
+    }
+    cli_cache || load_config().ok().and_then(|c| c.cache).unwrap_or(false)
+}
+
+/// Resolve the fields a list command shows: `--fields`, then the preset for
+/// `command` in the config file. `None` keeps the command's own columns.
+pub fn resolve_fields(cli_fields: Option<Vec<String>>, command: &str) -> Option<Vec<String>> {
+    cli_fields.or_else(|| {
+        load_config()
+            .ok()
+            .and_then(|c| c.fields)
+            .and_then(|mut presets| presets.remove(command))
+    })
+}
+
+/// Resolve the table style: `--table-style`, then the config file, then
+/// rounded box drawing.
+pub fn resolve_table_style(cli_style: Option<TableStyle>) -> TableStyle {
+    cli_style
+        .or_else(|| load_config().ok().and_then(|c| c.table_style))
+        .unwrap_or_default()
+}
+
 /// Show enough of a key to recognise it, never enough to use it.
 pub fn mask_key(key: &str) -> String {
     if key.len() <= 8 {
@@ -228,4 +439,79 @@ mod tests {
         // environment, so resolution continues past it.
         assert_eq!(key_source(Some("   ")), key_source(None));
     }
+
+    #[test]
+    fn a_base_url_loses_its_trailing_slash() {
+        assert_eq!(
+            normalize_base_url("http://127.0.0.1:8080/")
+                .unwrap()
+                .as_deref(),
+            Some("http://127.0.0.1:8080")
+        );
+        assert_eq!(
+            normalize_base_url(" https://api-pro.dexpaprika.com ")
+                .unwrap()
+                .as_deref(),
+            Some("https://api-pro.dexpaprika.com")
+        );
+    }
+
+    #[test]
+    fn a_blank_base_url_means_not_set() {
+        assert_eq!(normalize_base_url("   ").unwrap(), None);
+    }
+
+    #[test]
+    fn a_base_url_that_is_not_http_is_refused() {
+        for bad in ["api.dexpaprika.com", "ftp://example.com", "not a url"] {
+            assert!(
+                normalize_base_url(bad).is_err(),
+                "expected {bad:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn an_explicit_base_url_flag_beats_everything_else() {
+        assert_eq!(
+            resolve_api_base_url(Some("http://localhost:9000/")).unwrap(),
+            "http://localhost:9000"
+        );
+        assert_eq!(
+            resolve_streaming_base_url(Some("http://localhost:9001")).unwrap(),
+            "http://localhost:9001"
+        );
+    }
+
+    #[test]
+    fn stored_host_overrides_survive_a_round_trip_and_stay_out_when_unset() {
+        let json = serde_json::to_string(&Config {
+            api_key: Some("api_x".into()),
+            ..Config::default()
+        })
+        .unwrap();
+        assert!(!json.contains("base_url"));
+
+        let config: Config = serde_json::from_str(
+            r#"{"api_key":null,"api_base_url":"https://api-pro.dexpaprika.com"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.api_base_url.as_deref(),
+            Some("https://api-pro.dexpaprika.com")
+        );
+        assert_eq!(config.streaming_base_url, None);
+    }
+
+    #[test]
+    fn a_stored_table_style_is_read_by_its_flag_name() {
+        let config: Config =
+            serde_json::from_str(r#"{"api_key":null,"table_style":"markdown"}"#).unwrap();
+        assert_eq!(config.table_style, Some(TableStyle::Markdown));
+        assert!(serde_json::from_str::<Config>(r#"{"table_style":"fancy"}"#).is_err());
+        assert_eq!(
+            resolve_table_style(Some(TableStyle::Html)),
+            TableStyle::Html
+        );
+    }
 }
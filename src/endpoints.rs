@@ -0,0 +1,134 @@
+//! Typed calls for the REST endpoints the CLI uses.
+//!
+//! Each method is a thin layer over `ApiClient::dexpaprika_get`: it builds the
+//! path, turns a query builder into parameters, and decodes into the matching
+//! `models` type. Retries, pacing, caching and cassettes all apply as usual.
+
+use anyhow::Result;
+
+use crate::client::ApiClient;
+use crate::models::{
+    Dex, DexSearchResult, DexStats, DexesResponse, Network, PoolDetail, PoolOhlcv,
+    PoolSearchResponse, TokenDetail, TokenPrice, TokenSearchResponse, TransactionsResponse,
+};
+use crate::query::{OhlcvQuery, PoolSearchQuery, TokenSearchQuery, TransactionsQuery};
+
+/// Borrow owned parameters the way `dexpaprika_get` takes them.
+fn as_refs<'a>(params: &'a [(&'static str, String)]) -> Vec<(&'static str, &'a str)> {
+    params.iter().map(|(k, v)| (*k, v.as_str())).collect()
+}
+
+impl ApiClient {
+    /// Counts of networks, DEXes, pools and tokens. Cacheable.
+    pub async fn stats(&self) -> Result<DexStats> {
+        self.dexpaprika_get("/stats", &[]).await
+    }
+
+    /// Every supported network. Cacheable.
+    pub async fn networks(&self) -> Result<Vec<Network>> {
+        self.dexpaprika_get("/networks", &[]).await
+    }
+
+    /// One page of the DEXes on `network`. This endpoint still pages by
+    /// number, starting at 1. Cacheable.
+    pub async fn dexes(&self, network: &str, limit: usize, page: usize) -> Result<Vec<Dex>> {
+        let (limit, page) = (limit.to_string(), page.to_string());
+        let resp: DexesResponse = self
+            .dexpaprika_get(
+                &format!("/networks/{network}/dexes"),
+                &[("limit", &limit), ("page", &page)],
+            )
+            .await?;
+        Ok(resp.dexes)
+    }
+
+    /// One page of pools on `network` matching `query`.
+    pub async fn pool_search(
+        &self,
+        network: &str,
+        query: &PoolSearchQuery,
+    ) -> Result<PoolSearchResponse> {
+        let params = query.params();
+        self.dexpaprika_get(
+            &format!("/networks/{network}/pools/search"),
+            &as_refs(&params),
+        )
+        .await
+    }
+
+    /// One pool, priced the other way round when `inversed` is set.
+    pub async fn pool(&self, network: &str, address: &str, inversed: bool) -> Result<PoolDetail> {
+        let params: &[(&str, &str)] = if inversed {
+            &[("inversed", "true")]
+        } else {
+            &[]
+        };
+        self.dexpaprika_get(&format!("/networks/{network}/pools/{address}"), params)
+            .await
+    }
+
+    /// One page of a pool's swaps, newest first.
+    pub async fn pool_transactions(
+        &self,
+        network: &str,
+        pool: &str,
+        query: &TransactionsQuery,
+    ) -> Result<TransactionsResponse> {
+        let params = query.params();
+        self.dexpaprika_get(
+            &format!("/networks/{network}/pools/{pool}/transactions"),
+            &as_refs(&params),
+        )
+        .await
+    }
+
+    /// Candles for a pool.
+    pub async fn pool_ohlcv(
+        &self,
+        network: &str,
+        pool: &str,
+        query: &OhlcvQuery,
+    ) -> Result<Vec<PoolOhlcv>> {
+        let params = query.params();
+        self.dexpaprika_get(
+            &format!("/networks/{network}/pools/{pool}/ohlcv"),
+            &as_refs(&params),
+        )
+        .await
+    }
+
+    /// One token.
+    pub async fn token(&self, network: &str, address: &str) -> Result<TokenDetail> {
+        self.dexpaprika_get(&format!("/networks/{network}/tokens/{address}"), &[])
+            .await
+    }
+
+    /// One page of tokens on `network` matching `query`.
+    pub async fn token_search(
+        &self,
+        network: &str,
+        query: &TokenSearchQuery,
+    ) -> Result<TokenSearchResponse> {
+        let params = query.params();
+        self.dexpaprika_get(
+            &format!("/networks/{network}/tokens/search"),
+            &as_refs(&params),
+        )
+        .await
+    }
+
+    /// Current USD prices for several tokens in one call. Addresses the API
+    /// does not know are left out of the answer rather than failing it.
+    pub async fn prices(&self, network: &str, tokens: &[&str]) -> Result<Vec<TokenPrice>> {
+        self.dexpaprika_get(
+            &format!("/networks/{network}/multi/prices"),
+            &[("tokens", &tokens.join(","))],
+        )
+        .await
+    }
+
+    /// Tokens, pools and DEXes matching `query` across every network.
+    pub async fn search(&self, query: &str) -> Result<DexSearchResult> {
+        self.dexpaprika_get("/search", &[("query", query)]).await
+    }
+}
@@ -0,0 +1,231 @@
+//! Typed API failures and the process exit code each one maps to.
+//!
+//! Every failure the client produces is one of these, wrapped in `anyhow` like
+//! any other error. `main` finds it again with a downcast, so scripts can branch
+//! on the exit code (or the `code` field in JSON mode) instead of on wording
+//! that is free to change.
+
+use std::fmt;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// 404: almost always a wrong network id or address.
+    NotFound { body: String },
+    /// 429 that outlasted the retries.
+    RateLimited { retry_after: Option<Duration> },
+    /// 401: the key was read and rejected.
+    Unauthorized { body: String },
+    /// 403: typically a free key sent to the Pro host.
+    Forbidden { body: String },
+    /// Any other 4xx. The request itself is wrong and resending will not help.
+    BadRequest { status: StatusCode, body: String },
+    /// 5xx that outlasted the retries.
+    ServerError { status: StatusCode },
+    /// An error response naming where a removed endpoint went.
+    Deprecated {
+        status: StatusCode,
+        replacement: String,
+        message: Option<String>,
+    },
+    /// No usable answer at all: DNS, connect, TLS, timeout, a dropped body.
+    Network(reqwest::Error),
+    /// A 2xx whose body is not the shape the CLI expects.
+    Decode(serde_json::Error),
+}
+
+impl ApiError {
+    /// Classify an error status. The deprecation case is decided by the body,
+    /// not the status, so the client checks for it before calling this.
+    pub fn from_status(status: StatusCode, retry_after: Option<Duration>, body: String) -> Self {
+        match status {
+            StatusCode::NOT_FOUND => Self::NotFound { body },
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited { retry_after },
+            StatusCode::UNAUTHORIZED => Self::Unauthorized { body },
+            StatusCode::FORBIDDEN => Self::Forbidden { body },
+            s if s.is_server_error() => Self::ServerError { status },
+            _ => Self::BadRequest { status, body },
+        }
+    }
+
+    /// Stable machine-readable name, carried as `code` in JSON-mode errors.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound { .. } => "not_found",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Unauthorized { .. } => "unauthorized",
+            Self::Forbidden { .. } => "forbidden",
+            Self::BadRequest { .. } => "bad_request",
+            Self::ServerError { .. } => "server_error",
+            Self::Deprecated { .. } => "deprecated",
+            Self::Network(_) => "network",
+            Self::Decode(_) => "decode",
+        }
+    }
+
+    /// Process exit code. 1 stays the catch-all for failures that are not API
+    /// errors and 2 belongs to clap's usage errors, so API errors start at 3.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::NotFound { .. } => 3,
+            Self::BadRequest { .. } => 4,
+            Self::Unauthorized { .. } => 5,
+            Self::Forbidden { .. } => 6,
+            Self::RateLimited { .. } => 7,
+            Self::ServerError { .. } => 8,
+            Self::Deprecated { .. } => 9,
+            Self::Network(_) => 10,
+            Self::Decode(_) => 11,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { body } => write!(
+                f,
+                "Not found. Check the network ID and address. API response: {body}"
+            ),
+            Self::RateLimited {
+                retry_after: Some(wait),
+            } => write!(
+                f,
+                "DexPaprika API rate limit reached (429 Too Many Requests). \
+                 The API asks to wait {}s before retrying.",
+                wait.as_secs()
+            ),
+            Self::RateLimited { retry_after: None } => write!(
+                f,
+                "DexPaprika API rate limit reached (429 Too Many Requests). Back off and retry."
+            ),
+            Self::Unauthorized { body } => write!(
+                f,
+                "DexPaprika API rejected the API key (401 Unauthorized). The key is the entire \
+                 Authorization value, with no \"Bearer\" in front of it. API response: {body}"
+            ),
+            Self::Forbidden { body } => write!(
+                f,
+                "DexPaprika API refused the request (403 Forbidden). A free key sent to the Pro \
+                 host is the usual cause. API response: {body}"
+            ),
+            Self::BadRequest { status, body } => {
+                write!(f, "DexPaprika API error {status}: {body}")
+            }
+            Self::ServerError { status } => write!(
+                f,
+                "DexPaprika API is temporarily unavailable. Try again shortly. ({status})"
+            ),
+            Self::Deprecated {
+                status,
+                replacement,
+                message: Some(message),
+            } => write!(
+                f,
+                "This endpoint was removed. Use {replacement} instead. ({status}) API says: {message}"
+            ),
+            Self::Deprecated {
+                status,
+                replacement,
+                message: None,
+            } => write!(
+                f,
+                "This endpoint was removed. Use {replacement} instead. ({status})"
+            ),
+            Self::Network(e) => write!(f, "Could not reach the DexPaprika API: {e}"),
+            Self::Decode(e) => write!(f, "Unexpected response from the DexPaprika API: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Network(e) => Some(e),
+            Self::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The API error behind an `anyhow` error, if there is one anywhere in its
+/// chain. Context added on the way up does not hide it.
+pub fn find_api_error(err: &anyhow::Error) -> Option<&ApiError> {
+    err.chain().find_map(|e| e.downcast_ref::<ApiError>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_classify_into_their_own_variant() {
+        let cases = [
+            (StatusCode::NOT_FOUND, "not_found"),
+            (StatusCode::TOO_MANY_REQUESTS, "rate_limited"),
+            (StatusCode::UNAUTHORIZED, "unauthorized"),
+            (StatusCode::FORBIDDEN, "forbidden"),
+            (StatusCode::BAD_REQUEST, "bad_request"),
+            (StatusCode::UNPROCESSABLE_ENTITY, "bad_request"),
+            (StatusCode::BAD_GATEWAY, "server_error"),
+            (StatusCode::INTERNAL_SERVER_ERROR, "server_error"),
+        ];
+        for (status, code) in cases {
+            assert_eq!(
+                ApiError::from_status(status, None, String::new()).code(),
+                code,
+                "{status}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_variant_has_a_distinct_exit_code_clear_of_1_and_2() {
+        let decode = serde_json::from_str::<u8>("x").unwrap_err();
+        let errors = [
+            ApiError::NotFound {
+                body: String::new(),
+            },
+            ApiError::RateLimited { retry_after: None },
+            ApiError::Unauthorized {
+                body: String::new(),
+            },
+            ApiError::Forbidden {
+                body: String::new(),
+            },
+            ApiError::BadRequest {
+                status: StatusCode::BAD_REQUEST,
+                body: String::new(),
+            },
+            ApiError::ServerError {
+                status: StatusCode::BAD_GATEWAY,
+            },
+            ApiError::Deprecated {
+                status: StatusCode::GONE,
+                replacement: "/search".into(),
+                message: None,
+            },
+            ApiError::Decode(decode),
+        ];
+        let mut codes: Vec<u8> = errors.iter().map(ApiError::exit_code).collect();
+        codes.push(10); // Network: no reqwest::Error can be built by hand.
+        assert!(codes.iter().all(|&c| c > 2));
+        let count = codes.len();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), count, "exit codes must not collide");
+    }
+
+    #[test]
+    fn an_api_error_is_found_under_added_context() {
+        let err = anyhow::Error::new(ApiError::RateLimited { retry_after: None })
+            .context("while fetching page 3");
+        assert_eq!(
+            find_api_error(&err).map(ApiError::code),
+            Some("rate_limited")
+        );
+        assert!(find_api_error(&anyhow::anyhow!("plain")).is_none());
+    }
+}
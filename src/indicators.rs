@@ -0,0 +1,405 @@
+//! Technical indicators over OHLCV candles, computed locally.
+//!
+//! Each indicator turns a series of candles, oldest first, into one or more
+//! series of the same length. A value is `None` until enough candles have gone
+//! by to compute it: the first 19 of `sma:20` are `None`, for instance. A
+//! candle without a close is a gap rather than a price of 0: every value that
+//! would take it in is `None`, and the count starts over after it.
+//!
+//! ```
+//! use dexpaprika::indicators::Indicator;
+//!
+//! let rsi = Indicator::parse("rsi:14").unwrap();
+//! assert_eq!(rsi.columns(), ["rsi_14"]);
+//! assert_eq!(rsi.min_candles(), 15);
+//! ```
+
+use crate::models::PoolOhlcv;
+
+/// Periods used when a spec leaves them out, as most charting tools do.
+const DEFAULT_AVERAGE_PERIOD: usize = 20;
+const DEFAULT_RSI_PERIOD: usize = 14;
+const DEFAULT_ATR_PERIOD: usize = 14;
+
+/// How many standard deviations the Bollinger bands sit from their average.
+const BOLLINGER_WIDTH: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indicator {
+    /// Simple moving average of the close.
+    Sma(usize),
+    /// Exponential moving average of the close, seeded with the SMA.
+    Ema(usize),
+    /// Wilder's relative strength index, 0 to 100.
+    Rsi(usize),
+    /// Volume-weighted average of the typical price, from the first candle.
+    Vwap,
+    /// Wilder's average true range.
+    Atr(usize),
+    /// Bollinger bands: the SMA and two standard deviations either side.
+    Bollinger(usize),
+}
+
+impl Indicator {
+    /// Parse `name` or `name:period`: `sma:20`, `ema:50`, `rsi`, `vwap`,
+    /// `atr:14`, `bb:20`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim().to_ascii_lowercase();
+        let (name, period) = match spec.split_once(':') {
+            Some((name, period)) => {
+                let period = period
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|p| *p > 0)
+                    .ok_or_else(|| {
+                        format!("`{spec}`: the period is a whole number of candles, like {name}:20")
+                    })?;
+                (name.to_string(), Some(period))
+            }
+            None => (spec.clone(), None),
+        };
+        let or = |default| period.unwrap_or(default);
+        Ok(match name.as_str() {
+            "sma" => Indicator::Sma(or(DEFAULT_AVERAGE_PERIOD)),
+            "ema" => Indicator::Ema(or(DEFAULT_AVERAGE_PERIOD)),
+            "rsi" => Indicator::Rsi(or(DEFAULT_RSI_PERIOD)),
+            "atr" => Indicator::Atr(or(DEFAULT_ATR_PERIOD)),
+            "bb" | "bollinger" => Indicator::Bollinger(or(DEFAULT_AVERAGE_PERIOD)),
+            "vwap" if period.is_some() => {
+                return Err("`vwap` runs from the first candle and takes no period".into())
+            }
+            "vwap" => Indicator::Vwap,
+            _ => {
+                return Err(format!(
+                    "unknown indicator `{name}`. Use sma, ema, rsi, vwap, atr or bb."
+                ))
+            }
+        })
+    }
+
+    /// The names of the series `compute` returns, in the same order. They
+    /// become JSON fields and table columns.
+    pub fn columns(&self) -> Vec<String> {
+        match self {
+            Indicator::Sma(n) => vec![format!("sma_{n}")],
+            Indicator::Ema(n) => vec![format!("ema_{n}")],
+            Indicator::Rsi(n) => vec![format!("rsi_{n}")],
+            Indicator::Vwap => vec!["vwap".into()],
+            Indicator::Atr(n) => vec![format!("atr_{n}")],
+            Indicator::Bollinger(n) => vec![
+                format!("bb_upper_{n}"),
+                format!("bb_middle_{n}"),
+                format!("bb_lower_{n}"),
+            ],
+        }
+    }
+
+    /// How many candles it takes to get a first value. RSI compares each
+    /// close with the one before, so it needs one more than its period.
+    pub fn min_candles(&self) -> usize {
+        match self {
+            Indicator::Sma(n) | Indicator::Ema(n) | Indicator::Atr(n) | Indicator::Bollinger(n) => {
+                *n
+            }
+            Indicator::Rsi(n) => n + 1,
+            Indicator::Vwap => 1,
+        }
+    }
+
+    /// The indicator over `candles`, oldest first: one series per column.
+    pub fn compute(&self, candles: &[PoolOhlcv]) -> Vec<Vec<Option<f64>>> {
+        let close: Vec<Option<f64>> = candles.iter().map(|c| c.close).collect();
+        match *self {
+            Indicator::Sma(n) => vec![by_runs(&close, n, sma)],
+            Indicator::Ema(n) => vec![by_runs(&close, n, ema)],
+            Indicator::Rsi(n) => vec![by_runs(&close, n, rsi)],
+            Indicator::Vwap => vec![vwap(candles)],
+            Indicator::Atr(n) => vec![by_runs(&true_range(candles), n, wilder)],
+            Indicator::Bollinger(n) => {
+                let middle = by_runs(&close, n, sma);
+                let (mut upper, mut lower) = (vec![None; close.len()], vec![None; close.len()]);
+                for (i, mean) in middle.iter().enumerate() {
+                    let Some(mean) = mean else { continue };
+                    // A mean means the whole window has closes.
+                    let window = close[i + 1 - n..=i].iter().flatten();
+                    let variance = window.map(|c| (c - mean).powi(2)).sum::<f64>() / n as f64;
+                    let spread = BOLLINGER_WIDTH * variance.sqrt();
+                    upper[i] = Some(mean + spread);
+                    lower[i] = Some(mean - spread);
+                }
+                vec![upper, middle, lower]
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Indicator {
+    /// The spec it parses from, period included: `sma:20`, `vwap`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Indicator::Sma(n) => write!(f, "sma:{n}"),
+            Indicator::Ema(n) => write!(f, "ema:{n}"),
+            Indicator::Rsi(n) => write!(f, "rsi:{n}"),
+            Indicator::Vwap => write!(f, "vwap"),
+            Indicator::Atr(n) => write!(f, "atr:{n}"),
+            Indicator::Bollinger(n) => write!(f, "bb:{n}"),
+        }
+    }
+}
+
+/// `series` over each run of values between the gaps in `values`, with
+/// `None` at the gaps.
+fn by_runs(
+    values: &[Option<f64>],
+    n: usize,
+    series: fn(&[f64], usize) -> Vec<Option<f64>>,
+) -> Vec<Option<f64>> {
+    let mut out = Vec::with_capacity(values.len());
+    let mut run = Vec::new();
+    for value in values {
+        match value {
+            Some(v) => run.push(*v),
+            None => {
+                out.extend(series(&run, n));
+                run.clear();
+                out.push(None);
+            }
+        }
+    }
+    out.extend(series(&run, n));
+    out
+}
+
+fn sma(values: &[f64], n: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    let mut sum = 0.0;
+    for (i, v) in values.iter().enumerate() {
+        sum += v;
+        if i >= n {
+            sum -= values[i - n];
+        }
+        if i + 1 >= n {
+            out[i] = Some(sum / n as f64);
+        }
+    }
+    out
+}
+
+fn ema(values: &[f64], n: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if values.len() < n {
+        return out;
+    }
+    let alpha = 2.0 / (n as f64 + 1.0);
+    let mut current = values[..n].iter().sum::<f64>() / n as f64;
+    out[n - 1] = Some(current);
+    for i in n..values.len() {
+        current += alpha * (values[i] - current);
+        out[i] = Some(current);
+    }
+    out
+}
+
+fn rsi(close: &[f64], n: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; close.len()];
+    if close.len() <= n {
+        return out;
+    }
+    let change = |i: usize| close[i] - close[i - 1];
+    let (mut gain, mut loss) = (0.0, 0.0);
+    for i in 1..=n {
+        gain += change(i).max(0.0);
+        loss += (-change(i)).max(0.0);
+    }
+    let (mut gain, mut loss) = (gain / n as f64, loss / n as f64);
+    let index = |gain: f64, loss: f64| {
+        if loss == 0.0 {
+            100.0
+        } else {
+            100.0 - 100.0 / (1.0 + gain / loss)
+        }
+    };
+    out[n] = Some(index(gain, loss));
+    for (i, slot) in out.iter_mut().enumerate().skip(n + 1) {
+        gain = (gain * (n - 1) as f64 + change(i).max(0.0)) / n as f64;
+        loss = (loss * (n - 1) as f64 + (-change(i)).max(0.0)) / n as f64;
+        *slot = Some(index(gain, loss));
+    }
+    out
+}
+
+/// A running average, so a candle without a price is left out of it and
+/// gets no value of its own.
+fn vwap(candles: &[PoolOhlcv]) -> Vec<Option<f64>> {
+    let (mut weighted, mut volume) = (0.0, 0.0);
+    candles
+        .iter()
+        .map(|c| {
+            let typical = (c.high? + c.low? + c.close?) / 3.0;
+            let v = c.volume.unwrap_or(0.0);
+            weighted += typical * v;
+            volume += v;
+            (volume > 0.0).then(|| weighted / volume)
+        })
+        .collect()
+}
+
+/// Each candle's range, stretched to the close before it when the price
+/// gapped. The first candle has only its own range.
+fn true_range(candles: &[PoolOhlcv]) -> Vec<Option<f64>> {
+    candles
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let (high, low) = (c.high?, c.low?);
+            match i.checked_sub(1) {
+                Some(p) => {
+                    let prev = candles[p].close?;
+                    Some(
+                        (high - low)
+                            .max((high - prev).abs())
+                            .max((low - prev).abs()),
+                    )
+                }
+                None => Some(high - low),
+            }
+        })
+        .collect()
+}
+
+/// Wilder's smoothing: the mean of the first `n` values, then each new value
+/// weighted 1/n.
+fn wilder(values: &[f64], n: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if values.len() < n {
+        return out;
+    }
+    let mut current = values[..n].iter().sum::<f64>() / n as f64;
+    out[n - 1] = Some(current);
+    for i in n..values.len() {
+        current = (current * (n - 1) as f64 + values[i]) / n as f64;
+        out[i] = Some(current);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles(closes: &[f64]) -> Vec<PoolOhlcv> {
+        closes
+            .iter()
+            .map(|&c| PoolOhlcv {
+                time_open: None,
+                time_close: None,
+                open: Some(c),
+                high: Some(c + 1.0),
+                low: Some(c - 1.0),
+                close: Some(c),
+                volume: Some(10.0),
+            })
+            .collect()
+    }
+
+    fn rounded(series: &[Option<f64>]) -> Vec<Option<f64>> {
+        series
+            .iter()
+            .map(|v| v.map(|v| (v * 1000.0).round() / 1000.0))
+            .collect()
+    }
+
+    #[test]
+    fn specs_take_an_optional_period() {
+        assert_eq!(Indicator::parse("sma:5"), Ok(Indicator::Sma(5)));
+        assert_eq!(Indicator::parse("RSI"), Ok(Indicator::Rsi(14)));
+        assert_eq!(Indicator::parse("bollinger"), Ok(Indicator::Bollinger(20)));
+        assert!(Indicator::parse("sma:0").is_err());
+        assert!(Indicator::parse("vwap:3").is_err());
+        assert_eq!(Indicator::parse("bb").unwrap().to_string(), "bb:20");
+        assert!(Indicator::parse("macd")
+            .unwrap_err()
+            .contains("unknown indicator"));
+    }
+
+    #[test]
+    fn averages_start_once_their_window_is_full() {
+        let c = candles(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(
+            Indicator::Sma(3).compute(&c),
+            [vec![None, None, Some(2.0), Some(3.0), Some(4.0)]]
+        );
+        // Seeded at 2, then half way to each new close.
+        assert_eq!(
+            Indicator::Ema(3).compute(&c),
+            [vec![None, None, Some(2.0), Some(3.0), Some(4.0)]]
+        );
+        assert_eq!(
+            Indicator::Ema(3).compute(&candles(&[1.0, 2.0, 3.0, 7.0]))[0][3],
+            Some(4.5)
+        );
+    }
+
+    #[test]
+    fn rsi_is_100_without_losses_and_smooths_the_way_wilder_did() {
+        let rising = candles(&[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(
+            Indicator::Rsi(2).compute(&rising)[0],
+            [None, None, Some(100.0), Some(100.0)]
+        );
+        // Changes +1, -1, then +2: averages 0.5/0.5, then 1.25/0.25.
+        let mixed = candles(&[1.0, 2.0, 1.0, 3.0]);
+        assert_eq!(
+            rounded(&Indicator::Rsi(2).compute(&mixed)[0]),
+            [None, None, Some(50.0), Some(83.333)]
+        );
+    }
+
+    #[test]
+    fn atr_vwap_and_bands() {
+        // A gap up from 2 to 5 makes the true range 4 rather than the 2 of the
+        // candle itself.
+        let c = candles(&[1.0, 2.0, 5.0]);
+        assert_eq!(
+            rounded(&Indicator::Atr(2).compute(&c)[0]),
+            [None, Some(2.0), Some(3.0)]
+        );
+        // Equal volumes: the running mean of the typical prices.
+        assert_eq!(
+            Indicator::Vwap.compute(&c)[0],
+            [Some(1.0), Some(1.5), Some(8.0 / 3.0)]
+        );
+        let bands = Indicator::Bollinger(2).compute(&candles(&[1.0, 3.0]));
+        assert_eq!(
+            bands,
+            [
+                vec![None, Some(4.0)],
+                vec![None, Some(2.0)],
+                vec![None, Some(0.0)]
+            ]
+        );
+    }
+
+    #[test]
+    fn a_missing_close_is_a_gap_not_a_zero() {
+        let mut c = candles(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        c[2].close = None;
+        assert_eq!(
+            Indicator::Sma(2).compute(&c),
+            [vec![None, Some(1.5), None, None, Some(4.5), Some(5.5)]]
+        );
+        // Starts over after the gap, seeded from the closes after it.
+        assert_eq!(
+            Indicator::Ema(2).compute(&c)[0][3..],
+            [None, Some(4.5), Some(5.5)]
+        );
+        assert_eq!(Indicator::Rsi(1).compute(&c)[0][2..4], [None, None]);
+        // The candle after the gap has no close to measure its range from.
+        assert_eq!(Indicator::Atr(1).compute(&c)[0][2..4], [Some(2.0), None]);
+        assert_eq!(Indicator::Vwap.compute(&c)[0][2], None);
+        let bands = Indicator::Bollinger(2).compute(&c);
+        assert!(bands
+            .iter()
+            .all(|series| series[2].is_none() && series[3].is_none()));
+    }
+}
@@ -0,0 +1,56 @@
+//! Client library for the DexPaprika REST and streaming APIs.
+//!
+//! This is the code behind `dexpaprika-cli`, minus the terminal: an
+//! `ApiClient` with typed methods for each endpoint, request builders for the
+//! search and history endpoints, and the streaming feeds as
+//! `futures::Stream`s. No API key is needed to start.
+//!
+//! ```no_run
+//! use dexpaprika::query::PoolSearchQuery;
+//! use dexpaprika::ApiClient;
+//! use futures::StreamExt;
+//!
+//! # async fn demo() -> anyhow::Result<()> {
+//! let client = ApiClient::with_api_key(None);
+//!
+//! let top = client
+//!     .pool_search("ethereum", &PoolSearchQuery::new().limit(5).order_by("liquidity_usd"))
+//!     .await?;
+//! for pool in &top.results {
+//!     println!("{:?} {:?}", pool.id, pool.liquidity_usd);
+//! }
+//!
+//! let mut prices = client
+//!     .price_stream("ethereum", "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")
+//!     .await?;
+//! while let Some(event) = prices.next().await {
+//!     println!("{}", event?.price_usd);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Failures are `anyhow::Error`s; the ones the API produced carry an
+//! `ApiError`, which `error::find_api_error` digs out.
+
+pub mod cache;
+pub mod candles;
+pub mod cassette;
+pub mod client;
+pub mod endpoints;
+pub mod error;
+pub mod indicators;
+pub mod models;
+pub mod ohlcv;
+pub mod pages;
+pub mod query;
+pub mod ratelimit;
+pub mod retry;
+pub mod search_mapping;
+pub mod stream;
+
+#[cfg(test)]
+mod test_server;
+
+pub use client::ApiClient;
+pub use error::ApiError;
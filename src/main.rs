@@ -1,11 +1,19 @@
-mod client;
 mod commands;
 mod config;
 mod output;
 mod shell;
+#[cfg(test)]
+mod test_server;
 
 use clap::{Parser, Subcommand};
-use commands::pools::PriceChangeBounds;
+use commands::fanout::NetworkArgs;
+use commands::paging::PageArgs;
+use commands::participants::AggregateBy;
+use commands::trades::TradeArgs;
+use commands::view::ViewArgs;
+use dexpaprika::indicators::Indicator;
+use dexpaprika::query::PriceChangeBounds;
+use dexpaprika::{cache, cassette, client, error, retry};
 use output::OutputFormat;
 use std::process::ExitCode;
 
@@ -28,7 +36,7 @@ pub(crate) struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Output format: table or json
+    /// Output format: table, json, ndjson, csv or tsv
     #[arg(short, long, global = true, default_value = "table")]
     pub(crate) output: OutputFormat,
 
@@ -36,17 +44,91 @@ pub(crate) struct Cli {
     #[arg(long, global = true, default_value = "false")]
     pub(crate) raw: bool,
 
+    /// Reshape the JSON output with a jq-style expression, or with JSONPath
+    /// when it starts with $. Implies --output json.
+    ///
+    /// e.g. '.data.results[] | {id, price_usd}' or '$.data.results[*].id'.
+    /// Applies to the --raw shape as well, which has no data/_meta wrapper.
+    #[arg(long, global = true, value_name = "EXPR", value_parser = output::query::Query::parse)]
+    pub(crate) query: Option<output::query::Query>,
+
+    /// How tables are drawn. Defaults to rounded, or to table_style in the
+    /// stored config.
+    ///
+    /// ascii and plain survive CI logs and screen readers; markdown prints a
+    /// GitHub-flavored table to paste into notes.
+    #[arg(long, global = true, value_name = "STYLE")]
+    pub(crate) table_style: Option<output::table_style::TableStyle>,
+
+    /// Tables with full addresses and a column for every time window.
+    ///
+    /// Pool and token lists gain the 7d/30d volumes and the 5m/1h/6h changes;
+    /// pool and token details gain a table of every window; transactions gain
+    /// the transaction hash.
+    #[arg(long, global = true)]
+    pub(crate) wide: bool,
+
     /// Optional API key. Beats DEXPAPRIKA_API_KEY and the stored config.
     ///
     /// The CLI works without one; a key raises the monthly credit allowance.
     #[arg(long, global = true, value_name = "KEY")]
     pub(crate) api_key: Option<String>,
+
+    /// REST API base URL. Beats DEXPAPRIKA_API_URL and the stored config.
+    ///
+    /// Defaults to https://api.dexpaprika.com. Pro plans use
+    /// https://api-pro.dexpaprika.com; a local mock server works too.
+    #[arg(long, global = true, value_name = "URL")]
+    pub(crate) api_url: Option<String>,
+
+    /// Streaming (SSE) base URL. Beats DEXPAPRIKA_STREAMING_URL and the stored config.
+    ///
+    /// Defaults to https://streaming.dexpaprika.com.
+    #[arg(long, global = true, value_name = "URL")]
+    pub(crate) streaming_url: Option<String>,
+
+    /// Retries after a 429, a 5xx or a dropped connection, with jittered
+    /// exponential backoff. Beats the stored config; default 2, 0 disables.
+    ///
+    /// Other 4xx answers are never retried. A Retry-After header from the API
+    /// is honoured in place of the backoff.
+    #[arg(long, global = true, value_name = "N")]
+    pub(crate) retries: Option<u32>,
+
+    /// Client-side pace in requests per minute, shared by every request and
+    /// stream connection. Beats the stored config; 0 disables.
+    ///
+    /// Without it the pace follows the plan `config set-key` recorded for the
+    /// stored key (300 for Pro), or 60.
+    #[arg(long, global = true, value_name = "PER_MINUTE")]
+    pub(crate) rate_limit: Option<u32>,
+
+    /// Cache slow-moving responses (networks, dexes, stats) on disk under
+    /// ~/.dexpaprika/cache. Also switched on by `"cache": true` in the config.
+    #[arg(long, global = true)]
+    pub(crate) cache: bool,
+
+    /// Skip the response cache for this run, whatever the config says
+    #[arg(long, global = true, conflicts_with = "cache")]
+    pub(crate) no_cache: bool,
+
+    /// Save every HTTP exchange, REST and streaming, as cassette files in DIR
+    ///
+    /// Replay them later with --replay. The response cache is bypassed while
+    /// recording so that every request reaches the cassette.
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+    pub(crate) record: Option<std::path::PathBuf>,
+
+    /// Answer every request from cassette files recorded with --record, with
+    /// no network access. A request that was not recorded is an error.
+    #[arg(long, global = true, value_name = "DIR")]
+    pub(crate) replay: Option<std::path::PathBuf>,
 }
 
 /// Subcommands of `config`.
 #[derive(clap::Subcommand, Debug)]
 pub(crate) enum ConfigCommands {
-    /// Show which key is in use and what the API makes of it
+    /// Show which key and hosts are in use and what the API makes of the key
     Show,
     /// Validate a key against the API, then store it
     SetKey {
@@ -57,6 +139,13 @@ pub(crate) enum ConfigCommands {
     Delete,
 }
 
+/// Subcommands of `cache`.
+#[derive(clap::Subcommand, Debug)]
+pub(crate) enum CacheCommands {
+    /// Remove every cached response
+    Clear,
+}
+
 /// Parse a percentage bound and refuse the values f64 accepts but the API does
 /// not. "nan" and "inf" parse happily into f64 and go out on the wire as NaN and
 /// inf, where pools/search answers 500. The CLI turns any 5xx into "DexPaprika
@@ -82,7 +171,10 @@ enum Commands {
 
     /// List all supported networks/chains
     #[command(after_help = "EXAMPLES:\n  dexpaprika-cli networks")]
-    Networks,
+    Networks {
+        #[command(flatten)]
+        view: ViewArgs,
+    },
 
     /// List DEXes on a network
     #[command(after_help = "EXAMPLES:\n  dexpaprika-cli dexes ethereum --limit 10")]
@@ -95,6 +187,8 @@ enum Commands {
         /// Page number (1-indexed)
         #[arg(long, default_value = "1")]
         page: usize,
+        #[command(flatten)]
+        view: ViewArgs,
     },
 
     /// List top pools on a network
@@ -102,14 +196,16 @@ enum Commands {
         after_help = "EXAMPLES:\n  dexpaprika-cli pools ethereum --limit 5\n  dexpaprika-cli pools solana --order-by volume_usd_24h --sort desc\n  dexpaprika-cli --output json pools ethereum --order-by price_change_percentage_5m --sort desc\n\nThe table shows the 24h change. Sort by the 5m, 1h or 6h window and the values\nyou sorted on are in --output json, so that example asks for JSON."
     )]
     Pools {
-        /// Network ID (e.g., ethereum, solana)
-        network: String,
+        #[command(flatten)]
+        networks: NetworkArgs,
         /// Maximum number of results (max 100)
         #[arg(long, default_value = "10")]
         limit: usize,
-        /// Page number (1-indexed)
-        #[arg(long, default_value = "1")]
-        page: usize,
+        /// Cursor for the next page, taken from the previous response
+        #[arg(long)]
+        cursor: Option<String>,
+        #[command(flatten)]
+        pages: PageArgs,
         /// Order by field: volume_usd_24h, volume_usd_7d, volume_usd_30d, liquidity_usd,
         /// txns_24h, created_at, price_usd, price_change_percentage_{24h,6h,1h,5m}
         #[arg(long, default_value = "volume_usd_24h")]
@@ -117,6 +213,12 @@ enum Commands {
         /// Sort order
         #[arg(long, default_value = "desc")]
         sort: String,
+        /// Add a column with each pool's hourly closes over the last day. One
+        /// extra request per row; one page of one network, table output only
+        #[arg(long)]
+        sparkline: bool,
+        #[command(flatten)]
+        view: ViewArgs,
     },
 
     /// Filter pools by volume, liquidity, transactions, price change, and creation date
@@ -125,8 +227,8 @@ enum Commands {
         after_help = "EXAMPLES:\n  dexpaprika-cli pool-filter ethereum --volume-24h-min 100000\n  dexpaprika-cli pool-filter solana --liquidity-usd-min 50000 --sort-by liquidity\n  dexpaprika-cli pool-filter ethereum --price-change-1h-min 50 --sort-by price_change_percentage_1h\n  dexpaprika-cli pool-filter ethereum --price-change-24h-max -20\n\nPRICE CHANGE BOUNDS:\n  Percentages, and negative values are the point: --price-change-24h-max -20 means\n  down 20% or more over 24h. Write -0.5 rather than -.5, which clap reads as a flag.\n  The 6h, 1h and 5m windows exist for pools only.\n  The table shows the 24h change; all four windows come back in --output json."
     )]
     PoolFilter {
-        /// Network ID (e.g., ethereum, solana)
-        network: String,
+        #[command(flatten)]
+        networks: NetworkArgs,
         /// Minimum 24h volume in USD
         #[arg(long)]
         volume_24h_min: Option<f64>,
@@ -189,9 +291,13 @@ enum Commands {
         /// Maximum number of results (max 100)
         #[arg(long, default_value = "50")]
         limit: usize,
-        /// Page number (1-indexed)
-        #[arg(long, default_value = "1")]
-        page: usize,
+        /// Cursor for the next page, taken from the previous response
+        #[arg(long)]
+        cursor: Option<String>,
+        #[command(flatten)]
+        pages: PageArgs,
+        #[command(flatten)]
+        view: ViewArgs,
     },
 
     /// Get detailed info about a specific pool
@@ -226,12 +332,16 @@ enum Commands {
         /// Cursor for the next page, taken from the previous response
         #[arg(long)]
         cursor: Option<String>,
+        #[command(flatten)]
+        pages: PageArgs,
         /// Order by field
         #[arg(long, default_value = "volume_usd")]
         order_by: String,
         /// Sort order
         #[arg(long, default_value = "desc")]
         sort: String,
+        #[command(flatten)]
+        view: ViewArgs,
     },
 
     /// Get recent transactions for a pool
@@ -249,12 +359,55 @@ enum Commands {
         /// Cursor for pagination
         #[arg(long)]
         cursor: Option<String>,
+        #[command(flatten)]
+        pages: PageArgs,
         /// Filter transactions starting from this UNIX timestamp (inclusive, max 7 days)
         #[arg(long)]
         from: Option<i64>,
         /// Filter transactions up to this UNIX timestamp (exclusive)
         #[arg(long)]
         to: Option<i64>,
+        /// Keep polling the newest page and print each new transaction as it
+        /// turns up, like tail -f, until Ctrl+C. JSON comes out as NDJSON, a
+        /// line per transaction, and --query runs on each one
+        #[arg(long, conflicts_with_all = ["cursor", "all", "max_pages", "matches", "from", "to"])]
+        follow: bool,
+        /// Seconds between polls under --follow
+        #[arg(long, value_name = "SECONDS", default_value_t = 10, requires = "follow",
+              value_parser = clap::value_parser!(u64).range(1..))]
+        every: u64,
+        /// Instead of the trades, a row per address that made them: trades,
+        /// USD bought and sold, net flow of each token, first and last seen
+        #[arg(long, value_name = "BY", value_enum, conflicts_with_all = ["follow", "matches"])]
+        aggregate: Option<AggregateBy>,
+        #[command(flatten)]
+        trades: TradeArgs,
+        #[command(flatten)]
+        view: ViewArgs,
+    },
+
+    /// Export a pool's transaction history to a file, resumable
+    #[command(
+        name = "transactions-export",
+        after_help = "EXAMPLES:\n  dexpaprika-cli transactions-export ethereum 0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640 --since 2026-01-01 --out trades.csv"
+    )]
+    TransactionsExport {
+        /// Network ID
+        network: String,
+        /// Pool contract address
+        pool_address: String,
+        /// Oldest time to export (unix timestamp, RFC3339, or yyyy-mm-dd)
+        #[arg(long)]
+        since: String,
+        /// Newest time to export [default: now]
+        #[arg(long)]
+        until: Option<String>,
+        /// File to write: CSV, TSV or NDJSON, by --output or the extension
+        #[arg(long, value_name = "FILE")]
+        out: std::path::PathBuf,
+        /// Where to keep progress for a resume [default: FILE.checkpoint]
+        #[arg(long, value_name = "FILE")]
+        checkpoint: Option<std::path::PathBuf>,
     },
 
     /// Get OHLCV data for a pool
@@ -276,12 +429,58 @@ enum Commands {
         /// Interval (1m, 5m, 10m, 15m, 30m, 1h, 6h, 12h, 24h)
         #[arg(long, default_value = "24h")]
         interval: String,
-        /// Maximum number of data points (max 366)
-        #[arg(long, default_value = "50")]
-        limit: usize,
+        /// Number of data points [default: 50, or all of --start..--end]. Above
+        /// 366 the range is fetched in several requests and stitched together
+        #[arg(long)]
+        limit: Option<usize>,
         /// Invert the price ratio
         #[arg(long)]
         inversed: bool,
+        /// Draw a candlestick chart with a volume strip, sized to the terminal,
+        /// instead of the table
+        #[arg(long)]
+        chart: bool,
+        /// Indicators to compute from the candles and add as columns, e.g.
+        /// sma:20,ema:50,rsi:14,vwap,atr:14,bb:20
+        #[arg(
+            long,
+            value_name = "LIST",
+            value_delimiter = ',',
+            value_parser = Indicator::parse,
+            conflicts_with = "chart"
+        )]
+        indicators: Vec<Indicator>,
+        #[command(flatten)]
+        view: ViewArgs,
+    },
+
+    /// Build candles from a pool's transactions, at any interval down to a second
+    #[command(
+        after_help = "EXAMPLES:\n  dexpaprika-cli candles ethereum 0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640 --from 2026-01-01T00:00:00Z --to 2026-01-01T00:10:00Z --interval 10s"
+    )]
+    Candles {
+        /// Network ID
+        network: String,
+        /// Pool contract address
+        pool_address: String,
+        /// Start of the range (unix timestamp, RFC3339, or yyyy-mm-dd)
+        #[arg(long)]
+        from: String,
+        /// End of the range, at most 7 days after --from [default: now]
+        #[arg(long)]
+        to: Option<String>,
+        /// Candle width: a number and s, m, h or d, such as 10s, 90s or 5m
+        #[arg(long, default_value = "1m")]
+        interval: String,
+        /// The token to price and count buys and sells of: 0, 1, or the
+        /// symbol or address of one of the pool's tokens
+        #[arg(long, value_name = "TOKEN", default_value = "0")]
+        base: String,
+        /// Fetch at most N pages of 100 transactions
+        #[arg(long, value_name = "N", default_value_t = commands::paging::ALL_PAGES_CAP)]
+        max_pages: usize,
+        #[command(flatten)]
+        view: ViewArgs,
     },
 
     /// Get detailed info about a token
@@ -308,15 +507,19 @@ enum Commands {
         /// Maximum number of results (max 100)
         #[arg(long, default_value = "10")]
         limit: usize,
-        /// Page number (1-indexed)
-        #[arg(long, default_value = "1")]
-        page: usize,
+        /// Cursor for the next page, taken from the previous response
+        #[arg(long)]
+        cursor: Option<String>,
+        #[command(flatten)]
+        pages: PageArgs,
         /// Order by field
         #[arg(long, default_value = "volume_usd")]
         order_by: String,
         /// Sort order
         #[arg(long, default_value = "desc")]
         sort: String,
+        #[command(flatten)]
+        view: ViewArgs,
     },
 
     /// Filter tokens on a network by volume, liquidity, FDV, txns, creation date
@@ -325,14 +528,16 @@ enum Commands {
         after_help = "EXAMPLES:\n  dexpaprika-cli filter-tokens ethereum --volume-24h-min 100000\n  dexpaprika-cli filter-tokens solana --fdv-min 1000000 --sort-by liquidity_usd"
     )]
     FilterTokens {
-        /// Network ID (e.g., ethereum, solana)
-        network: String,
+        #[command(flatten)]
+        networks: NetworkArgs,
         /// Maximum number of results (max 100)
         #[arg(long, default_value = "10")]
         limit: usize,
-        /// Page number (1-indexed)
-        #[arg(long, default_value = "1")]
-        page: usize,
+        /// Cursor for the next page, taken from the previous response
+        #[arg(long)]
+        cursor: Option<String>,
+        #[command(flatten)]
+        pages: PageArgs,
         /// Sort by field (volume_24h, volume_7d, liquidity_usd, txns_24h, created_at, fdv)
         #[arg(long, default_value = "volume_24h")]
         sort_by: String,
@@ -369,6 +574,8 @@ enum Commands {
         /// Only tokens created before this UNIX timestamp
         #[arg(long)]
         created_before: Option<u64>,
+        #[command(flatten)]
+        view: ViewArgs,
     },
 
     /// Get top tokens on a network ranked by volume, price, liquidity, or activity
@@ -377,20 +584,30 @@ enum Commands {
         after_help = "EXAMPLES:\n  dexpaprika-cli top-tokens ethereum\n  dexpaprika-cli top-tokens solana --limit 20\n  dexpaprika-cli top-tokens ethereum --order-by price_change --sort asc"
     )]
     TopTokens {
-        /// Network ID (e.g., ethereum, solana, bsc)
-        network: String,
+        #[command(flatten)]
+        networks: NetworkArgs,
         /// Maximum number of results (max 100)
         #[arg(long, default_value = "20")]
         limit: usize,
-        /// Page number (1-indexed)
-        #[arg(long, default_value = "1")]
-        page: usize,
-        /// Order by field (volume_24h, price_usd, liquidity_usd, txns, price_change)
+        /// Cursor for the next page, taken from the previous response
+        #[arg(long)]
+        cursor: Option<String>,
+        #[command(flatten)]
+        pages: PageArgs,
+        /// Order by field (volume_24h, liquidity_usd, txns, price_change, fdv). The API
+        /// can't order tokens by price: use --sort-local price_usd
         #[arg(long, default_value = "volume_24h")]
         order_by: String,
         /// Sort direction (asc, desc)
         #[arg(long, default_value = "desc")]
         sort: String,
+        /// Add a column with each token's hourly closes over the last day, taken
+        /// from its deepest pool. Two extra requests per row; one page of one
+        /// network, table output only
+        #[arg(long)]
+        sparkline: bool,
+        #[command(flatten)]
+        view: ViewArgs,
     },
 
     /// Get batch prices for multiple tokens
@@ -403,6 +620,8 @@ enum Commands {
         /// Comma-separated token addresses (max 10)
         #[arg(long)]
         tokens: String,
+        #[command(flatten)]
+        view: ViewArgs,
     },
 
     /// Search for tokens, pools, and DEXes across all networks
@@ -500,44 +719,114 @@ enum Commands {
         #[command(subcommand)]
         command: ConfigCommands,
     },
+
+    /// Manage the on-disk response cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
 }
 
+impl Cli {
+    /// The format this run prints in, errors included: `--query` works on
+    /// JSON, so it turns table output into JSON and refuses the other formats.
+    pub(crate) fn output_format(&self) -> anyhow::Result<OutputFormat> {
+        match (&self.query, self.output) {
+            (Some(_), OutputFormat::Table) => Ok(OutputFormat::Json),
+            (Some(_), OutputFormat::Ndjson | OutputFormat::Csv | OutputFormat::Tsv) => {
+                anyhow::bail!("--query reshapes JSON output. Drop --output, or use --output json.")
+            }
+            (_, output) => Ok(output),
+        }
+    }
+}
+
+/// Run `cli`'s command, printing in `output`, the format
+/// [`Cli::output_format`] settled on.
 pub(crate) fn run(
     cli: Cli,
+    output: OutputFormat,
 ) -> std::pin::Pin<Box<dyn std::future::Future<Output = anyhow::Result<()>> + Send>> {
-    Box::pin(run_inner(cli))
+    let context = output::context::RenderContext {
+        query: cli.query.clone(),
+        style: config::resolve_table_style(cli.table_style),
+        wide: cli.wide,
+    };
+    Box::pin(output::context::scope(context, run_inner(cli, output)))
 }
 
-async fn run_inner(cli: Cli) -> anyhow::Result<()> {
+async fn run_inner(cli: Cli, output: OutputFormat) -> anyhow::Result<()> {
     // Keyless unless a key is configured: --api-key, then DEXPAPRIKA_API_KEY,
     // then ~/.dexpaprika/config.json. No key keeps the previous behaviour.
     let api_key = config::resolve_api_key(cli.api_key.as_deref());
-    let client = client::ApiClient::with_api_key(api_key);
-    let output = cli.output;
+    // Hosts resolve the same way: flag, environment, config file, public default.
+    let api_base = config::resolve_api_base_url(cli.api_url.as_deref())?;
+    let streaming_base = config::resolve_streaming_base_url(cli.streaming_url.as_deref())?;
+    let retries = config::resolve_retries(cli.retries);
+    let rate_limit = config::resolve_rate_limit(cli.rate_limit, api_key.as_deref());
+    let cassette = match (&cli.record, &cli.replay) {
+        (Some(dir), _) => Some(cassette::Cassette::record(dir)?),
+        (None, Some(dir)) => Some(cassette::Cassette::replay(dir)?),
+        (None, None) => None,
+    };
+    // A cassette has to see every request, so it overrides the cache.
+    let use_cache = cassette.is_none() && config::resolve_cache(cli.cache, cli.no_cache);
+    let client = client::ApiClient::with_api_key(api_key)
+        .with_base_urls(&api_base, &streaming_base)
+        .with_retry_policy(retry::RetryPolicy::with_max_retries(retries))
+        .with_rate_limit(rate_limit)
+        .with_cache(
+            use_cache
+                .then(|| config::cache_dir().map(cache::ResponseCache::new))
+                .transpose()?,
+        )
+        .with_cassette(cassette);
     let raw = cli.raw;
 
     match cli.command {
         Commands::Stats => commands::stats::execute(&client, output, raw).await,
-        Commands::Networks => commands::networks::execute_networks(&client, output, raw).await,
+        Commands::Networks { view } => {
+            let view = view.resolve("networks");
+            commands::networks::execute_networks(&client, &view, output, raw).await
+        }
         Commands::Dexes {
             network,
             limit,
             page,
-        } => commands::networks::execute_dexes(&client, &network, limit, page, output, raw).await,
+            view,
+        } => {
+            let view = view.resolve("dexes");
+            commands::networks::execute_dexes(&client, &network, limit, page, &view, output, raw)
+                .await
+        }
         Commands::Pools {
-            network,
+            networks,
             limit,
-            page,
+            cursor,
+            pages,
             order_by,
             sort,
+            sparkline,
+            view,
         } => {
+            let view = view.resolve("pools");
             commands::pools::execute_pools(
-                &client, &network, limit, page, &order_by, &sort, output, raw,
+                &client,
+                &networks,
+                limit,
+                cursor.as_deref(),
+                pages,
+                &order_by,
+                &sort,
+                sparkline,
+                &view,
+                output,
+                raw,
             )
             .await
         }
         Commands::PoolFilter {
-            network,
+            networks,
             volume_24h_min,
             volume_24h_max,
             volume_7d_min,
@@ -558,11 +847,14 @@ async fn run_inner(cli: Cli) -> anyhow::Result<()> {
             sort_by,
             sort_dir,
             limit,
-            page,
+            cursor,
+            pages,
+            view,
         } => {
+            let view = view.resolve("pool-filter");
             commands::pools::execute_pool_filter(
                 &client,
-                &network,
+                &networks,
                 volume_24h_min,
                 volume_24h_max,
                 volume_7d_min,
@@ -585,7 +877,9 @@ async fn run_inner(cli: Cli) -> anyhow::Result<()> {
                 &sort_by,
                 &sort_dir,
                 limit,
-                page,
+                cursor.as_deref(),
+                pages,
+                &view,
                 output,
                 raw,
             )
@@ -611,17 +905,22 @@ async fn run_inner(cli: Cli) -> anyhow::Result<()> {
             dex,
             limit,
             cursor,
+            pages,
             order_by,
             sort,
+            view,
         } => {
+            let view = view.resolve("dex-pools");
             commands::pools::execute_dex_pools(
                 &client,
                 &network,
                 &dex,
                 limit,
                 cursor.as_deref(),
+                pages,
                 &order_by,
                 &sort,
+                &view,
                 output,
                 raw,
             )
@@ -632,17 +931,57 @@ async fn run_inner(cli: Cli) -> anyhow::Result<()> {
             pool_address,
             limit,
             cursor,
+            pages,
             from,
             to,
+            follow,
+            every,
+            aggregate,
+            trades,
+            view,
         } => {
+            // The address rows have fields of their own, so they have a
+            // preset of their own.
+            let view = view.resolve(if aggregate.is_some() {
+                "transactions-aggregate"
+            } else {
+                "transactions"
+            });
+            let trades = trades.resolve()?;
             commands::pools::execute_transactions(
                 &client,
                 &network,
                 &pool_address,
                 limit,
                 cursor.as_deref(),
+                pages,
                 from,
                 to,
+                follow.then_some(every),
+                aggregate,
+                &trades,
+                &view,
+                output,
+                raw,
+            )
+            .await
+        }
+        Commands::TransactionsExport {
+            network,
+            pool_address,
+            since,
+            until,
+            out,
+            checkpoint,
+        } => {
+            commands::export::execute_transactions_export(
+                &client,
+                &network,
+                &pool_address,
+                &since,
+                until.as_deref(),
+                &out,
+                checkpoint,
                 output,
                 raw,
             )
@@ -656,7 +995,11 @@ async fn run_inner(cli: Cli) -> anyhow::Result<()> {
             interval,
             limit,
             inversed,
+            chart,
+            indicators,
+            view,
         } => {
+            let view = view.resolve("pool-ohlcv");
             commands::pools::execute_ohlcv(
                 &client,
                 &network,
@@ -666,6 +1009,35 @@ async fn run_inner(cli: Cli) -> anyhow::Result<()> {
                 &interval,
                 limit,
                 inversed,
+                chart,
+                &indicators,
+                &view,
+                output,
+                raw,
+            )
+            .await
+        }
+        Commands::Candles {
+            network,
+            pool_address,
+            from,
+            to,
+            interval,
+            base,
+            max_pages,
+            view,
+        } => {
+            let view = view.resolve("candles");
+            commands::candles::execute_candles(
+                &client,
+                &network,
+                &pool_address,
+                &from,
+                to.as_deref(),
+                &interval,
+                &base,
+                max_pages,
+                &view,
                 output,
                 raw,
             )
@@ -679,27 +1051,33 @@ async fn run_inner(cli: Cli) -> anyhow::Result<()> {
             network,
             token_address,
             limit,
-            page,
+            cursor,
+            pages,
             order_by,
             sort,
+            view,
         } => {
+            let view = view.resolve("token-pools");
             commands::tokens::execute_token_pools(
                 &client,
                 &network,
                 &token_address,
                 limit,
-                page,
+                cursor.as_deref(),
+                pages,
                 &order_by,
                 &sort,
+                &view,
                 output,
                 raw,
             )
             .await
         }
         Commands::FilterTokens {
-            network,
+            networks,
             limit,
-            page,
+            cursor,
+            pages,
             sort_by,
             sort_dir,
             volume_24h_min,
@@ -712,12 +1090,15 @@ async fn run_inner(cli: Cli) -> anyhow::Result<()> {
             price_change_24h_max,
             created_after,
             created_before,
+            view,
         } => {
+            let view = view.resolve("filter-tokens");
             commands::tokens::execute_filter_tokens(
                 &client,
-                &network,
+                &networks,
                 limit,
-                page,
+                cursor.as_deref(),
+                pages,
                 &sort_by,
                 &sort_dir,
                 volume_24h_min,
@@ -730,25 +1111,45 @@ async fn run_inner(cli: Cli) -> anyhow::Result<()> {
                 price_change_24h_max,
                 created_after,
                 created_before,
+                &view,
                 output,
                 raw,
             )
             .await
         }
         Commands::TopTokens {
-            network,
+            networks,
             limit,
-            page,
+            cursor,
+            pages,
             order_by,
             sort,
+            sparkline,
+            view,
         } => {
+            let view = view.resolve("top-tokens");
             commands::tokens::execute_top_tokens(
-                &client, &network, limit, page, &order_by, &sort, output, raw,
+                &client,
+                &networks,
+                limit,
+                cursor.as_deref(),
+                pages,
+                &order_by,
+                &sort,
+                sparkline,
+                &view,
+                output,
+                raw,
             )
             .await
         }
-        Commands::Prices { network, tokens } => {
-            commands::tokens::execute_prices(&client, &network, &tokens, output, raw).await
+        Commands::Prices {
+            network,
+            tokens,
+            view,
+        } => {
+            let view = view.resolve("prices");
+            commands::tokens::execute_prices(&client, &network, &tokens, &view, output, raw).await
         }
         Commands::Search { query } => commands::search::execute(&client, &query, output, raw).await,
         Commands::Stream {
@@ -796,28 +1197,50 @@ async fn run_inner(cli: Cli) -> anyhow::Result<()> {
         }
         Commands::Onboard => commands::onboard::execute(),
         Commands::Config { command } => match command {
-            ConfigCommands::Show => commands::config_cmd::show(cli.api_key.as_deref()).await,
-            ConfigCommands::SetKey { key } => commands::config_cmd::set_key(&key).await,
+            ConfigCommands::Show => {
+                commands::config_cmd::show(&client, cli.api_key.as_deref()).await
+            }
+            ConfigCommands::SetKey { key } => commands::config_cmd::set_key(&client, &key).await,
             ConfigCommands::Delete => commands::config_cmd::delete(),
         },
+        Commands::Cache { command } => match command {
+            CacheCommands::Clear => commands::cache_cmd::clear(),
+        },
     }
 }
 
 #[tokio::main]
 async fn main() -> ExitCode {
     let cli = Cli::parse();
-    let output = cli.output;
+    let (output, result) = match cli.output_format() {
+        Ok(output) => (output, run(cli, output).await),
+        Err(e) => (cli.output, Err(e)),
+    };
 
-    if let Err(e) = run(cli).await {
+    if let Err(e) = result {
+        // API failures carry their own exit code and a stable `code` name;
+        // anything else is the catch-all 1.
+        let api_error = error::find_api_error(&e);
+        let exit_code = api_error.map(error::ApiError::exit_code).unwrap_or(1);
+        // An error a crawl already put into the document it printed goes
+        // to stderr, so stdout holds that one document.
+        let reported = e.is::<output::rows::Reported>();
         match output {
-            OutputFormat::Json => {
-                println!("{}", serde_json::json!({"error": e.to_string()}));
+            OutputFormat::Json | OutputFormat::Ndjson if !reported => {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "error": e.to_string(),
+                        "code": api_error.map(error::ApiError::code).unwrap_or("error"),
+                        "exit_code": exit_code,
+                    })
+                );
             }
-            OutputFormat::Table => {
+            _ => {
                 eprintln!("Error: {e}");
             }
         }
-        return ExitCode::FAILURE;
+        return ExitCode::from(exit_code);
     }
     ExitCode::SUCCESS
 }
@@ -903,6 +1326,86 @@ mod tests {
         );
     }
 
+    #[test]
+    fn search_backed_lists_take_a_cursor_and_page_caps_instead_of_a_page_number() {
+        for command in ["pools", "pool-filter", "top-tokens", "filter-tokens"] {
+            let with_page =
+                Cli::try_parse_from(["dexpaprika-cli", command, "ethereum", "--page", "2"]);
+            assert!(with_page.is_err(), "{command} must reject --page");
+
+            let cli = Cli::try_parse_from([
+                "dexpaprika-cli",
+                command,
+                "ethereum",
+                "--cursor",
+                "abc",
+                "--max-pages",
+                "3",
+            ])
+            .unwrap_or_else(|e| panic!("{command} should take --cursor and --max-pages: {e}"));
+            let (cursor, pages) = match cli.command {
+                Commands::Pools { cursor, pages, .. }
+                | Commands::PoolFilter { cursor, pages, .. }
+                | Commands::TopTokens { cursor, pages, .. }
+                | Commands::FilterTokens { cursor, pages, .. } => (cursor, pages),
+                _ => panic!("expected {command}"),
+            };
+            assert_eq!(cursor.as_deref(), Some("abc"));
+            assert_eq!(pages.max_pages, Some(3));
+            assert!(pages.crawling());
+        }
+
+        let zero = Cli::try_parse_from(["dexpaprika-cli", "pools", "ethereum", "--max-pages", "0"]);
+        assert!(zero.is_err(), "--max-pages 0 would fetch nothing");
+    }
+
+    #[test]
+    fn search_backed_lists_take_one_network_or_several() {
+        for command in ["pools", "pool-filter", "top-tokens", "filter-tokens"] {
+            let cli =
+                Cli::try_parse_from(["dexpaprika-cli", command, "--networks", "ethereum,base"])
+                    .unwrap_or_else(|e| panic!("{command} should take --networks: {e}"));
+            let networks = match cli.command {
+                Commands::Pools { networks, .. }
+                | Commands::PoolFilter { networks, .. }
+                | Commands::TopTokens { networks, .. }
+                | Commands::FilterTokens { networks, .. } => networks,
+                _ => panic!("expected {command}"),
+            };
+            assert_eq!(networks.network, None);
+            assert_eq!(
+                networks.networks,
+                Some(vec!["ethereum".to_string(), "base".to_string()])
+            );
+
+            assert!(Cli::try_parse_from(["dexpaprika-cli", command, "all"]).is_ok());
+            assert!(
+                Cli::try_parse_from(["dexpaprika-cli", command]).is_err(),
+                "{command} needs a network"
+            );
+            assert!(
+                Cli::try_parse_from(["dexpaprika-cli", command, "ethereum", "--networks", "base"])
+                    .is_err(),
+                "{command} takes a network or --networks, not both"
+            );
+        }
+    }
+
+    #[test]
+    fn fields_split_on_commas() {
+        let cli =
+            Cli::try_parse_from(["dexpaprika-cli", "networks", "--fields", "id,display_name"])
+                .unwrap();
+        let Commands::Networks { view } = cli.command else {
+            panic!("expected networks");
+        };
+        assert_eq!(
+            view.fields,
+            Some(vec!["id".to_string(), "display_name".to_string()])
+        );
+        assert!(Cli::try_parse_from(["dexpaprika-cli", "search", "x", "--fields", "id"]).is_err());
+    }
+
     #[test]
     fn pool_filter_accepts_negative_price_change_bounds() {
         // "down at least 20 percent" is a max of -20. Without
@@ -1022,4 +1525,39 @@ mod tests {
         ]);
         assert_eq!(bounds.price_change_24h_min, Some(-0.5));
     }
+
+    #[tokio::test]
+    async fn each_run_gets_the_output_flags_it_was_given() {
+        let run = |args: &[&str]| {
+            let cli = Cli::try_parse_from([&["dexpaprika-cli"], args].concat()).unwrap();
+            let output = cli.output_format().unwrap();
+            run(cli, output)
+        };
+        // Indexing an object by number fails, so the query shows whether
+        // it was applied.
+        let queried = ["attribution", "--query", ".[0]"];
+        assert!(run(&["attribution"]).await.is_ok());
+        assert!(run(&queried).await.is_err());
+        assert!(run(&["attribution"]).await.is_ok());
+    }
+
+    #[test]
+    fn a_query_prints_json_and_refuses_the_formats_it_cannot_reshape() {
+        let format = |args: &[&str]| {
+            Cli::try_parse_from([&["dexpaprika-cli", "stats"], args].concat())
+                .unwrap()
+                .output_format()
+        };
+        assert!(matches!(format(&[]), Ok(OutputFormat::Table)));
+        assert!(matches!(format(&["--query", ".x"]), Ok(OutputFormat::Json)));
+        assert!(matches!(
+            format(&["--query", ".x", "--output", "json"]),
+            Ok(OutputFormat::Json)
+        ));
+        assert!(format(&["--query", ".x", "--output", "csv"]).is_err());
+        assert!(matches!(
+            format(&["--output", "csv"]),
+            Ok(OutputFormat::Csv)
+        ));
+    }
 }
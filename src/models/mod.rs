@@ -0,0 +1,21 @@
+//! Response types for the REST endpoints, as they come off the wire.
+//!
+//! Almost every field is an `Option`: the API omits fields freely, and a
+//! missing number should show up as a blank cell, not as a failed decode.
+
+pub mod networks;
+pub mod pools;
+pub mod search;
+pub mod tokens;
+
+pub use networks::{Dex, DexStats, DexesResponse, Network};
+pub use pools::{
+    PoolDetail, PoolDetailPeriod, PoolDetailPriceStats, PoolOhlcv, PoolSearchItem,
+    PoolSearchResponse, PoolToken, PoolTransaction, TradeSide, TransactionsPageInfo,
+    TransactionsResponse,
+};
+pub use search::{DexSearchDex, DexSearchPool, DexSearchResult, DexSearchToken};
+pub use tokens::{
+    TokenDetail, TokenPeriodStats, TokenPrice, TokenPriceStats, TokenSearchItem,
+    TokenSearchResponse, TokenSummary,
+};
@@ -0,0 +1,36 @@
+//! Networks, their DEXes, and the global counts from `/stats`.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Network {
+    pub id: String,
+    pub display_name: Option<String>,
+    pub volume_usd_24h: Option<f64>,
+    pub txns_24h: Option<i64>,
+    pub pools_count: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DexesResponse {
+    pub dexes: Vec<Dex>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Dex {
+    pub dex_id: Option<String>,
+    pub dex_name: Option<String>,
+    pub chain: Option<String>,
+    pub protocol: Option<String>,
+    pub volume_usd_24h: Option<f64>,
+    pub txns_24h: Option<i64>,
+    pub pools_count: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DexStats {
+    pub chains: Option<i64>,
+    pub factories: Option<i64>,
+    pub pools: Option<i64>,
+    pub tokens: Option<i64>,
+}
@@ -0,0 +1,336 @@
+//! Pool shapes: detail, transactions, OHLCV and the search envelope.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PoolToken {
+    /// Token contract address (DexPaprika uses "id" for address in pool tokens)
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    #[serde(flatten)]
+    pub extra: Option<std::collections::BTreeMap<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PoolDetailPeriod {
+    pub last_price_usd_change: Option<f64>,
+    pub volume_usd: Option<f64>,
+    pub buys: Option<i64>,
+    pub sells: Option<i64>,
+    pub txns: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PoolDetailPriceStats {
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PoolDetail {
+    pub id: Option<String>,
+    pub chain: Option<String>,
+    pub dex_id: Option<String>,
+    pub dex_name: Option<String>,
+    pub factory_id: Option<String>,
+    #[serde(default)]
+    pub fee: Option<serde_json::Value>,
+    pub created_at: Option<String>,
+    pub created_at_block_number: Option<i64>,
+    pub last_price: Option<f64>,
+    pub last_price_usd: Option<f64>,
+    pub price_time: Option<String>,
+    pub price_stats: Option<PoolDetailPriceStats>,
+    pub token_reserves: Option<serde_json::Value>,
+    pub tokens: Option<Vec<PoolToken>>,
+    #[serde(rename = "24h")]
+    pub h24: Option<PoolDetailPeriod>,
+    #[serde(rename = "6h")]
+    pub h6: Option<PoolDetailPeriod>,
+    #[serde(rename = "1h")]
+    pub h1: Option<PoolDetailPeriod>,
+    #[serde(rename = "30m")]
+    pub m30: Option<PoolDetailPeriod>,
+    #[serde(rename = "15m")]
+    pub m15: Option<PoolDetailPeriod>,
+    #[serde(rename = "5m")]
+    pub m5: Option<PoolDetailPeriod>,
+}
+
+/// Wrapper for paginated transaction responses.
+///
+/// The cursor for the next page has been seen both at the top level, as on the
+/// search endpoints, and nested under `page_info`; `next_cursor()` reads
+/// either.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TransactionsResponse {
+    pub transactions: Vec<PoolTransaction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_next_page: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_info: Option<TransactionsPageInfo>,
+}
+
+impl TransactionsResponse {
+    /// The cursor for the next page, wherever the API put it.
+    pub fn next_cursor(&self) -> Option<&str> {
+        self.next_cursor
+            .as_deref()
+            .or_else(|| self.page_info.as_ref()?.next_cursor.as_deref())
+            .filter(|c| !c.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TransactionsPageInfo {
+    pub next_cursor: Option<String>,
+    #[serde(flatten)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PoolTransaction {
+    pub id: Option<String>,
+    pub chain: Option<String>,
+    pub token_0: Option<String>,
+    pub token_0_symbol: Option<String>,
+    pub token_1: Option<String>,
+    pub token_1_symbol: Option<String>,
+    pub amount_0: Option<serde_json::Value>,
+    pub amount_1: Option<serde_json::Value>,
+    pub volume_0: Option<f64>,
+    pub volume_1: Option<f64>,
+    pub price_0_usd: Option<f64>,
+    pub price_1_usd: Option<f64>,
+    pub created_at: Option<String>,
+    /// The fields the typed ones don't cover, such as the block and log
+    /// index, kept in name order so every run writes the same columns.
+    #[serde(flatten)]
+    pub extra: Option<std::collections::BTreeMap<String, serde_json::Value>>,
+}
+
+/// Which way a swap went for one token of the pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// Fields of a transaction's `extra` that name who made it, tried in this
+/// order. Chains differ in which of them they send. The originator comes
+/// first: on EVM swap events `sender` is whichever contract called the pool,
+/// usually a router shared by every wallet that trades through it.
+pub const PARTICIPANT_FIELDS: &[&str] = &["from", "maker", "trader", "sender", "recipient"];
+
+impl PoolTransaction {
+    /// The address behind the swap: the first of `PARTICIPANT_FIELDS` the
+    /// transaction carries.
+    pub fn participant(&self) -> Option<&str> {
+        let extra = self.extra.as_ref()?;
+        PARTICIPANT_FIELDS
+            .iter()
+            .filter_map(|field| extra.get(*field)?.as_str())
+            .find(|address| !address.is_empty())
+    }
+
+    /// Where `token` sits in the pair: `0` or `1`, or the symbol or address
+    /// of one of the two, in any case.
+    pub fn token_index(&self, token: &str) -> Option<usize> {
+        match token.trim() {
+            "0" => return Some(0),
+            "1" => return Some(1),
+            _ => {}
+        }
+        let is = |value: &Option<String>| {
+            value
+                .as_deref()
+                .is_some_and(|v| v.eq_ignore_ascii_case(token.trim()))
+        };
+        if is(&self.token_0_symbol) || is(&self.token_0) {
+            Some(0)
+        } else if is(&self.token_1_symbol) || is(&self.token_1) {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    /// The signed amount of token `token` (0 or 1), as the pool saw it:
+    /// negative when the pool paid it out.
+    pub fn amount(&self, token: usize) -> Option<f64> {
+        let amount = if token == 1 {
+            &self.amount_1
+        } else {
+            &self.amount_0
+        };
+        amount.as_ref().and_then(number)
+    }
+
+    /// What the swap was worth in USD, counted on the side of token `token`:
+    /// its volume, or else its amount, at its USD price.
+    pub fn usd_value(&self, token: usize) -> Option<f64> {
+        let (volume, price) = if token == 1 {
+            (self.volume_1, self.price_1_usd)
+        } else {
+            (self.volume_0, self.price_0_usd)
+        };
+        Some(volume.or_else(|| self.amount(token))?.abs() * price?)
+    }
+
+    /// Whether the swap bought or sold token `token`. A buy takes the token
+    /// out of the pool; a swap with no amount for it has no side.
+    pub fn side(&self, token: usize) -> Option<TradeSide> {
+        let amount = self.amount(token)?;
+        if amount < 0.0 {
+            Some(TradeSide::Buy)
+        } else if amount > 0.0 {
+            Some(TradeSide::Sell)
+        } else {
+            None
+        }
+    }
+}
+
+/// Amounts come as numbers or as decimal strings.
+pub(crate) fn number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PoolOhlcv {
+    pub time_open: Option<String>,
+    pub time_close: Option<String>,
+    pub open: Option<f64>,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub close: Option<f64>,
+    pub volume: Option<f64>,
+}
+
+/// Unified response for the cursor-paginated `/networks/{network}/pools/search`
+/// endpoint. Backs both the pool list and the pool filter commands.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PoolSearchResponse {
+    #[serde(default)]
+    pub results: Vec<PoolSearchItem>,
+    pub has_next_page: Option<bool>,
+    pub next_cursor: Option<String>,
+}
+
+/// A single result item from `/networks/{network}/pools/search`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PoolSearchItem {
+    /// Pool address (the search endpoint returns it under "id", not "address").
+    pub id: Option<String>,
+    pub chain: Option<String>,
+    pub dex_id: Option<String>,
+    pub dex_name: Option<String>,
+    #[serde(default)]
+    pub fee: Option<serde_json::Value>,
+    pub created_at: Option<String>,
+    pub created_at_block_number: Option<i64>,
+    pub volume_usd_24h: Option<f64>,
+    pub volume_usd_7d: Option<f64>,
+    pub volume_usd_30d: Option<f64>,
+    pub liquidity_usd: Option<f64>,
+    pub transactions_24h: Option<i64>,
+    pub price_usd: Option<f64>,
+    pub price_change_percentage_5m: Option<f64>,
+    pub price_change_percentage_1h: Option<f64>,
+    pub price_change_percentage_6h: Option<f64>,
+    pub price_change_percentage_24h: Option<f64>,
+    pub tokens: Option<Vec<PoolToken>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One result object, copied verbatim from a live
+    /// `/networks/ethereum/pools/search?limit=2&dex_name=curve` response
+    /// captured on 2026-08-05. Field names come off the wire, not from docs.
+    const LIVE_DEX_POOLS_SAMPLE: &str = r#"{
+      "results": [
+        {
+          "id": "0x4f493b7de8aac7d55f71853688b1f7c8f0243c85",
+          "dex_id": "curve",
+          "dex_name": "Curve",
+          "chain": "ethereum",
+          "volume_usd_24h": 15883391.558251368,
+          "created_at": "2025-01-25T17:20:47Z",
+          "created_at_block_number": 21702976,
+          "transactions_24h": 289,
+          "price_usd": 0.9995787501356217,
+          "price_change_percentage_5m": null,
+          "price_change_percentage_1h": 0.02422482089565938,
+          "price_change_percentage_6h": 0.009802157529374174,
+          "price_change_percentage_24h": 0.007018797950998323,
+          "fee": null,
+          "volume_usd_7d": 31781851.73428885,
+          "volume_usd_30d": 136889876.39037386,
+          "liquidity_usd": 7407910.088430515,
+          "tokens": [
+            {"id": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", "chain": "ethereum", "has_image": true}
+          ]
+        }
+      ],
+      "has_next_page": true,
+      "next_cursor": "eyJjaGFpbiI6ImV0aGVyZXVtIn0",
+      "query": {"network": "ethereum", "limit": 2, "dex_name": "curve", "order_by": "volume_usd_24h"}
+    }"#;
+
+    #[test]
+    fn dex_pools_payload_decodes_from_the_search_envelope() {
+        let resp: PoolSearchResponse =
+            serde_json::from_str(LIVE_DEX_POOLS_SAMPLE).expect("live sample must decode");
+
+        // The envelope is results/has_next_page/next_cursor, not pools/page_info.
+        assert_eq!(resp.results.len(), 1);
+        assert_eq!(resp.has_next_page, Some(true));
+        assert_eq!(
+            resp.next_cursor.as_deref(),
+            Some("eyJjaGFpbiI6ImV0aGVyZXVtIn0")
+        );
+
+        // The 24h volume field is volume_usd_24h. A bare volume_usd would decode
+        // as None here, which is exactly the silent breakage this test guards.
+        let pool = &resp.results[0];
+        assert_eq!(pool.volume_usd_24h, Some(15883391.558251368));
+        assert_eq!(pool.dex_id.as_deref(), Some("curve"));
+        assert_eq!(
+            pool.id.as_deref(),
+            Some("0x4f493b7de8aac7d55f71853688b1f7c8f0243c85")
+        );
+        assert_eq!(pool.transactions_24h, Some(289));
+    }
+
+    #[test]
+    fn a_swap_is_valued_and_sided_from_the_base_token() {
+        let tx: PoolTransaction = serde_json::from_str(
+            r#"{"token_0_symbol": "WETH", "token_1": "0xA0b8", "token_1_symbol": "USDC",
+                "amount_0": "-2", "amount_1": 5000, "volume_0": null, "volume_1": 5000.0,
+                "price_0_usd": 2500.0, "price_1_usd": 1.0, "sender": "", "recipient": "0xbob"}"#,
+        )
+        .unwrap();
+        assert_eq!(tx.token_index("weth"), Some(0));
+        assert_eq!(tx.token_index("0xa0b8"), Some(1));
+        assert_eq!(tx.token_index("DAI"), None);
+        // No volume_0, so the amount stands in for it.
+        assert_eq!(tx.usd_value(0), Some(5000.0));
+        assert_eq!(tx.side(0), Some(TradeSide::Buy));
+        assert_eq!(tx.side(1), Some(TradeSide::Sell));
+        // An empty sender names nobody, so the recipient stands in.
+        assert_eq!(tx.participant(), Some("0xbob"));
+        let routed: PoolTransaction =
+            serde_json::from_str(r#"{"sender": "0xrouter", "from": "0xalice"}"#).unwrap();
+        assert_eq!(routed.participant(), Some("0xalice"));
+    }
+}
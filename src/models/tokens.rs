@@ -0,0 +1,103 @@
+//! Token shapes: detail, batch prices and the search envelope.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TokenDetail {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub chain: Option<String>,
+    pub decimals: Option<i64>,
+    pub total_supply: Option<f64>,
+    pub description: Option<String>,
+    pub website: Option<String>,
+    pub telegram: Option<String>,
+    pub twitter: Option<String>,
+    pub has_image: Option<bool>,
+    pub added_at: Option<String>,
+    pub price_stats: Option<TokenPriceStats>,
+    pub summary: Option<TokenSummary>,
+    pub last_updated: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TokenPriceStats {
+    pub high_24h: Option<f64>,
+    pub low_24h: Option<f64>,
+    pub ath: Option<f64>,
+    pub ath_date: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TokenSummary {
+    pub chain: Option<String>,
+    pub id: Option<String>,
+    pub price_usd: Option<f64>,
+    pub fdv: Option<f64>,
+    pub liquidity_usd: Option<f64>,
+    pub pools: Option<i64>,
+    #[serde(rename = "24h")]
+    pub h24: Option<TokenPeriodStats>,
+    #[serde(rename = "6h")]
+    pub h6: Option<TokenPeriodStats>,
+    #[serde(rename = "1h")]
+    pub h1: Option<TokenPeriodStats>,
+    #[serde(rename = "30m")]
+    pub m30: Option<TokenPeriodStats>,
+    #[serde(rename = "15m")]
+    pub m15: Option<TokenPeriodStats>,
+    #[serde(rename = "5m")]
+    pub m5: Option<TokenPeriodStats>,
+    #[serde(rename = "1m")]
+    pub m1: Option<TokenPeriodStats>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TokenPeriodStats {
+    pub volume: Option<f64>,
+    pub volume_usd: Option<f64>,
+    pub sells: Option<i64>,
+    pub buys: Option<i64>,
+    pub txns: Option<i64>,
+    pub buy_usd: Option<f64>,
+    pub sell_usd: Option<f64>,
+    pub last_price_usd_change: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TokenPrice {
+    pub id: Option<String>,
+    pub chain: Option<String>,
+    pub price_usd: Option<f64>,
+}
+
+// --- Unified token search types (for GET /networks/{network}/tokens/search) ---
+
+/// Unified response for the cursor-paginated `/networks/{network}/tokens/search`
+/// endpoint. Backs both the top-tokens and the token-filter commands.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TokenSearchResponse {
+    #[serde(default)]
+    pub results: Vec<TokenSearchItem>,
+    pub has_next_page: Option<bool>,
+    pub next_cursor: Option<String>,
+}
+
+/// A single result item from `/networks/{network}/tokens/search`. The shape is
+/// flat: there is no name, symbol, buys/sells, pools count, or nested time
+/// buckets, unlike the removed list endpoints.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TokenSearchItem {
+    pub address: Option<String>,
+    pub chain: Option<String>,
+    pub created_at: Option<String>,
+    pub price_usd: Option<f64>,
+    pub volume_usd_24h: Option<f64>,
+    pub volume_usd_7d: Option<f64>,
+    pub volume_usd_30d: Option<f64>,
+    pub liquidity_usd: Option<f64>,
+    pub fdv_usd: Option<f64>,
+    pub txns_24h: Option<i64>,
+    pub price_change_percentage_24h: Option<f64>,
+}
@@ -0,0 +1,346 @@
+//! Long OHLCV ranges, fetched a chunk at a time.
+//!
+//! One request returns at most `MAX_POINTS` candles, about two weeks of 1h
+//! candles. `fetch_range` covers any number of points by splitting them into
+//! chunks aligned to the interval, one request each through the client's rate
+//! limiter, and stitching the answers into one series: ordered by open time,
+//! overlaps dropped, and the stretches of the range with no candle, its ends
+//! included, reported as `Gap`s.
+//!
+//! ```no_run
+//! use dexpaprika::ohlcv::{fetch_range, interval_seconds, parse_time};
+//! use dexpaprika::query::OhlcvQuery;
+//! use dexpaprika::ApiClient;
+//!
+//! # async fn demo() -> anyhow::Result<()> {
+//! let client = ApiClient::with_api_key(None);
+//! let step = interval_seconds("1h").unwrap();
+//! let from = parse_time("2025-01-01").unwrap();
+//! // A year of hourly candles: 24 requests.
+//! let query = OhlcvQuery::new("2025-01-01").interval("1h");
+//! let series = fetch_range(&client, "ethereum", "0x88e6...", &query, from, step, 8760).await?;
+//! println!("{} candles, {} gaps", series.candles.len(), series.gaps.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::models::PoolOhlcv;
+use crate::query::OhlcvQuery;
+use crate::ApiClient;
+
+/// The most candles the API returns for one request.
+pub const MAX_POINTS: usize = 366;
+
+/// Seconds in an interval such as `15m`, `1h` or `24h`; `s` counts seconds
+/// and `d` days.
+pub fn interval_seconds(interval: &str) -> Option<i64> {
+    let interval = interval.trim();
+    let unit = match interval.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        _ => return None,
+    };
+    let count: i64 = interval[..interval.len() - 1].parse().ok()?;
+    (count > 0).then_some(count * unit)
+}
+
+/// A `--start` or `--end` the way the API takes it: UNIX seconds,
+/// `yyyy-mm-dd` (midnight UTC) or RFC 3339.
+pub fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+        return DateTime::from_timestamp(value.parse().ok()?, 0);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date.and_hms_opt(0, 0, 0)?.and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// The error for a value of `flag` that is none of the formats `parse_time`
+/// reads.
+pub fn invalid_time(flag: &str, value: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Invalid {flag} format: \"{value}\". Use yyyy-mm-dd, unix timestamp, or RFC3339."
+    )
+}
+
+/// `parse_time` for the value of a command-line flag, with an error that
+/// names the flag.
+pub fn parse_time_flag(flag: &str, value: &str) -> Result<DateTime<Utc>> {
+    parse_time(value).ok_or_else(|| invalid_time(flag, value))
+}
+
+/// `from` rounded down to the open of the candle it falls in. Candles open on
+/// multiples of the interval, so a daily candle opens at midnight UTC.
+pub fn align(from: DateTime<Utc>, step: i64) -> i64 {
+    let at = from.timestamp();
+    at - at.rem_euclid(step)
+}
+
+/// How many candles open from `from` to `to`, both included.
+pub fn points_between(from: DateTime<Utc>, to: DateTime<Utc>, step: i64) -> usize {
+    let span = to.timestamp() - align(from, step);
+    if span < 0 {
+        0
+    } else {
+        (span / step) as usize + 1
+    }
+}
+
+/// The requests that cover `points` candles from the aligned `start`: the open
+/// time of each chunk's first candle and how many candles it asks for.
+pub fn chunks(start: i64, step: i64, points: usize) -> Vec<(i64, usize)> {
+    (0..points)
+        .step_by(MAX_POINTS)
+        .map(|first| {
+            let count = MAX_POINTS.min(points - first);
+            (start + first as i64 * step, count)
+        })
+        .collect()
+}
+
+/// A stretch of the range with no candles: from the open of the first
+/// missing candle to the open of the last.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Gap {
+    pub from: String,
+    pub to: String,
+    pub missing: usize,
+}
+
+/// The candles of a range, oldest first, and the gaps between them.
+#[derive(Debug)]
+pub struct Series {
+    pub candles: Vec<PoolOhlcv>,
+    pub gaps: Vec<Gap>,
+    /// Requests it took.
+    pub requests: usize,
+}
+
+/// `points` candles of `pool` from the candle `from` falls in, however many
+/// requests that takes. `query` supplies the interval and `inversed`; its
+/// start, end and limit are set per chunk. The requests go one after another,
+/// so the client's rate limiter paces them like any other crawl.
+pub async fn fetch_range(
+    client: &ApiClient,
+    network: &str,
+    pool: &str,
+    query: &OhlcvQuery,
+    from: DateTime<Utc>,
+    step: i64,
+    points: usize,
+) -> Result<Series> {
+    let start = align(from, step);
+    let plan = chunks(start, step, points);
+    let mut candles = Vec::new();
+    for &(chunk_start, count) in &plan {
+        let mut chunk = query.clone().limit(count);
+        chunk.start = chunk_start.to_string();
+        chunk.end = Some((chunk_start + count as i64 * step).to_string());
+        candles.extend(client.pool_ohlcv(network, pool, &chunk).await?);
+    }
+    let (candles, gaps) = merge(candles, start, last_open(start, step, points), step);
+    Ok(Series {
+        candles,
+        gaps,
+        requests: plan.len(),
+    })
+}
+
+/// The open of the last of `points` candles from the aligned `start`, or of
+/// the current candle if that one hasn't opened yet: a candle of the future
+/// isn't missing.
+pub fn last_open(start: i64, step: i64, points: usize) -> i64 {
+    let last = start + points.saturating_sub(1) as i64 * step;
+    last.min(align(Utc::now(), step))
+}
+
+/// Stitch chunked candles into one series: drop those opening outside
+/// `first..=last` and repeats of an open time, order the rest, and find the
+/// gaps in the range. A candle whose open time doesn't parse is kept, but has
+/// no say in the gaps.
+pub fn merge(
+    mut candles: Vec<PoolOhlcv>,
+    first: i64,
+    last: i64,
+    step: i64,
+) -> (Vec<PoolOhlcv>, Vec<Gap>) {
+    let open = |c: &PoolOhlcv| {
+        c.time_open
+            .as_deref()
+            .and_then(parse_time)
+            .map(|t| t.timestamp())
+    };
+    candles.retain(|c| open(c).is_none_or(|t| (first..=last).contains(&t)));
+    candles.sort_by_key(|c| open(c));
+    candles.dedup_by(|a, b| a.time_open.is_some() && a.time_open == b.time_open);
+
+    let gaps = gaps(&candles, first, last, step);
+    (candles, gaps)
+}
+
+/// The stretches of `first..=last`, candle opens `step` seconds apart, where
+/// candles are missing: between two candles, and before the first or after
+/// the last one that came back. Candles whose open time doesn't parse are
+/// passed over.
+pub fn gaps(candles: &[PoolOhlcv], first: i64, last: i64, step: i64) -> Vec<Gap> {
+    if last < first {
+        return Vec::new();
+    }
+    let mut times: Vec<i64> = candles
+        .iter()
+        .filter_map(|c| c.time_open.as_deref().and_then(parse_time))
+        .map(|t| t.timestamp())
+        .filter(|t| (first..=last).contains(t))
+        .collect();
+    times.sort_unstable();
+    times.dedup();
+    // A candle just outside each end stands in for the range's bounds.
+    let mut bounded = Vec::with_capacity(times.len() + 2);
+    bounded.push(first - step);
+    bounded.extend(times);
+    bounded.push(last + step);
+    bounded
+        .windows(2)
+        .filter(|pair| pair[1] - pair[0] > step)
+        .map(|pair| Gap {
+            from: timestamp(pair[0] + step),
+            to: timestamp(pair[1] - step),
+            missing: ((pair[1] - pair[0]) / step - 1) as usize,
+        })
+        .collect()
+}
+
+/// UNIX seconds as RFC 3339 in UTC, the way the API writes times.
+pub(crate) fn timestamp(at: i64) -> String {
+    DateTime::from_timestamp(at, 0)
+        .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(time_open: &str) -> PoolOhlcv {
+        PoolOhlcv {
+            time_open: Some(time_open.into()),
+            time_close: None,
+            open: Some(1.0),
+            high: Some(1.0),
+            low: Some(1.0),
+            close: Some(1.0),
+            volume: Some(1.0),
+        }
+    }
+
+    #[test]
+    fn times_and_intervals_parse_the_ways_the_api_takes_them() {
+        assert_eq!(interval_seconds("15m"), Some(900));
+        assert_eq!(interval_seconds("24h"), Some(86400));
+        assert_eq!(interval_seconds("10s"), Some(10));
+        assert_eq!(interval_seconds("h"), None);
+        let midnight = parse_time("2026-01-01").unwrap();
+        assert_eq!(midnight.timestamp(), 1767225600);
+        assert_eq!(parse_time("1767225600"), Some(midnight));
+        assert_eq!(parse_time("2026-01-01T00:00:00Z"), Some(midnight));
+        assert_eq!(parse_time("yesterday"), None);
+    }
+
+    #[test]
+    fn a_year_of_hours_splits_into_full_chunks_and_a_remainder() {
+        let from = parse_time("2025-01-01T00:30:00Z").unwrap();
+        let to = parse_time("2025-12-31T23:00:00Z").unwrap();
+        let points = points_between(from, to, 3600);
+        assert_eq!(points, 8760);
+        let start = align(from, 3600);
+        let plan = chunks(start, 3600, points);
+        assert_eq!(plan.len(), 24);
+        assert_eq!(plan[0], (start, MAX_POINTS));
+        assert_eq!(plan[1].0, start + MAX_POINTS as i64 * 3600);
+        assert_eq!(plan[23].1, 8760 - 23 * MAX_POINTS);
+    }
+
+    #[test]
+    fn merging_drops_overlaps_and_reports_what_is_missing() {
+        let first = parse_time("2026-01-01").unwrap().timestamp();
+        let (candles, gaps) = merge(
+            vec![
+                candle("2026-01-01T02:00:00Z"),
+                candle("2026-01-01T00:00:00Z"),
+                candle("2026-01-01T01:00:00Z"),
+                // The next chunk starts with the candle the last one ended on.
+                candle("2026-01-01T02:00:00Z"),
+                candle("2026-01-01T06:00:00Z"),
+                // Past the end of the range.
+                candle("2026-01-01T07:00:00Z"),
+            ],
+            first,
+            first + 6 * 3600,
+            3600,
+        );
+        let opens: Vec<_> = candles
+            .iter()
+            .filter_map(|c| c.time_open.as_deref())
+            .collect();
+        assert_eq!(
+            opens,
+            [
+                "2026-01-01T00:00:00Z",
+                "2026-01-01T01:00:00Z",
+                "2026-01-01T02:00:00Z",
+                "2026-01-01T06:00:00Z"
+            ]
+        );
+        assert_eq!(
+            gaps,
+            [Gap {
+                from: "2026-01-01T03:00:00Z".into(),
+                to: "2026-01-01T05:00:00Z".into(),
+                missing: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_candles_at_the_start_of_the_range_are_a_gap() {
+        let first = parse_time("2026-01-01").unwrap().timestamp();
+        let candles = [
+            candle("2026-01-01T02:00:00Z"),
+            candle("2026-01-01T03:00:00Z"),
+        ];
+        assert_eq!(
+            gaps(&candles, first, first + 3 * 3600, 3600),
+            [Gap {
+                from: "2026-01-01T00:00:00Z".into(),
+                to: "2026-01-01T01:00:00Z".into(),
+                missing: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_candles_at_the_end_of_the_range_are_a_gap() {
+        let first = parse_time("2026-01-01").unwrap().timestamp();
+        let candles = [candle("2026-01-01T00:00:00Z")];
+        assert_eq!(
+            gaps(&candles, first, first + 2 * 3600, 3600),
+            [Gap {
+                from: "2026-01-01T01:00:00Z".into(),
+                to: "2026-01-01T02:00:00Z".into(),
+                missing: 2,
+            }]
+        );
+        // A chunk that came back empty leaves the whole range missing.
+        assert_eq!(gaps(&[], first, first + 2 * 3600, 3600)[0].missing, 3);
+    }
+}
@@ -0,0 +1,339 @@
+//! Terminal charts: `pool-ohlcv --chart` and the `--sparkline` trend column.
+//!
+//! Both draw with block characters, or with plain ASCII under
+//! `--table-style ascii`, and are pure functions of the numbers so they can be
+//! tested without a terminal.
+
+use dexpaprika::models::PoolOhlcv;
+
+use crate::output::context;
+use crate::output::table_style::TableStyle;
+use crate::output::{format_price, format_usd};
+
+/// Rows of the price area of a candlestick chart.
+const PRICE_ROWS: usize = 16;
+
+/// Rows of the volume strip under it.
+const VOLUME_ROWS: usize = 4;
+
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const ASCII_BLOCKS: [char; 9] = [' ', '.', '.', ':', ':', '=', '=', '#', '#'];
+
+/// The glyphs a chart is drawn with.
+struct Glyphs {
+    up: char,
+    down: char,
+    wick: char,
+    axis: char,
+    tick: char,
+    blocks: [char; 9],
+}
+
+const UNICODE: Glyphs = Glyphs {
+    up: '█',
+    down: '░',
+    wick: '│',
+    axis: '│',
+    tick: '┤',
+    blocks: BLOCKS,
+};
+
+const ASCII: Glyphs = Glyphs {
+    up: '#',
+    down: '=',
+    wick: '|',
+    axis: '|',
+    tick: '+',
+    blocks: ASCII_BLOCKS,
+};
+
+fn active_glyphs() -> &'static Glyphs {
+    if context::style() == TableStyle::Ascii {
+        &ASCII
+    } else {
+        &UNICODE
+    }
+}
+
+/// The terminal's width in columns: `COLUMNS`, then what `stty` reports for
+/// the controlling terminal, then 80.
+pub fn terminal_width() -> usize {
+    if let Some(cols) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.trim().parse().ok())
+    {
+        return cols;
+    }
+    stty_columns().unwrap_or(80)
+}
+
+#[cfg(unix)]
+fn stty_columns() -> Option<usize> {
+    let tty = std::fs::File::open("/dev/tty").ok()?;
+    let out = std::process::Command::new("stty")
+        .arg("size")
+        .stdin(tty)
+        .output()
+        .ok()?;
+    // "rows cols"
+    String::from_utf8_lossy(&out.stdout)
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn stty_columns() -> Option<usize> {
+    None
+}
+
+/// `values` as a row of block characters, lowest to highest. A flat series
+/// sits in the middle; an empty one is `-`.
+pub fn sparkline(values: &[f64]) -> String {
+    draw_sparkline(values, active_glyphs())
+}
+
+fn draw_sparkline(values: &[f64], glyphs: &Glyphs) -> String {
+    let blocks = glyphs.blocks;
+    let values: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    let (Some(lo), Some(hi)) = (
+        values.iter().copied().reduce(f64::min),
+        values.iter().copied().reduce(f64::max),
+    ) else {
+        return "-".into();
+    };
+    values
+        .iter()
+        .map(|v| {
+            if hi == lo {
+                return blocks[4];
+            }
+            let level = ((v - lo) / (hi - lo) * 7.0).round() as usize;
+            blocks[level.min(7) + 1]
+        })
+        .collect()
+}
+
+/// A candlestick chart of `candles`, oldest on the left, with a volume strip
+/// underneath, fitted to `width` columns. When there are more candles than
+/// columns the most recent ones are kept.
+pub fn candlestick(candles: &[PoolOhlcv], width: usize) -> String {
+    draw_candlestick(candles, width, active_glyphs())
+}
+
+fn draw_candlestick(candles: &[PoolOhlcv], width: usize, glyphs: &Glyphs) -> String {
+    let mut candles: Vec<&PoolOhlcv> = candles
+        .iter()
+        .filter(|c| c.high.is_some() && c.low.is_some())
+        .collect();
+    candles.sort_by(|a, b| a.time_open.cmp(&b.time_open));
+    let total = candles.len();
+    if total == 0 {
+        return "No candles to chart.".into();
+    }
+
+    // The labels are scaled to the candles drawn, and their width decides how
+    // many are drawn: trim until the two agree.
+    let (hi, lo, max_volume, labels, label_width, plot_width) = loop {
+        let hi = candles
+            .iter()
+            .filter_map(|c| c.high)
+            .fold(f64::MIN, f64::max);
+        let lo = candles
+            .iter()
+            .filter_map(|c| c.low)
+            .fold(f64::MAX, f64::min);
+        let max_volume = candles
+            .iter()
+            .filter_map(|c| c.volume)
+            .fold(0.0_f64, f64::max);
+        let labels = [
+            format_price(hi),
+            format_price((hi + lo) / 2.0),
+            format_price(lo),
+            format_usd(max_volume),
+        ];
+        let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let plot_width = width.saturating_sub(label_width + 2).max(1);
+        if candles.len() <= plot_width {
+            break (hi, lo, max_volume, labels, label_width, plot_width);
+        }
+        candles.drain(..candles.len() - plot_width);
+    };
+    // A gap between candles when they fit with one.
+    let step = if candles.len() * 2 <= plot_width {
+        2
+    } else {
+        1
+    };
+
+    let mut out = String::new();
+    if candles.len() < total {
+        out.push_str(&format!(
+            "Last {} of {total} candles; widen the terminal to see more.\n",
+            candles.len()
+        ));
+    }
+
+    let span = if hi > lo { hi - lo } else { 1.0 };
+    let row_of = |price: f64| -> usize {
+        let r = ((hi - price) / span * PRICE_ROWS as f64).floor() as usize;
+        r.min(PRICE_ROWS - 1)
+    };
+    for row in 0..PRICE_ROWS {
+        let label = match row {
+            0 => labels[0].as_str(),
+            r if r == PRICE_ROWS / 2 => labels[1].as_str(),
+            r if r == PRICE_ROWS - 1 => labels[2].as_str(),
+            _ => "",
+        };
+        let axis = if label.is_empty() {
+            glyphs.axis
+        } else {
+            glyphs.tick
+        };
+        let mut line = format!("{label:>label_width$} {axis}");
+        for c in &candles {
+            let (high, low) = (c.high.unwrap_or(hi), c.low.unwrap_or(lo));
+            let open = c.open.unwrap_or(low);
+            let close = c.close.unwrap_or(high);
+            let (body_top, body_bottom) = (row_of(open.max(close)), row_of(open.min(close)));
+            let glyph = if (body_top..=body_bottom).contains(&row) {
+                if close >= open {
+                    glyphs.up
+                } else {
+                    glyphs.down
+                }
+            } else if (row_of(high)..=row_of(low)).contains(&row) {
+                glyphs.wick
+            } else {
+                ' '
+            };
+            line.push(glyph);
+            if step == 2 {
+                line.push(' ');
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+
+    for row in 0..VOLUME_ROWS {
+        let label = if row == 0 { labels[3].as_str() } else { "" };
+        let mut line = format!("{label:>label_width$} {}", glyphs.axis);
+        // Eighths of a row, counted up from the bottom of the strip.
+        let floor = (VOLUME_ROWS - 1 - row) * 8;
+        for c in &candles {
+            let eighths = if max_volume > 0.0 {
+                (c.volume.unwrap_or(0.0) / max_volume * (VOLUME_ROWS * 8) as f64).round() as usize
+            } else {
+                0
+            };
+            line.push(glyphs.blocks[eighths.saturating_sub(floor).min(8)]);
+            if step == 2 {
+                line.push(' ');
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+
+    let date = |c: &PoolOhlcv| -> String {
+        c.time_open
+            .as_deref()
+            .unwrap_or("-")
+            .chars()
+            .take(16)
+            .collect::<String>()
+            .replace('T', " ")
+    };
+    let (first, last) = (date(candles[0]), date(candles[candles.len() - 1]));
+    let drawn = candles.len() * step;
+    let gap = drawn
+        .saturating_sub(first.chars().count() + last.chars().count())
+        .max(2);
+    let pad = " ".repeat(label_width + 2);
+    if candles.len() > 1 {
+        out.push_str(&format!("{pad}{first}{}{last}", " ".repeat(gap)));
+    } else {
+        out.push_str(&format!("{pad}{first}"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(time: &str, open: f64, high: f64, low: f64, close: f64, volume: f64) -> PoolOhlcv {
+        PoolOhlcv {
+            time_open: Some(time.into()),
+            time_close: None,
+            open: Some(open),
+            high: Some(high),
+            low: Some(low),
+            close: Some(close),
+            volume: Some(volume),
+        }
+    }
+
+    #[test]
+    fn sparklines_span_the_lowest_to_the_highest_block() {
+        assert_eq!(draw_sparkline(&[1.0, 2.0, 3.0, 8.0], &UNICODE), "▁▂▃█");
+        assert_eq!(draw_sparkline(&[5.0, 5.0], &UNICODE), "▄▄");
+        assert_eq!(draw_sparkline(&[], &UNICODE), "-");
+    }
+
+    #[test]
+    fn candles_are_drawn_oldest_first_with_a_volume_strip() {
+        let chart = draw_candlestick(
+            &[
+                candle("2026-01-02T00:00:00Z", 3.0, 4.0, 1.0, 2.0, 50.0),
+                candle("2026-01-01T00:00:00Z", 1.0, 3.0, 1.0, 3.0, 100.0),
+            ],
+            40,
+            &UNICODE,
+        );
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines.len(), PRICE_ROWS + VOLUME_ROWS + 1);
+        // The rising day comes first and tops out at its close of 3, a third
+        // of the way down; the falling day's wick reaches the high of 4.
+        assert_eq!(lines[0], "  $4.00 ┤  │");
+        assert_eq!(lines[5], "        │█ ░");
+        assert_eq!(lines[11], "        │█ │");
+        // Half the volume fills half the strip.
+        assert_eq!(lines[PRICE_ROWS], "$100.00 │█");
+        assert_eq!(lines[PRICE_ROWS + 2], "        │█ █");
+        assert!(lines.last().unwrap().contains("2026-01-01 00:00"));
+        assert!(lines.last().unwrap().ends_with("2026-01-02 00:00"));
+    }
+
+    #[test]
+    fn only_the_most_recent_candles_fit_a_narrow_terminal() {
+        let candles: Vec<PoolOhlcv> = (0..50)
+            .map(|d| {
+                let time = format!("2026-01-01T00:{d:02}:00Z");
+                candle(&time, 1.0, 2.0, 0.5, 1.5, 10.0)
+            })
+            .collect();
+        let chart = draw_candlestick(&candles, 30, &UNICODE);
+        assert!(chart.starts_with("Last 21 of 50 candles"));
+
+        // A spike among the candles cut off doesn't set the scale of the
+        // ones drawn.
+        let mut candles = candles;
+        for c in &mut candles[..10] {
+            c.high = Some(100.0);
+            c.volume = Some(5000.0);
+        }
+        let chart = draw_candlestick(&candles, 30, &UNICODE);
+        let lines: Vec<&str> = chart.lines().collect();
+        assert!(lines[0].starts_with("Last 21 of 50 candles"), "{chart}");
+        assert!(lines[1].trim_start().starts_with("$2.00 ┤"), "{chart}");
+        assert!(
+            lines[1 + PRICE_ROWS].trim_start().starts_with("$10.00 │"),
+            "{chart}"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! `--query`, `--table-style` and `--wide`: how this run draws its output.
+//!
+//! They change the last step of printing and nothing before it, for every
+//! command alike, so rather than ride along with `OutputFormat` through each
+//! command and printer they are gathered here. `run` puts them in scope for
+//! the command it runs, and the printers read them from there. A command
+//! typed at the `shell` prompt is a run of its own, with its own flags, inside
+//! the run of `shell`. Outside any run, such as in a test, the defaults apply.
+
+use std::future::Future;
+
+use crate::output::query::Query;
+use crate::output::table_style::TableStyle;
+
+/// The flags that decide how output is drawn.
+#[derive(Clone, Debug, Default)]
+pub struct RenderContext {
+    /// Reshape JSON output before it is printed.
+    pub query: Option<Query>,
+    /// How tables are drawn.
+    pub style: TableStyle,
+    /// Full addresses, and a column for every time window.
+    pub wide: bool,
+}
+
+tokio::task_local! {
+    static CONTEXT: RenderContext;
+}
+
+/// Run `future` with `context` in effect.
+pub async fn scope<F: Future>(context: RenderContext, future: F) -> F::Output {
+    CONTEXT.scope(context, future).await
+}
+
+pub fn query() -> Option<Query> {
+    CONTEXT.try_with(|c| c.query.clone()).ok().flatten()
+}
+
+pub fn style() -> TableStyle {
+    CONTEXT.try_with(|c| c.style).unwrap_or_default()
+}
+
+pub fn wide() -> bool {
+    CONTEXT.try_with(|c| c.wide).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn an_inner_run_has_its_own_flags_and_the_outer_one_gets_its_back() {
+        let wide = RenderContext {
+            wide: true,
+            ..RenderContext::default()
+        };
+        assert!(!super::wide());
+        scope(wide, async {
+            assert!(super::wide());
+            scope(RenderContext::default(), async { assert!(!super::wide()) }).await;
+            assert!(super::wide());
+        })
+        .await;
+    }
+}
@@ -0,0 +1,227 @@
+//! `--output csv` and `--output tsv`: one line per row, for spreadsheets and
+//! the classic Unix tools.
+//!
+//! Nested objects and arrays are flattened into dotted column names, such as
+//! `tokens.0.symbol` or `price_stats.high_24h`, in the order the fields are
+//! declared; the API's untyped extra fields follow in name order. The header
+//! is the union of the columns of the first rows written. A crawl or a stream
+//! can't take a header back once it's out, so there that means the first page
+//! or event, and fields that only turn up later are left out, with a warning
+//! naming them.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, Write};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::output::node::Node;
+use crate::output::OutputFormat;
+
+/// Flatten `row` into `(column, cell)` pairs in field order. A null becomes
+/// an empty cell, so a column doesn't vanish because one row lacks a value.
+/// A row that isn't an object comes out as a single `value` column.
+pub fn flatten<T: Serialize + ?Sized>(row: &T) -> Result<Vec<(String, String)>> {
+    let node = Node::from_serialize(row)?;
+    let mut cells = Vec::new();
+    match node {
+        Node::Map(_) => node.flatten_into("", &mut cells),
+        other => other.flatten_into("value", &mut cells),
+    }
+    Ok(cells)
+}
+
+/// The header and separator of a CSV or TSV document being written, possibly
+/// over several calls.
+pub struct Delimited {
+    separator: char,
+    columns: Option<Vec<String>>,
+    /// Columns that turned up after the header, each warned about once.
+    left_out: BTreeSet<String>,
+}
+
+impl Delimited {
+    pub fn new(output: OutputFormat) -> Self {
+        Self {
+            separator: if matches!(output, OutputFormat::Tsv) {
+                '\t'
+            } else {
+                ','
+            },
+            columns: None,
+            left_out: BTreeSet::new(),
+        }
+    }
+
+    /// Carry on a document whose header, `columns`, is already written.
+    pub fn with_columns(mut self, columns: Option<Vec<String>>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// The header, once it has been written.
+    pub fn columns(&self) -> Option<&[String]> {
+        self.columns.as_deref()
+    }
+
+    /// Write `rows` to `out`, with the header first if this is the first
+    /// non-empty batch.
+    pub fn write<T: Serialize>(&mut self, out: &mut impl Write, rows: &[T]) -> Result<()> {
+        let flat = rows.iter().map(flatten).collect::<Result<Vec<_>>>()?;
+        if flat.is_empty() {
+            return Ok(());
+        }
+        if self.columns.is_none() {
+            let mut columns: Vec<String> = Vec::new();
+            for (column, _) in flat.iter().flatten() {
+                if !columns.contains(column) {
+                    columns.push(column.clone());
+                }
+            }
+            self.line(out, &columns)?;
+            self.columns = Some(columns);
+        }
+        let columns = self.columns.as_deref().unwrap_or_default();
+        let mut lines = Vec::with_capacity(flat.len());
+        let mut late = Vec::new();
+        for row in flat {
+            let mut cells: HashMap<String, String> = row.into_iter().collect();
+            for column in cells.keys() {
+                if !columns.contains(column) && self.left_out.insert(column.clone()) {
+                    late.push(column.clone());
+                }
+            }
+            lines.push(
+                columns
+                    .iter()
+                    .map(|c| cells.remove(c).unwrap_or_default())
+                    .collect::<Vec<_>>(),
+            );
+        }
+        for line in lines {
+            self.line(out, &line)?;
+        }
+        out.flush()?;
+        if !late.is_empty() {
+            late.sort();
+            eprintln!(
+                "Warning: {} turned up after the header was written, so the column is left out.",
+                late.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    fn line(&self, out: &mut impl Write, cells: &[String]) -> io::Result<()> {
+        let mut line = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            if i > 0 {
+                line.push(self.separator);
+            }
+            self.push_cell(&mut line, cell);
+        }
+        writeln!(out, "{line}")
+    }
+
+    /// CSV quotes a cell that needs it (RFC 4180). TSV has no quoting, so
+    /// tabs and line breaks inside a cell become spaces.
+    fn push_cell(&self, line: &mut String, cell: &str) {
+        if self.separator == '\t' {
+            line.extend(cell.chars().map(|c| match c {
+                '\t' | '\n' | '\r' => ' ',
+                c => c,
+            }));
+        } else if cell.contains([',', '"', '\n', '\r']) {
+            line.push('"');
+            line.push_str(&cell.replace('"', "\"\""));
+            line.push('"');
+        } else {
+            line.push_str(cell);
+        }
+    }
+}
+
+/// Print `rows` as a complete CSV or TSV document.
+pub fn print_delimited<T: Serialize>(rows: &[T], output: OutputFormat) -> Result<()> {
+    Delimited::new(output).write(&mut io::stdout().lock(), rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render<T: Serialize>(output: OutputFormat, batches: &[&[T]]) -> String {
+        let mut out = Vec::new();
+        let mut doc = Delimited::new(output);
+        for rows in batches {
+            doc.write(&mut out, rows).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn nested_fields_flatten_to_dotted_columns_in_declaration_order() {
+        #[derive(Serialize)]
+        struct Token {
+            symbol: &'static str,
+        }
+        #[derive(Serialize)]
+        struct Pool {
+            id: &'static str,
+            volume: Option<f64>,
+            tokens: Vec<Token>,
+        }
+        let pool = Pool {
+            id: "0xabc",
+            volume: None,
+            tokens: vec![Token { symbol: "WETH" }, Token { symbol: "USDC" }],
+        };
+        assert_eq!(
+            flatten(&pool).unwrap(),
+            [
+                ("id".to_string(), "0xabc".to_string()),
+                ("volume".to_string(), String::new()),
+                ("tokens.0.symbol".to_string(), "WETH".to_string()),
+                ("tokens.1.symbol".to_string(), "USDC".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_quotes_and_tsv_flattens_awkward_cells() {
+        let rows = [serde_json::json!({"name": "a, \"b\"", "n": 1.5})];
+        assert_eq!(
+            render(OutputFormat::Csv, &[&rows]),
+            "n,name\n1.5,\"a, \"\"b\"\"\"\n"
+        );
+        let rows = [serde_json::json!({"name": "tab\there"})];
+        assert_eq!(render(OutputFormat::Tsv, &[&rows]), "name\ntab here\n");
+    }
+
+    #[test]
+    fn the_first_batch_fixes_the_header() {
+        let first = [serde_json::json!({"a": 1}), serde_json::json!({"b": 2})];
+        let later = [serde_json::json!({"a": 3, "c": 4})];
+        assert_eq!(
+            render(OutputFormat::Csv, &[&[], &first, &later]),
+            "a,b\n1,\n,2\n3,\n"
+        );
+        let mut doc = Delimited::new(OutputFormat::Csv);
+        doc.write(&mut Vec::new(), &first).unwrap();
+        doc.write(&mut Vec::new(), &later).unwrap();
+        assert_eq!(doc.left_out.iter().collect::<Vec<_>>(), ["c"]);
+    }
+
+    #[test]
+    fn extra_fields_come_out_in_the_same_order_every_time() {
+        let tx: dexpaprika::models::PoolTransaction = serde_json::from_str(
+            r#"{"id": "t", "sender": "0xa", "log_index": 3, "block": 9, "recipient": "0xb"}"#,
+        )
+        .unwrap();
+        let columns: Vec<_> = flatten(&tx).unwrap().into_iter().map(|(c, _)| c).collect();
+        assert_eq!(
+            &columns[columns.len() - 4..],
+            ["block", "log_index", "recipient", "sender"]
+        );
+    }
+}
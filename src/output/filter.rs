@@ -0,0 +1,487 @@
+//! `--where`: a small expression language evaluated against each row.
+//!
+//! ```text
+//! dex_id == "uniswap_v3" && liquidity_usd > 1e6
+//! tokens.0.symbol ~ "eth" || fdv_usd / liquidity_usd < 20
+//! !(fee >= 0.01)
+//! ```
+//!
+//! Fields are named as in `--output json`, with dotted paths into nested
+//! values. Comparisons are `==`, `!=`, `<`, `<=`, `>`, `>=` and `~`, a
+//! case-insensitive substring match. `+ - * /` work on numbers, and `&&`,
+//! `||` and `!` combine the rest. A string that holds a number compares as
+//! that number, since the API sends some amounts as strings. A missing field
+//! is null: it equals `null` and fails every other comparison.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::output::node::Node;
+
+/// A parsed `--where` expression.
+#[derive(Clone)]
+pub struct Filter {
+    source: String,
+    expr: Expr,
+}
+
+impl fmt::Debug for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Filter {
+    /// Parse an expression. The error names what went wrong and where.
+    pub fn parse(source: &str) -> Result<Filter, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some((token, at)) = parser.tokens.get(parser.pos) {
+            return Err(format!("unexpected {token} at column {}", at + 1));
+        }
+        Ok(Filter {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    /// Whether `row` passes.
+    pub fn matches(&self, row: &Node) -> bool {
+        truthy(&self.expr.eval(row))
+    }
+
+    /// Every field the expression reads, as written.
+    pub fn fields(&self) -> Vec<&str> {
+        let mut fields = Vec::new();
+        self.expr.fields(&mut fields);
+        fields
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Literal(Value),
+    Field(String),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Expr {
+    fn eval(&self, row: &Node) -> Value {
+        match self {
+            Expr::Literal(value) => value.clone(),
+            Expr::Field(path) => field_value(row, path),
+            Expr::Not(inner) => Value::Bool(!truthy(&inner.eval(row))),
+            Expr::Neg(inner) => number(&inner.eval(row))
+                .map(|n| Value::from(-n))
+                .unwrap_or(Value::Null),
+            Expr::Binary(Op::Or, l, r) => Value::Bool(truthy(&l.eval(row)) || truthy(&r.eval(row))),
+            Expr::Binary(Op::And, l, r) => {
+                Value::Bool(truthy(&l.eval(row)) && truthy(&r.eval(row)))
+            }
+            Expr::Binary(op, l, r) => binary(*op, &l.eval(row), &r.eval(row)),
+        }
+    }
+
+    fn fields<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Literal(_) => {}
+            Expr::Field(path) => out.push(path),
+            Expr::Not(inner) | Expr::Neg(inner) => inner.fields(out),
+            Expr::Binary(_, l, r) => {
+                l.fields(out);
+                r.fields(out);
+            }
+        }
+    }
+}
+
+fn binary(op: Op, l: &Value, r: &Value) -> Value {
+    match op {
+        Op::Eq => Value::Bool(compare(l, r) == Some(Ordering::Equal) || l.is_null() && r.is_null()),
+        Op::Ne => {
+            Value::Bool(!(compare(l, r) == Some(Ordering::Equal) || l.is_null() && r.is_null()))
+        }
+        Op::Lt => Value::Bool(compare(l, r) == Some(Ordering::Less)),
+        Op::Le => Value::Bool(matches!(
+            compare(l, r),
+            Some(Ordering::Less | Ordering::Equal)
+        )),
+        Op::Gt => Value::Bool(compare(l, r) == Some(Ordering::Greater)),
+        Op::Ge => Value::Bool(matches!(
+            compare(l, r),
+            Some(Ordering::Greater | Ordering::Equal)
+        )),
+        Op::Contains => match (text(l), text(r)) {
+            (Some(l), Some(r)) => Value::Bool(l.to_lowercase().contains(&r.to_lowercase())),
+            _ => Value::Bool(false),
+        },
+        Op::Add | Op::Sub | Op::Mul | Op::Div => {
+            let (Some(a), Some(b)) = (number(l), number(r)) else {
+                return Value::Null;
+            };
+            let n = match op {
+                Op::Add => a + b,
+                Op::Sub => a - b,
+                Op::Mul => a * b,
+                _ => a / b,
+            };
+            // A zero divisor gives null rather than infinity.
+            serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number)
+        }
+        Op::Or | Op::And => unreachable!("short-circuited in eval"),
+    }
+}
+
+/// The value at `path` in `row`, null when it is missing. Objects and arrays
+/// come back as their JSON text.
+pub fn field_value(row: &Node, path: &str) -> Value {
+    match row.path(path) {
+        Some(Node::Scalar(value)) => value.clone(),
+        Some(node) => Value::String(node.text()),
+        None => Value::Null,
+    }
+}
+
+/// Numbers compare as numbers, a numeric string included; two other strings
+/// compare as text, which orders ISO timestamps correctly. Anything else,
+/// null included, has no order.
+pub fn compare(l: &Value, r: &Value) -> Option<Ordering> {
+    match (l, r) {
+        (Value::String(a), Value::String(b)) => match (a.parse::<f64>(), b.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b),
+            _ => Some(a.cmp(b)),
+        },
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => number(l)?.partial_cmp(&number(r)?),
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        _ => true,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "number {n}"),
+            Token::Str(s) => write!(f, "string {s:?}"),
+            Token::Ident(name) => write!(f, "`{name}`"),
+            Token::Op(op) => write!(f, "`{op}`"),
+            Token::Open => f.write_str("`(`"),
+            Token::Close => f.write_str("`)`"),
+        }
+    }
+}
+
+/// Operators, longest first so that `<=` is not read as `<` then `=`.
+const OPERATORS: [&str; 14] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "~", "!", "+", "-", "*", "/",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<(usize, char)> = source.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while let Some(&(at, c)) = chars.get(i) {
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' || c == ')' {
+            tokens.push((if c == '(' { Token::Open } else { Token::Close }, at));
+            i += 1;
+        } else if c == '"' || c == '\'' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(format!("unclosed string at column {}", at + 1)),
+                    Some(&(_, '\\')) => {
+                        if let Some(&(_, escaped)) = chars.get(i + 1) {
+                            value.push(escaped);
+                        }
+                        i += 2;
+                    }
+                    Some(&(_, q)) if q == c => {
+                        i += 1;
+                        break;
+                    }
+                    Some(&(_, other)) => {
+                        value.push(other);
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push((Token::Str(value), at));
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while chars.get(i).is_some_and(|&(_, c)| {
+                c.is_ascii_alphanumeric() || c == '.' || c == '_'
+                    // The sign of an exponent: 1e-6.
+                    || (c == '-' || c == '+') && matches!(chars[i - 1].1, 'e' | 'E')
+            }) {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().map(|&(_, c)| c).collect();
+            let n = word
+                .replace('_', "")
+                .parse::<f64>()
+                .map_err(|_| format!("`{word}` at column {} is not a number", at + 1))?;
+            tokens.push((Token::Number(n), at));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while chars
+                .get(i)
+                .is_some_and(|&(_, c)| c.is_alphanumeric() || c == '_' || c == '.')
+            {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().map(|&(_, c)| c).collect();
+            tokens.push((Token::Ident(word), at));
+        } else {
+            let rest = &source[at..];
+            let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) else {
+                return Err(format!("unexpected `{c}` at column {}", at + 1));
+            };
+            tokens.push((Token::Op(op), at));
+            i += op.chars().count();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent, loosest binding first: `||`, `&&`, comparisons, `+ -`,
+/// `* /`, then `!`, unary `-` and atoms.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat("||") {
+            left = Expr::Binary(Op::Or, Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.comparison()?;
+        while self.eat("&&") {
+            left = Expr::Binary(Op::And, Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.sum()?;
+        let op = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+            ("~", Op::Contains),
+        ]
+        .into_iter()
+        .find(|(symbol, _)| self.eat(symbol));
+        match op {
+            Some((_, op)) => Ok(Expr::Binary(op, Box::new(left), Box::new(self.sum()?))),
+            None => Ok(left),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut left = self.product()?;
+        loop {
+            let op = if self.eat("+") {
+                Op::Add
+            } else if self.eat("-") {
+                Op::Sub
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        loop {
+            let op = if self.eat("*") {
+                Op::Mul
+            } else if self.eat("/") {
+                Op::Div
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let Some((token, at)) = self.tokens.get(self.pos).cloned() else {
+            return Err("the expression ends too soon".to_string());
+        };
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Literal(Value::from(n))),
+            Token::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Token::Ident(word) => Ok(match word.as_str() {
+                "true" => Expr::Literal(Value::Bool(true)),
+                "false" => Expr::Literal(Value::Bool(false)),
+                "null" => Expr::Literal(Value::Null),
+                _ => Expr::Field(word),
+            }),
+            Token::Open => {
+                let inner = self.or()?;
+                if !matches!(self.tokens.get(self.pos), Some((Token::Close, _))) {
+                    return Err(format!("`(` at column {} is never closed", at + 1));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            other => Err(format!("unexpected {other} at column {}", at + 1)),
+        }
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        let found = matches!(self.tokens.get(self.pos), Some((Token::Op(o), _)) if *o == op);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row() -> Node {
+        Node::from_serialize(&serde_json::json!({
+            "dex_id": "uniswap_v3",
+            "fee": 0.003,
+            "liquidity_usd": 2_500_000.0,
+            "fdv_usd": 50_000_000.0,
+            "amount_usd": "1234.5",
+            "created_at": "2026-03-01T00:00:00Z",
+            "tokens": [{"symbol": "WETH"}, {"symbol": "USDC"}],
+            "volume_usd_7d": null,
+        }))
+        .unwrap()
+    }
+
+    fn check(source: &str) -> bool {
+        Filter::parse(source)
+            .unwrap_or_else(|e| panic!("{source}: {e}"))
+            .matches(&row())
+    }
+
+    #[test]
+    fn compares_fields_with_literals() {
+        assert!(check(r#"dex_id == "uniswap_v3" && liquidity_usd > 1e6"#));
+        assert!(!check(r#"dex_id == "curve" || liquidity_usd < 1_000"#));
+        assert!(check(
+            "tokens.1.symbol == 'USDC' && tokens.0.symbol ~ \"eth\""
+        ));
+        assert!(check("fdv_usd / liquidity_usd == 20"));
+        assert!(check("amount_usd > 1000 && amount_usd <= 1234.5"));
+        assert!(check(r#"created_at >= "2026-01-01""#));
+        assert!(check("!(fee >= 0.01) && -fee < 0"));
+    }
+
+    #[test]
+    fn missing_values_only_equal_null() {
+        assert!(check("volume_usd_7d == null && nope == null"));
+        assert!(!check("volume_usd_7d > 0"));
+        assert!(!check("volume_usd_7d <= 0"));
+        assert!(!check("liquidity_usd / 0 > 0"));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert!(check("fee > 1 && fee > 2 || fee < 1"));
+        assert!(!check("fee < 1 && (fee > 2 || fee > 1)"));
+    }
+
+    #[test]
+    fn reports_where_parsing_failed() {
+        let err = |source: &str| Filter::parse(source).unwrap_err();
+        assert_eq!(err("fee > "), "the expression ends too soon");
+        assert_eq!(err("fee > 1 1"), "unexpected number 1 at column 9");
+        assert_eq!(err("(fee > 1"), "`(` at column 1 is never closed");
+        assert_eq!(err("fee = 1"), "unexpected `=` at column 5");
+        assert_eq!(err("dex_id == \"uni"), "unclosed string at column 11");
+        assert_eq!(
+            Filter::parse("tokens.0.symbol ~ 'a' && fee > 0")
+                .unwrap()
+                .fields(),
+            ["tokens.0.symbol", "fee"]
+        );
+    }
+}
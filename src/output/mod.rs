@@ -1,14 +1,22 @@
 use anyhow::Result;
 use chrono::Utc;
 use serde::Serialize;
+use tabled::builder::Builder;
+use tabled::settings::location::ByColumnName;
 use tabled::settings::object::Columns;
-use tabled::settings::{Modify, Style, Width};
+use tabled::settings::{Modify, Remove, Width};
 use tabled::Table;
 
 #[derive(Clone, Copy, Debug, clap::ValueEnum)]
 pub enum OutputFormat {
     Table,
     Json,
+    /// One compact JSON object per row, after a `_meta` line
+    Ndjson,
+    /// One line per row, nested fields flattened to dotted column names
+    Csv,
+    /// Like csv, separated by tabs
+    Tsv,
 }
 
 // --- Attribution / _meta wrapper ---
@@ -26,6 +34,27 @@ pub struct ResponseMeta {
     pub api_docs: String,
     pub attribution: String,
     pub timestamp: String,
+    /// Present only when the response cache was consulted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<dexpaprika::cache::CacheOutcome>,
+    /// Present only for `--all` / `--max-pages` crawls.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+    /// Present only when an OHLCV series is missing candles.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gaps: Option<Vec<dexpaprika::ohlcv::Gap>>,
+    /// Present only when a crawl failed after it had started writing rows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// How far a crawl got. `next_cursor` is set when it stopped before the last
+/// page, and resumes it through `--cursor`.
+#[derive(Serialize)]
+pub struct Pagination {
+    pub pages: usize,
+    pub rows: usize,
+    pub next_cursor: Option<String>,
 }
 
 impl ResponseMeta {
@@ -36,8 +65,29 @@ impl ResponseMeta {
             api_docs: "https://api.dexpaprika.com".into(),
             attribution: "Powered by DexPaprika · Free DEX & DeFi data".into(),
             timestamp: Utc::now().to_rfc3339(),
+            cache: None,
+            pagination: None,
+            gaps: None,
+            error: None,
         }
     }
+
+    /// Record what the response cache did for the data this meta describes.
+    pub fn with_cache(mut self, cache: Option<dexpaprika::cache::CacheOutcome>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn with_pagination(mut self, pagination: Pagination) -> Self {
+        self.pagination = Some(pagination);
+        self
+    }
+
+    /// Record the gaps in an OHLCV series, if it has any.
+    pub fn with_gaps(mut self, gaps: &[dexpaprika::ohlcv::Gap]) -> Self {
+        self.gaps = (!gaps.is_empty()).then(|| gaps.to_vec());
+        self
+    }
 }
 
 // --- Shared output helpers ---
@@ -51,8 +101,9 @@ pub fn truncate(s: &str, max: usize) -> String {
     truncated
 }
 
+/// Shorten an address to `0x1234...cdef`, unless `--wide` asked for it whole.
 pub fn truncate_address(addr: &str) -> String {
-    if addr.len() <= 13 {
+    if context::wide() || addr.len() <= 13 {
         return addr.to_string();
     }
     format!("{}...{}", &addr[..6], &addr[addr.len() - 4..])
@@ -97,6 +148,17 @@ pub fn print_json<T: Serialize>(data: &T) -> Result<()> {
 }
 
 pub fn print_json_wrapped<T: Serialize>(data: &T, meta: ResponseMeta, raw: bool) -> Result<()> {
+    if let Some(query) = context::query() {
+        let doc = if raw {
+            node::Node::from_serialize(data)?
+        } else {
+            node::Node::from_serialize(&WrappedResponse { data, _meta: meta })?
+        };
+        for result in query.run(&doc)? {
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        return Ok(());
+    }
     if raw {
         println!("{}", serde_json::to_string_pretty(data)?);
     } else {
@@ -106,13 +168,74 @@ pub fn print_json_wrapped<T: Serialize>(data: &T, meta: ResponseMeta, raw: bool)
     Ok(())
 }
 
-pub fn print_detail_table(rows: Vec<[String; 2]>) {
-    let table = Table::from_iter(rows)
-        .with(Style::rounded())
-        .with(Modify::new(Columns::first()).with(Width::wrap(20)))
-        .with(Modify::new(Columns::last()).with(Width::wrap(80)))
-        .to_string();
-    println!("{table}");
+/// `print_json_wrapped` for one page of a cursor-paginated list whose rows
+/// have lost the envelope that carried `next_cursor`: it goes into
+/// `_meta.pagination`, as in a crawl, or to stderr under `--raw`.
+pub fn print_json_page<T: Serialize>(
+    rows: &[T],
+    meta: ResponseMeta,
+    next_cursor: Option<&str>,
+    raw: bool,
+) -> Result<()> {
+    let meta = meta.with_pagination(Pagination {
+        pages: 1,
+        rows: rows.len(),
+        next_cursor: next_cursor.map(str::to_string),
+    });
+    print_json_wrapped(&rows, meta, raw)?;
+    if raw {
+        eprint_more_results_hint(next_cursor.map(|_| true), next_cursor);
+    }
+    Ok(())
+}
+
+pub fn print_detail_table(mut rows: Vec<[String; 2]>) {
+    let style = context::style();
+    if style.needs_header() {
+        rows.insert(0, ["Field".into(), "Value".into()]);
+    }
+    let mut table = Table::from_iter(rows);
+    // Wrapping could split an address that --wide asked for whole.
+    if style.wraps() && !context::wide() {
+        table
+            .with(Modify::new(Columns::first()).with(Width::wrap(20)))
+            .with(Modify::new(Columns::last()).with(Width::wrap(80)));
+    }
+    println!("{}", table_style::render(table));
+}
+
+/// Remove the columns that only `--wide` shows. The list tables declare every
+/// window, so one row struct serves both widths.
+pub fn drop_wide_columns(table: &mut Table, columns: &[&str]) {
+    if !context::wide() {
+        for column in columns {
+            table.with(Remove::column(ByColumnName::new(*column)));
+        }
+    }
+}
+
+/// `table` with one more column on the right: `header` over `cells`, one per
+/// row.
+pub fn push_column(table: Table, header: &str, cells: Vec<String>) -> Table {
+    let mut builder = Builder::from(table);
+    builder.push_column(std::iter::once(header.to_string()).chain(cells));
+    builder.build()
+}
+
+/// The `--wide` companion to a detail table: a row per metric and a column per
+/// time window, for the windows the response has.
+pub fn print_windows_table(metrics: &[&str], windows: Vec<(&str, Vec<String>)>) {
+    if windows.is_empty() {
+        return;
+    }
+    let mut builder = Builder::default();
+    builder.push_record(std::iter::once("").chain(windows.iter().map(|(w, _)| *w)));
+    for (i, metric) in metrics.iter().enumerate() {
+        builder.push_record(
+            std::iter::once(metric.to_string()).chain(windows.iter().map(|(_, c)| c[i].clone())),
+        );
+    }
+    println!("{}", table_style::render(builder.build()));
 }
 
 pub fn print_dexpaprika_footer() {
@@ -123,16 +246,37 @@ pub fn print_dexpaprika_footer() {
 /// API is cursor-based (no page numbers), so when more results exist we surface
 /// the opaque cursor the caller can pass to fetch the next page.
 pub fn print_more_results_hint(has_next_page: Option<bool>, next_cursor: Option<&str>) {
-    if has_next_page == Some(true) {
-        match next_cursor {
-            Some(c) if !c.is_empty() => {
-                println!("  More results available. next_cursor: {c}");
-            }
-            _ => println!("  More results available."),
-        }
+    if let Some(hint) = more_results_hint(has_next_page, next_cursor) {
+        println!("  {hint}");
     }
 }
 
+/// The cursor hint for the formats whose stdout is all data, such as CSV: on
+/// stderr, where it can't end up in the file.
+pub fn eprint_more_results_hint(has_next_page: Option<bool>, next_cursor: Option<&str>) {
+    if let Some(hint) = more_results_hint(has_next_page, next_cursor) {
+        eprintln!("{hint}");
+    }
+}
+
+fn more_results_hint(has_next_page: Option<bool>, next_cursor: Option<&str>) -> Option<String> {
+    if has_next_page != Some(true) {
+        return None;
+    }
+    Some(match next_cursor {
+        Some(c) if !c.is_empty() => format!("More results available. next_cursor: {c}"),
+        _ => "More results available.".to_string(),
+    })
+}
+
+/// Close a list table: the attribution footer, then the cursor hint. The list
+/// tables leave this to the caller so a crawl can print one table per page and
+/// a single footer at the end.
+pub fn print_list_footer(has_next_page: Option<bool>, next_cursor: Option<&str>) {
+    print_dexpaprika_footer();
+    print_more_results_hint(has_next_page, next_cursor);
+}
+
 macro_rules! detail_field {
     ($rows:expr, $label:expr, $val:expr) => {
         $rows.push([$label.into(), $val]);
@@ -143,14 +287,24 @@ macro_rules! detail_field {
 // module declared below this point already sees detail_field!. The re-export
 // and the matching `use crate::output::{detail_field}` lines in four submodules
 // were redundant, and clippy on a current toolchain fails the build over them.
+pub mod chart;
+pub mod context;
+pub mod delimited;
+pub mod filter;
+pub mod ndjson;
 pub mod networks;
+pub mod node;
 pub mod pools;
+pub mod query;
+pub mod rows;
 pub mod search;
 pub mod stats;
 pub mod status;
 pub mod stream;
 pub mod stream_reserves;
+pub mod table_style;
 pub mod tokens;
+pub mod view;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,111 @@
+//! `--output ndjson`: one compact JSON object per line.
+//!
+//! The first line is `{"_meta": {...}}`, the attribution block the JSON
+//! output wraps around its data; `--raw` leaves it out. Every line after that
+//! is one row, so a consumer can start on the first row before the last one
+//! has arrived.
+
+use std::io::{self, Write};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::output::node::Node;
+use crate::output::query::Query;
+use crate::output::{eprint_more_results_hint, Pagination, ResponseMeta};
+
+#[derive(Serialize)]
+struct MetaLine<'a> {
+    _meta: &'a ResponseMeta,
+}
+
+/// Write the `_meta` line.
+pub fn write_meta(out: &mut impl Write, meta: &ResponseMeta) -> Result<()> {
+    writeln!(out, "{}", serde_json::to_string(&MetaLine { _meta: meta })?)?;
+    Ok(())
+}
+
+/// Write one line per row.
+pub fn write_rows<T: Serialize>(out: &mut impl Write, rows: &[T]) -> Result<()> {
+    for row in rows {
+        writeln!(out, "{}", serde_json::to_string(row)?)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// A line per row, or per result of `jq` run on each row. For output that
+/// has no end, where `--query` can only see a row at a time.
+pub fn write_lines<T: Serialize>(
+    out: &mut impl Write,
+    rows: &[T],
+    jq: Option<&Query>,
+) -> Result<()> {
+    for row in rows {
+        match jq {
+            Some(jq) => {
+                for result in jq.run(&Node::from_serialize(row)?)? {
+                    writeln!(out, "{}", serde_json::to_string(&result)?)?;
+                }
+            }
+            None => writeln!(out, "{}", serde_json::to_string(row)?)?,
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Print `rows` with the `_meta` line first, unless `raw`.
+pub fn print_rows<T: Serialize>(rows: &[T], meta: ResponseMeta, raw: bool) -> Result<()> {
+    let mut out = io::stdout().lock();
+    if !raw {
+        write_meta(&mut out, &meta)?;
+    }
+    write_rows(&mut out, rows)
+}
+
+/// Print one page of a cursor-paginated list. The rows have lost the envelope
+/// that carried `next_cursor`, so it moves into `_meta.pagination`, or to
+/// stderr under `--raw`.
+pub fn print_page<T: Serialize>(
+    rows: &[T],
+    meta: ResponseMeta,
+    next_cursor: Option<&str>,
+    raw: bool,
+) -> Result<()> {
+    let meta = meta.with_pagination(Pagination {
+        pages: 1,
+        rows: rows.len(),
+        next_cursor: next_cursor.map(str::to_string),
+    });
+    print_rows(rows, meta, raw)?;
+    if raw {
+        eprint_more_results_hint(next_cursor.map(|_| true), next_cursor);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_query_runs_on_each_row_and_prints_a_line_per_result() {
+        let rows = [
+            serde_json::json!({ "id": "a", "volume_0": 1 }),
+            serde_json::json!({ "id": "b", "volume_0": 20 }),
+            serde_json::json!({ "id": "c", "volume_0": 30 }),
+        ];
+        let jq = Query::parse("select(.volume_0 > 10) | .id").unwrap();
+        let mut out = Vec::new();
+        write_lines(&mut out, &rows, Some(&jq)).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "\"b\"\n\"c\"\n");
+
+        let mut out = Vec::new();
+        write_lines(&mut out, &rows[..1], None).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"id\":\"a\",\"volume_0\":1}\n"
+        );
+    }
+}
@@ -1,8 +1,7 @@
-use tabled::settings::Style;
 use tabled::{Table, Tabled};
 
-use crate::commands::networks::{Dex, Network};
-use crate::output::{format_usd, print_dexpaprika_footer};
+use crate::output::{format_usd, print_dexpaprika_footer, table_style};
+use dexpaprika::models::{Dex, Network};
 
 #[derive(Tabled)]
 struct NetworkRow {
@@ -39,7 +38,7 @@ pub fn print_networks_table(networks: &[Network]) {
         })
         .collect();
 
-    let table = Table::new(rows).with(Style::rounded()).to_string();
+    let table = table_style::render(Table::new(rows));
     println!("{table}");
     print_dexpaprika_footer();
 }
@@ -82,7 +81,7 @@ pub fn print_dexes_table(dexes: &[Dex]) {
         })
         .collect();
 
-    let table = Table::new(rows).with(Style::rounded()).to_string();
+    let table = table_style::render(Table::new(rows));
     println!("{table}");
     print_dexpaprika_footer();
 }
@@ -0,0 +1,194 @@
+//! A JSON value that keeps the order of object keys.
+//!
+//! `serde_json::Value` sorts keys, which is fine for lookups but not for
+//! output: columns, and the keys `--fields` picks, should come out in the
+//! order the struct declares or the user asked for. Rows get here by a round
+//! trip through JSON text, since serde_json has no way to serialize straight
+//! into anything else.
+
+use std::fmt;
+
+use anyhow::Result;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    /// Null, a bool, a number or a string.
+    Scalar(Value),
+    Map(Vec<(String, Node)>),
+    List(Vec<Node>),
+}
+
+impl Node {
+    pub const NULL: Node = Node::Scalar(Value::Null);
+
+    pub fn from_serialize<T: Serialize + ?Sized>(value: &T) -> Result<Self> {
+        Ok(serde_json::from_str(&serde_json::to_string(value)?)?)
+    }
+
+    /// The value at `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        match self {
+            Node::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// The value at a dotted path such as `tokens.0.symbol`: object keys and
+    /// array indexes, one per segment.
+    pub fn path(&self, path: &str) -> Option<&Node> {
+        path.split('.').try_fold(self, |node, segment| match node {
+            Node::Map(_) => node.get(segment),
+            Node::List(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            Node::Scalar(_) => None,
+        })
+    }
+
+    /// The keys of an object, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        let entries = match self {
+            Node::Map(entries) => entries.as_slice(),
+            _ => &[],
+        };
+        entries.iter().map(|(k, _)| k.as_str())
+    }
+
+    /// A scalar as it should read in a cell: strings bare, nulls empty, and
+    /// other values as JSON.
+    pub fn text(&self) -> String {
+        match self {
+            Node::Scalar(Value::Null) => String::new(),
+            Node::Scalar(Value::String(s)) => s.clone(),
+            // 1000.0 reads better as 1000 in a cell.
+            Node::Scalar(Value::Number(n)) if n.is_f64() => {
+                n.as_f64().map(|f| f.to_string()).unwrap_or_default()
+            }
+            Node::Scalar(v) => v.to_string(),
+            other => serde_json::to_string(other).unwrap_or_default(),
+        }
+    }
+
+    /// Flatten into `(column, cell)` pairs, nested keys joined with dots
+    /// under `prefix`. Empty objects and arrays give nothing.
+    pub fn flatten_into(&self, prefix: &str, cells: &mut Vec<(String, String)>) {
+        let child = |key: &dyn fmt::Display| {
+            if prefix.is_empty() {
+                key.to_string()
+            } else {
+                format!("{prefix}.{key}")
+            }
+        };
+        match self {
+            Node::Scalar(_) => cells.push((prefix.to_string(), self.text())),
+            Node::Map(entries) => {
+                for (key, node) in entries {
+                    node.flatten_into(&child(key), cells);
+                }
+            }
+            Node::List(items) => {
+                for (i, node) in items.iter().enumerate() {
+                    node.flatten_into(&child(&i), cells);
+                }
+            }
+        }
+    }
+}
+
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Node::Scalar(value) => value.serialize(serializer),
+            Node::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Node::List(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Node, E> {
+        Ok(Node::NULL)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Node::List(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Node::Map(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_key_order_and_number_types_through_a_round_trip() {
+        let text = r#"{"z":1,"a":1.5,"m":[{"k":"v"}],"n":null}"#;
+        let node: Node = serde_json::from_str(text).unwrap();
+        assert_eq!(node.keys().collect::<Vec<_>>(), ["z", "a", "m", "n"]);
+        assert_eq!(serde_json::to_string(&node).unwrap(), text);
+        assert_eq!(node.path("m.0.k").map(Node::text).as_deref(), Some("v"));
+        assert_eq!(node.path("m.1.k"), None);
+    }
+}
@@ -1,13 +1,18 @@
-use tabled::settings::Style;
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use tabled::builder::Builder;
+use tabled::settings::location::ByColumnName;
+use tabled::settings::Remove;
 use tabled::{Table, Tabled};
 
-use crate::commands::pools::{PoolDetail, PoolOhlcv, PoolSearchItem, PoolTransaction};
 use crate::output::{
-    format_percent, format_price, format_usd, print_detail_table, print_dexpaprika_footer,
-    truncate_address,
+    context, drop_wide_columns, format_percent, format_price, format_usd, print_detail_table,
+    print_dexpaprika_footer, print_windows_table, table_style, truncate_address,
 };
+use dexpaprika::candles::Candle;
+use dexpaprika::models::{PoolDetail, PoolOhlcv, PoolSearchItem, PoolTransaction, TradeSide};
 
-fn pool_pair(tokens: &Option<Vec<crate::commands::pools::PoolToken>>) -> String {
+fn pool_pair(tokens: &Option<Vec<dexpaprika::models::PoolToken>>) -> String {
     tokens
         .as_ref()
         .map(|ts| {
@@ -19,18 +24,53 @@ fn pool_pair(tokens: &Option<Vec<crate::commands::pools::PoolToken>>) -> String
         .unwrap_or_else(|| "-".into())
 }
 
+/// The pool search columns only `--wide` shows.
+const WIDE_POOL_COLUMNS: [&str; 5] = [
+    "Volume (7d)",
+    "Volume (30d)",
+    "5m Change",
+    "1h Change",
+    "6h Change",
+];
+
+/// A pool search table, without its Chain column unless the rows come from
+/// more than one network.
+fn search_rows_table<R: Tabled>(rows: Vec<R>, pools: &[PoolSearchItem]) -> Table {
+    let mut chains = pools.iter().map(|p| p.chain.as_deref());
+    let first = chains.next().flatten();
+    let mixed = chains.any(|c| c != first);
+    let mut table = Table::new(rows);
+    if !mixed {
+        table.with(Remove::column(ByColumnName::new("Chain")));
+    }
+    drop_wide_columns(&mut table, &WIDE_POOL_COLUMNS);
+    table
+}
+
 #[derive(Tabled)]
 struct FilterRow {
     #[tabled(rename = "Address")]
     address: String,
+    #[tabled(rename = "Chain")]
+    chain: String,
     #[tabled(rename = "DEX")]
     dex: String,
     #[tabled(rename = "Volume (24h)")]
     volume: String,
+    #[tabled(rename = "Volume (7d)")]
+    volume_7d: String,
+    #[tabled(rename = "Volume (30d)")]
+    volume_30d: String,
     #[tabled(rename = "Liquidity")]
     liquidity: String,
     #[tabled(rename = "Txns (24h)")]
     txns: String,
+    #[tabled(rename = "5m Change")]
+    change_5m: String,
+    #[tabled(rename = "1h Change")]
+    change_1h: String,
+    #[tabled(rename = "6h Change")]
+    change_6h: String,
     #[tabled(rename = "24h Change")]
     change: String,
     #[tabled(rename = "Created")]
@@ -40,10 +80,8 @@ struct FilterRow {
 /// Render filtered pools. The 24h change column is here so that filtering on a
 /// percentage puts a percentage in the output to check the result against.
 ///
-/// The table does not cover every bound the command takes. `--volume-7d-min`
-/// and `--volume-7d-max` have no column, and neither do the 6h, 1h and 5m
-/// windows; all three sets of values come back in `--output json`. Seven columns
-/// is already about as wide as this table renders comfortably.
+/// The 7d and 30d volumes and the 6h, 1h and 5m changes only get columns under
+/// `--wide`: seven columns is about as wide as this table renders comfortably.
 pub fn print_pool_filter_table(results: &[PoolSearchItem]) {
     let rows: Vec<FilterRow> = results
         .iter()
@@ -53,11 +91,20 @@ pub fn print_pool_filter_table(results: &[PoolSearchItem]) {
                 .as_deref()
                 .map(truncate_address)
                 .unwrap_or_else(|| "-".into()),
+            chain: r.chain.clone().unwrap_or_else(|| "-".into()),
             dex: r.dex_id.clone().unwrap_or_else(|| "-".into()),
             volume: r
                 .volume_usd_24h
                 .map(format_usd)
                 .unwrap_or_else(|| "-".into()),
+            volume_7d: r
+                .volume_usd_7d
+                .map(format_usd)
+                .unwrap_or_else(|| "-".into()),
+            volume_30d: r
+                .volume_usd_30d
+                .map(format_usd)
+                .unwrap_or_else(|| "-".into()),
             liquidity: r
                 .liquidity_usd
                 .map(format_usd)
@@ -66,6 +113,18 @@ pub fn print_pool_filter_table(results: &[PoolSearchItem]) {
                 .transactions_24h
                 .map(|t| t.to_string())
                 .unwrap_or_else(|| "-".into()),
+            change_5m: r
+                .price_change_percentage_5m
+                .map(format_percent)
+                .unwrap_or_else(|| "-".into()),
+            change_1h: r
+                .price_change_percentage_1h
+                .map(format_percent)
+                .unwrap_or_else(|| "-".into()),
+            change_6h: r
+                .price_change_percentage_6h
+                .map(format_percent)
+                .unwrap_or_else(|| "-".into()),
             change: r
                 .price_change_percentage_24h
                 .map(format_percent)
@@ -78,31 +137,46 @@ pub fn print_pool_filter_table(results: &[PoolSearchItem]) {
         })
         .collect();
 
-    let table = Table::new(rows).with(Style::rounded()).to_string();
-    println!("{table}");
-    print_dexpaprika_footer();
+    println!("{}", table_style::render(search_rows_table(rows, results)));
 }
 
 #[derive(Tabled)]
 struct PoolSearchRow {
     #[tabled(rename = "Pool")]
     pool: String,
+    #[tabled(rename = "Chain")]
+    chain: String,
     #[tabled(rename = "DEX")]
     dex: String,
     #[tabled(rename = "Price")]
     price: String,
     #[tabled(rename = "Volume (24h)")]
     volume: String,
+    #[tabled(rename = "Volume (7d)")]
+    volume_7d: String,
+    #[tabled(rename = "Volume (30d)")]
+    volume_30d: String,
     #[tabled(rename = "Liquidity")]
     liquidity: String,
+    #[tabled(rename = "5m Change")]
+    change_5m: String,
+    #[tabled(rename = "1h Change")]
+    change_1h: String,
+    #[tabled(rename = "6h Change")]
+    change_6h: String,
     #[tabled(rename = "24h Change")]
     change: String,
 }
 
-/// Render the pool list from the unified search endpoint. The search payload
-/// carries no token symbols, so the legacy "Pair" column is replaced by
-/// "Liquidity", which the search result does provide.
+/// Render the pool list from the unified search endpoint.
 pub fn print_pool_search_table(pools: &[PoolSearchItem]) {
+    println!("{}", table_style::render(pool_search_table(pools)));
+}
+
+/// The pool list table, unstyled. The search payload carries no token symbols,
+/// so the legacy "Pair" column is replaced by "Liquidity", which the search
+/// result does provide.
+pub fn pool_search_table(pools: &[PoolSearchItem]) -> Table {
     let rows: Vec<PoolSearchRow> = pools
         .iter()
         .map(|p| PoolSearchRow {
@@ -111,16 +185,37 @@ pub fn print_pool_search_table(pools: &[PoolSearchItem]) {
                 .as_deref()
                 .map(truncate_address)
                 .unwrap_or_else(|| "-".into()),
+            chain: p.chain.clone().unwrap_or_else(|| "-".into()),
             dex: p.dex_name.clone().unwrap_or_else(|| "-".into()),
             price: p.price_usd.map(format_price).unwrap_or_else(|| "-".into()),
             volume: p
                 .volume_usd_24h
                 .map(format_usd)
                 .unwrap_or_else(|| "-".into()),
+            volume_7d: p
+                .volume_usd_7d
+                .map(format_usd)
+                .unwrap_or_else(|| "-".into()),
+            volume_30d: p
+                .volume_usd_30d
+                .map(format_usd)
+                .unwrap_or_else(|| "-".into()),
             liquidity: p
                 .liquidity_usd
                 .map(format_usd)
                 .unwrap_or_else(|| "-".into()),
+            change_5m: p
+                .price_change_percentage_5m
+                .map(format_percent)
+                .unwrap_or_else(|| "-".into()),
+            change_1h: p
+                .price_change_percentage_1h
+                .map(format_percent)
+                .unwrap_or_else(|| "-".into()),
+            change_6h: p
+                .price_change_percentage_6h
+                .map(format_percent)
+                .unwrap_or_else(|| "-".into()),
             change: p
                 .price_change_percentage_24h
                 .map(format_percent)
@@ -128,9 +223,7 @@ pub fn print_pool_search_table(pools: &[PoolSearchItem]) {
         })
         .collect();
 
-    let table = Table::new(rows).with(Style::rounded()).to_string();
-    println!("{table}");
-    print_dexpaprika_footer();
+    search_rows_table(rows, pools)
 }
 
 pub fn print_pool_detail(pool: &PoolDetail) {
@@ -192,56 +285,59 @@ pub fn print_pool_detail(pool: &PoolDetail) {
         );
     }
 
-    if let Some(h24) = &pool.h24 {
-        detail_field!(
-            rows,
-            "Volume (24h)",
-            h24.volume_usd.map(format_usd).unwrap_or_else(|| "-".into())
-        );
-        detail_field!(
-            rows,
-            "24h Change",
-            h24.last_price_usd_change
-                .map(format_percent)
-                .unwrap_or_else(|| "-".into())
-        );
-        detail_field!(
-            rows,
-            "Buys/Sells (24h)",
-            format!("{}/{}", h24.buys.unwrap_or(0), h24.sells.unwrap_or(0))
-        );
-        detail_field!(
-            rows,
-            "Txns (24h)",
-            h24.txns
-                .map(|t| t.to_string())
-                .unwrap_or_else(|| "-".into())
-        );
-    }
+    // --wide puts every window in a table of its own below.
+    if !context::wide() {
+        if let Some(h24) = &pool.h24 {
+            detail_field!(
+                rows,
+                "Volume (24h)",
+                h24.volume_usd.map(format_usd).unwrap_or_else(|| "-".into())
+            );
+            detail_field!(
+                rows,
+                "24h Change",
+                h24.last_price_usd_change
+                    .map(format_percent)
+                    .unwrap_or_else(|| "-".into())
+            );
+            detail_field!(
+                rows,
+                "Buys/Sells (24h)",
+                format!("{}/{}", h24.buys.unwrap_or(0), h24.sells.unwrap_or(0))
+            );
+            detail_field!(
+                rows,
+                "Txns (24h)",
+                h24.txns
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| "-".into())
+            );
+        }
 
-    if let Some(h1) = &pool.h1 {
-        detail_field!(
-            rows,
-            "Volume (1h)",
-            h1.volume_usd.map(format_usd).unwrap_or_else(|| "-".into())
-        );
-        detail_field!(
-            rows,
-            "1h Change",
-            h1.last_price_usd_change
-                .map(format_percent)
-                .unwrap_or_else(|| "-".into())
-        );
-    }
+        if let Some(h1) = &pool.h1 {
+            detail_field!(
+                rows,
+                "Volume (1h)",
+                h1.volume_usd.map(format_usd).unwrap_or_else(|| "-".into())
+            );
+            detail_field!(
+                rows,
+                "1h Change",
+                h1.last_price_usd_change
+                    .map(format_percent)
+                    .unwrap_or_else(|| "-".into())
+            );
+        }
 
-    if let Some(m5) = &pool.m5 {
-        detail_field!(
-            rows,
-            "5m Change",
-            m5.last_price_usd_change
-                .map(format_percent)
-                .unwrap_or_else(|| "-".into())
-        );
+        if let Some(m5) = &pool.m5 {
+            detail_field!(
+                rows,
+                "5m Change",
+                m5.last_price_usd_change
+                    .map(format_percent)
+                    .unwrap_or_else(|| "-".into())
+            );
+        }
     }
 
     if let Some(tokens) = &pool.tokens {
@@ -262,53 +358,183 @@ pub fn print_pool_detail(pool: &PoolDetail) {
     }
 
     print_detail_table(rows);
+    if context::wide() {
+        print_windows_table(
+            &["Volume", "Change", "Buys", "Sells", "Txns"],
+            pool_windows(pool),
+        );
+    }
     print_dexpaprika_footer();
 }
 
+/// The windows a pool reports, each as its column of the `--wide` table.
+fn pool_windows(pool: &PoolDetail) -> Vec<(&'static str, Vec<String>)> {
+    let count = |n: Option<i64>| n.map(|n| n.to_string()).unwrap_or_else(|| "-".into());
+    [
+        ("24h", &pool.h24),
+        ("6h", &pool.h6),
+        ("1h", &pool.h1),
+        ("30m", &pool.m30),
+        ("15m", &pool.m15),
+        ("5m", &pool.m5),
+    ]
+    .into_iter()
+    .filter_map(|(window, period)| {
+        let p = period.as_ref()?;
+        Some((
+            window,
+            vec![
+                p.volume_usd.map(format_usd).unwrap_or_else(|| "-".into()),
+                p.last_price_usd_change
+                    .map(format_percent)
+                    .unwrap_or_else(|| "-".into()),
+                count(p.buys),
+                count(p.sells),
+                count(p.txns),
+            ],
+        ))
+    })
+    .collect()
+}
+
+/// A transaction with its side and USD value, both counted on the `--base`
+/// token of the pair.
+#[derive(Serialize)]
+pub struct Trade {
+    #[serde(flatten)]
+    pub tx: PoolTransaction,
+    pub side: Option<TradeSide>,
+    pub usd_value: Option<f64>,
+}
+
 #[derive(Tabled)]
 struct TxRow {
     #[tabled(rename = "Time")]
     time: String,
-    #[tabled(rename = "Type")]
-    tx_type: String,
-    #[tabled(rename = "Amount (USD)")]
-    amount: String,
+    #[tabled(rename = "Side")]
+    side: String,
+    #[tabled(rename = "Value (USD)")]
+    value: String,
     #[tabled(rename = "Token 0")]
     token_0: String,
     #[tabled(rename = "Token 1")]
     token_1: String,
+    #[tabled(rename = "Tx")]
+    id: String,
 }
 
-pub fn print_transactions_table(txs: &[PoolTransaction]) {
-    let rows: Vec<TxRow> = txs
-        .iter()
-        .map(|tx| {
-            let t0 = format!(
-                "{:.4} {}",
-                tx.volume_0.unwrap_or(0.0),
-                tx.token_0_symbol.as_deref().unwrap_or("?")
-            );
-            let t1 = format!(
-                "{:.4} {}",
-                tx.volume_1.unwrap_or(0.0),
-                tx.token_1_symbol.as_deref().unwrap_or("?")
-            );
-            let total_usd = tx.price_0_usd.unwrap_or(0.0) * tx.volume_0.unwrap_or(0.0)
-                + tx.price_1_usd.unwrap_or(0.0) * tx.volume_1.unwrap_or(0.0);
-
-            TxRow {
-                time: tx.created_at.clone().unwrap_or_else(|| "-".into()),
-                tx_type: "swap".into(),
-                amount: format_usd(total_usd.abs()),
-                token_0: crate::output::truncate(&t0, 25),
-                token_1: crate::output::truncate(&t1, 25),
-            }
-        })
-        .collect();
+fn tx_row(trade: &Trade) -> TxRow {
+    let tx = &trade.tx;
+    let t0 = format!(
+        "{:.4} {}",
+        tx.volume_0.unwrap_or(0.0),
+        tx.token_0_symbol.as_deref().unwrap_or("?")
+    );
+    let t1 = format!(
+        "{:.4} {}",
+        tx.volume_1.unwrap_or(0.0),
+        tx.token_1_symbol.as_deref().unwrap_or("?")
+    );
+    let cell = |s: String| {
+        if context::wide() {
+            s
+        } else {
+            crate::output::truncate(&s, 25)
+        }
+    };
+    TxRow {
+        time: tx.created_at.clone().unwrap_or_else(|| "-".into()),
+        side: match trade.side {
+            Some(TradeSide::Buy) => "buy".into(),
+            Some(TradeSide::Sell) => "sell".into(),
+            None => "swap".into(),
+        },
+        value: trade.usd_value.map_or_else(|| "-".into(), format_usd),
+        token_0: cell(t0),
+        token_1: cell(t1),
+        id: tx.id.clone().unwrap_or_else(|| "-".into()),
+    }
+}
 
-    let table = Table::new(rows).with(Style::rounded()).to_string();
-    println!("{table}");
-    print_dexpaprika_footer();
+pub fn print_transactions_table(trades: &[Trade]) {
+    let mut table = Table::new(trades.iter().map(tx_row));
+    drop_wide_columns(&mut table, &["Tx"]);
+    println!("{}", table_style::render(table));
+}
+
+/// One transaction on a line of its own, for `transactions --follow`, which
+/// can't know the column widths of rows that haven't happened yet.
+pub fn print_transaction_line(trade: &Trade) {
+    let row = tx_row(trade);
+    let mut line = format!(
+        "{}  {:<4}  {:>10}  {}  {}",
+        row.time, row.side, row.value, row.token_0, row.token_1
+    );
+    if context::wide() {
+        line.push_str("  ");
+        line.push_str(&row.id);
+    }
+    println!("{line}");
+}
+
+/// One address's trades in a pool, for `transactions --aggregate address`.
+/// Sides and USD values are counted on the `--base` token; the net flows are
+/// what the address took out of the pool, negative for what it put in.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Participant {
+    pub address: String,
+    pub trades: usize,
+    pub buys: usize,
+    pub sells: usize,
+    pub volume_usd: f64,
+    pub bought_usd: f64,
+    pub sold_usd: f64,
+    pub token_0_symbol: Option<String>,
+    pub net_token_0: f64,
+    pub token_1_symbol: Option<String>,
+    pub net_token_1: f64,
+    pub first_seen: Option<String>,
+    pub last_seen: Option<String>,
+}
+
+/// The net flow columns are headed by the pair's symbols, which every row
+/// of one pool shares.
+pub fn print_participants_table(rows: &[Participant]) {
+    let symbol = |pick: fn(&Participant) -> &Option<String>, fallback: &str| {
+        rows.iter()
+            .find_map(|p| pick(p).clone())
+            .unwrap_or_else(|| fallback.to_string())
+    };
+    let mut builder = Builder::default();
+    builder.push_record([
+        "Address".to_string(),
+        "Trades".into(),
+        "Buys".into(),
+        "Sells".into(),
+        "Volume (USD)".into(),
+        "Bought (USD)".into(),
+        "Sold (USD)".into(),
+        format!("Net {}", symbol(|p| &p.token_0_symbol, "token 0")),
+        format!("Net {}", symbol(|p| &p.token_1_symbol, "token 1")),
+        "First seen".into(),
+        "Last seen".into(),
+    ]);
+    for p in rows {
+        builder.push_record([
+            truncate_address(&p.address),
+            p.trades.to_string(),
+            p.buys.to_string(),
+            p.sells.to_string(),
+            format_usd(p.volume_usd),
+            format_usd(p.bought_usd),
+            format_usd(p.sold_usd),
+            format!("{:+.4}", p.net_token_0),
+            format!("{:+.4}", p.net_token_1),
+            p.first_seen.clone().unwrap_or_else(|| "-".into()),
+            p.last_seen.clone().unwrap_or_else(|| "-".into()),
+        ]);
+    }
+    println!("{}", table_style::render(builder.build()));
 }
 
 #[derive(Tabled)]
@@ -327,26 +553,105 @@ struct OhlcvRow {
     volume: String,
 }
 
-pub fn print_pool_ohlcv_table(data: &[PoolOhlcv]) {
-    let rows: Vec<OhlcvRow> = data
-        .iter()
-        .map(|d| OhlcvRow {
-            date: d
-                .time_open
-                .as_deref()
-                .unwrap_or("-")
-                .chars()
-                .take(19)
-                .collect(),
-            open: d.open.map(format_price).unwrap_or_else(|| "-".into()),
-            high: d.high.map(format_price).unwrap_or_else(|| "-".into()),
-            low: d.low.map(format_price).unwrap_or_else(|| "-".into()),
-            close: d.close.map(format_price).unwrap_or_else(|| "-".into()),
-            volume: d.volume.map(format_usd).unwrap_or_else(|| "-".into()),
-        })
-        .collect();
+fn ohlcv_row(d: &PoolOhlcv) -> OhlcvRow {
+    OhlcvRow {
+        date: d
+            .time_open
+            .as_deref()
+            .unwrap_or("-")
+            .chars()
+            .take(19)
+            .collect(),
+        open: d.open.map(format_price).unwrap_or_else(|| "-".into()),
+        high: d.high.map(format_price).unwrap_or_else(|| "-".into()),
+        low: d.low.map(format_price).unwrap_or_else(|| "-".into()),
+        close: d.close.map(format_price).unwrap_or_else(|| "-".into()),
+        volume: d.volume.map(format_usd).unwrap_or_else(|| "-".into()),
+    }
+}
 
-    let table = Table::new(rows).with(Style::rounded()).to_string();
+pub fn print_pool_ohlcv_table(data: &[PoolOhlcv]) {
+    let table = table_style::render(Table::new(data.iter().map(ohlcv_row)));
     println!("{table}");
     print_dexpaprika_footer();
 }
+
+#[derive(Tabled)]
+struct CandleRow {
+    #[tabled(rename = "Date")]
+    date: String,
+    #[tabled(rename = "Open")]
+    open: String,
+    #[tabled(rename = "High")]
+    high: String,
+    #[tabled(rename = "Low")]
+    low: String,
+    #[tabled(rename = "Close")]
+    close: String,
+    #[tabled(rename = "Volume")]
+    volume: String,
+    #[tabled(rename = "Buy Volume")]
+    buy_volume: String,
+    #[tabled(rename = "Sell Volume")]
+    sell_volume: String,
+    #[tabled(rename = "Trades")]
+    trades: String,
+}
+
+pub fn print_candles_table(candles: &[Candle]) {
+    let rows = candles.iter().map(|c| CandleRow {
+        date: c.time_open.chars().take(19).collect(),
+        open: format_price(c.open),
+        high: format_price(c.high),
+        low: format_price(c.low),
+        close: format_price(c.close),
+        volume: format_usd(c.volume_usd),
+        buy_volume: format_usd(c.buy_volume_usd),
+        sell_volume: format_usd(c.sell_volume_usd),
+        trades: c.trades.to_string(),
+    });
+    println!("{}", table_style::render(Table::new(rows)));
+}
+
+/// A candle and the `--indicators` values at it. The values serialize as
+/// fields of the candle, named as in `Indicator::columns`, so `--where` and
+/// `--fields` reach them like any other field.
+#[derive(Serialize)]
+pub struct IndicatorCandle {
+    #[serde(flatten)]
+    pub candle: PoolOhlcv,
+    #[serde(flatten)]
+    pub values: IndicatorValues,
+}
+
+/// Indicator values by column, in the order the indicators were asked for.
+pub struct IndicatorValues(pub Vec<(String, Option<f64>)>);
+
+impl Serialize for IndicatorValues {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (column, value) in &self.0 {
+            map.serialize_entry(column, value)?;
+        }
+        map.end()
+    }
+}
+
+/// The OHLCV table with a column per indicator. RSI is an index, so it gets
+/// two decimals rather than a price.
+pub fn indicator_table(rows: &[IndicatorCandle]) -> Table {
+    let mut builder = Builder::from(Table::new(rows.iter().map(|r| ohlcv_row(&r.candle))));
+    let columns = rows
+        .first()
+        .map(|r| r.values.0.as_slice())
+        .unwrap_or_default();
+    for (i, (column, _)) in columns.iter().enumerate() {
+        let cells = rows.iter().map(|r| match r.values.0[i].1 {
+            Some(v) if column.starts_with("rsi_") => format!("{v:.2}"),
+            Some(v) => format_price(v),
+            None => "-".into(),
+        });
+        builder.push_column(std::iter::once(column.clone()).chain(cells));
+    }
+    builder.build()
+}
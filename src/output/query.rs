@@ -0,0 +1,808 @@
+//! `--query`: reshape JSON output before it is printed, for when jq isn't
+//! installed.
+//!
+//! Two notations are understood. An expression starting with `$` is
+//! JSONPath (`$.data.results[*].id`, `$..symbol`) and prints one array of
+//! every match. Anything else is a subset of jq and prints each result in
+//! turn, as jq does:
+//!
+//! ```text
+//! .data.results[] | {id, price_usd}
+//! .data.results | map(select(.liquidity_usd > 1e6) | .id)
+//! [.data[] | .tokens[0].symbol] | length
+//! ```
+//!
+//! The jq subset has paths (`.a.b`, `.[0]`, `.[2:5]`, `.["k"]`, `.[]`, `..`),
+//! `|`, `,`, `//`, `?`, comparisons, `and`, `or`, array and object
+//! construction, literals, and the functions `select`, `map`, `length`,
+//! `keys`, `not`, `first` and `last`.
+//!
+//! The query for a run lives in `output::context`.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use crate::output::filter;
+use crate::output::node::Node;
+
+/// A parsed `--query`.
+#[derive(Clone, Debug)]
+pub struct Query {
+    kind: Kind,
+}
+
+#[derive(Clone, Debug)]
+enum Kind {
+    Path(Vec<Step>),
+    Jq(Jq),
+}
+
+impl Query {
+    /// Parse a JSONPath (starting with `$`) or jq expression.
+    pub fn parse(source: &str) -> Result<Query, String> {
+        let source = source.trim();
+        let kind = if let Some(path) = source.strip_prefix('$') {
+            Kind::Path(parse_path(path)?)
+        } else {
+            let tokens = tokenize(source)?;
+            let mut parser = Parser { tokens, pos: 0 };
+            let jq = parser.pipe()?;
+            if let Some(token) = parser.tokens.get(parser.pos) {
+                return Err(format!("unexpected {token:?} in the query"));
+            }
+            Kind::Jq(jq)
+        };
+        Ok(Query { kind })
+    }
+
+    /// The values to print for `input`.
+    pub fn run(&self, input: &Node) -> Result<Vec<Node>> {
+        match &self.kind {
+            Kind::Path(steps) => Ok(vec![Node::List(run_path(steps, input))]),
+            Kind::Jq(jq) => jq.eval(input),
+        }
+    }
+}
+
+// JSONPath.
+
+#[derive(Clone, Debug)]
+enum Step {
+    Child(String),
+    Index(i64),
+    Wildcard,
+    /// `..name` or `..*`: the step applied at every depth.
+    Descend(Box<Step>),
+}
+
+fn parse_path(mut rest: &str) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        let descend = rest.starts_with("..");
+        let step;
+        if descend || rest.starts_with('.') {
+            rest = &rest[if descend { 2 } else { 1 }..];
+            if rest.starts_with('[') {
+                (step, rest) = bracket_step(rest)?;
+            } else {
+                let end = rest.find(['.', '[']).unwrap_or(rest.len());
+                let name = &rest[..end];
+                step = match name {
+                    "" => return Err("a `.` in the path names nothing".to_string()),
+                    "*" => Step::Wildcard,
+                    _ => Step::Child(name.to_string()),
+                };
+                rest = &rest[end..];
+            }
+        } else if rest.starts_with('[') {
+            (step, rest) = bracket_step(rest)?;
+        } else {
+            return Err(format!("unexpected `{rest}` in the path"));
+        }
+        steps.push(if descend {
+            Step::Descend(Box::new(step))
+        } else {
+            step
+        });
+    }
+    Ok(steps)
+}
+
+fn bracket_step(rest: &str) -> Result<(Step, &str), String> {
+    let end = rest
+        .find(']')
+        .ok_or_else(|| "a `[` in the path is never closed".to_string())?;
+    let inner = rest[1..end].trim();
+    let step = if inner == "*" {
+        Step::Wildcard
+    } else if let Ok(i) = inner.parse::<i64>() {
+        Step::Index(i)
+    } else if inner.len() >= 2
+        && (inner.starts_with('\'') && inner.ends_with('\'')
+            || inner.starts_with('"') && inner.ends_with('"'))
+    {
+        Step::Child(inner[1..inner.len() - 1].to_string())
+    } else {
+        return Err(format!("`[{inner}]` is not an index, a quoted name or *"));
+    };
+    Ok((step, &rest[end + 1..]))
+}
+
+fn run_path(steps: &[Step], root: &Node) -> Vec<Node> {
+    let mut current = vec![root];
+    for step in steps {
+        current = current
+            .into_iter()
+            .flat_map(|node| match step {
+                Step::Descend(inner) => descendants(node)
+                    .into_iter()
+                    .flat_map(|node| apply_step(inner, node))
+                    .collect(),
+                _ => apply_step(step, node),
+            })
+            .collect();
+    }
+    current.into_iter().cloned().collect()
+}
+
+fn apply_step<'a>(step: &Step, node: &'a Node) -> Vec<&'a Node> {
+    match (step, node) {
+        (Step::Child(name), Node::Map(_)) => node.get(name).into_iter().collect(),
+        (Step::Index(i), Node::List(items)) => index(items, *i).into_iter().collect(),
+        (Step::Wildcard, Node::Map(entries)) => entries.iter().map(|(_, v)| v).collect(),
+        (Step::Wildcard, Node::List(items)) => items.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `node` and everything under it, parents first.
+fn descendants(node: &Node) -> Vec<&Node> {
+    let mut out = vec![node];
+    match node {
+        Node::Map(entries) => entries.iter().for_each(|(_, v)| out.extend(descendants(v))),
+        Node::List(items) => items.iter().for_each(|v| out.extend(descendants(v))),
+        Node::Scalar(_) => {}
+    }
+    out
+}
+
+fn index(items: &[Node], i: i64) -> Option<&Node> {
+    let i = if i < 0 { items.len() as i64 + i } else { i };
+    usize::try_from(i).ok().and_then(|i| items.get(i))
+}
+
+// jq.
+
+#[derive(Clone, Debug)]
+enum Jq {
+    Identity,
+    Recurse,
+    Literal(Node),
+    Key(Box<Jq>, String),
+    Index(Box<Jq>, i64),
+    Slice(Box<Jq>, Option<i64>, Option<i64>),
+    Iterate(Box<Jq>),
+    Optional(Box<Jq>),
+    Pipe(Box<Jq>, Box<Jq>),
+    Comma(Box<Jq>, Box<Jq>),
+    Alternative(Box<Jq>, Box<Jq>),
+    And(Box<Jq>, Box<Jq>),
+    Or(Box<Jq>, Box<Jq>),
+    Compare(&'static str, Box<Jq>, Box<Jq>),
+    Array(Option<Box<Jq>>),
+    Object(Vec<(String, Jq)>),
+    Call(String, Option<Box<Jq>>),
+}
+
+impl Jq {
+    fn eval(&self, input: &Node) -> Result<Vec<Node>> {
+        Ok(match self {
+            Jq::Identity => vec![input.clone()],
+            Jq::Recurse => descendants(input).into_iter().cloned().collect(),
+            Jq::Literal(value) => vec![value.clone()],
+            Jq::Key(target, key) => each(target, input, |node| match node {
+                Node::Map(_) => Ok(node.get(key).cloned().unwrap_or(Node::NULL)),
+                Node::Scalar(Value::Null) => Ok(Node::NULL),
+                other => bail!("Cannot index {} with \"{key}\"", type_name(other)),
+            })?,
+            Jq::Index(target, i) => each(target, input, |node| match node {
+                Node::List(items) => Ok(index(items, *i).cloned().unwrap_or(Node::NULL)),
+                Node::Scalar(Value::Null) => Ok(Node::NULL),
+                other => bail!("Cannot index {} with number", type_name(other)),
+            })?,
+            Jq::Slice(target, from, to) => each(target, input, |node| match node {
+                Node::List(items) => {
+                    let len = items.len() as i64;
+                    let clamp = |i: i64| (if i < 0 { len + i } else { i }).clamp(0, len) as usize;
+                    let from = from.map_or(0, clamp);
+                    let to = to.map_or(items.len(), clamp).max(from);
+                    Ok(Node::List(items[from..to].to_vec()))
+                }
+                Node::Scalar(Value::Null) => Ok(Node::NULL),
+                other => bail!("Cannot slice {}", type_name(other)),
+            })?,
+            Jq::Iterate(target) => {
+                let mut out = Vec::new();
+                for node in target.eval(input)? {
+                    match node {
+                        Node::List(items) => out.extend(items),
+                        Node::Map(entries) => out.extend(entries.into_iter().map(|(_, v)| v)),
+                        other => bail!("Cannot iterate over {}", type_name(&other)),
+                    }
+                }
+                out
+            }
+            Jq::Optional(inner) => inner.eval(input).unwrap_or_default(),
+            Jq::Pipe(left, right) => {
+                let mut out = Vec::new();
+                for node in left.eval(input)? {
+                    out.extend(right.eval(&node)?);
+                }
+                out
+            }
+            Jq::Comma(left, right) => {
+                let mut out = left.eval(input)?;
+                out.extend(right.eval(input)?);
+                out
+            }
+            Jq::Alternative(left, right) => {
+                let found: Vec<Node> = left
+                    .eval(input)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(truthy)
+                    .collect();
+                if found.is_empty() {
+                    right.eval(input)?
+                } else {
+                    found
+                }
+            }
+            Jq::And(left, right) | Jq::Or(left, right) => {
+                let is_and = matches!(self, Jq::And(..));
+                let mut out = Vec::new();
+                for l in left.eval(input)? {
+                    if truthy(&l) != is_and {
+                        out.push(boolean(!is_and));
+                        continue;
+                    }
+                    for r in right.eval(input)? {
+                        out.push(boolean(truthy(&r)));
+                    }
+                }
+                out
+            }
+            Jq::Compare(op, left, right) => {
+                let mut out = Vec::new();
+                for r in right.eval(input)? {
+                    for l in left.eval(input)? {
+                        out.push(boolean(compare(op, &l, &r)));
+                    }
+                }
+                out
+            }
+            Jq::Array(None) => vec![Node::List(Vec::new())],
+            Jq::Array(Some(inner)) => vec![Node::List(inner.eval(input)?)],
+            Jq::Object(entries) => {
+                let mut objects = vec![Vec::new()];
+                for (key, value) in entries {
+                    let values = value.eval(input)?;
+                    objects = objects
+                        .into_iter()
+                        .flat_map(|object: Vec<(String, Node)>| {
+                            values.iter().map(move |v| {
+                                let mut object = object.clone();
+                                object.push((key.clone(), v.clone()));
+                                object
+                            })
+                        })
+                        .collect();
+                }
+                objects.into_iter().map(Node::Map).collect()
+            }
+            Jq::Call(name, arg) => call(name, arg.as_deref(), input)?,
+        })
+    }
+}
+
+/// Evaluate `target` and map each output through `f`.
+fn each(target: &Jq, input: &Node, f: impl Fn(&Node) -> Result<Node>) -> Result<Vec<Node>> {
+    target.eval(input)?.iter().map(f).collect()
+}
+
+fn call(name: &str, arg: Option<&Jq>, input: &Node) -> Result<Vec<Node>> {
+    Ok(match (name, arg) {
+        ("select", Some(cond)) => cond
+            .eval(input)?
+            .iter()
+            .filter(|v| truthy(v))
+            .map(|_| input.clone())
+            .collect(),
+        ("map", Some(f)) => {
+            let items = Jq::Iterate(Box::new(Jq::Identity)).eval(input)?;
+            let mut out = Vec::new();
+            for item in &items {
+                out.extend(f.eval(item)?);
+            }
+            vec![Node::List(out)]
+        }
+        ("first", Some(f)) => f.eval(input)?.into_iter().take(1).collect(),
+        ("first", None) => Jq::Index(Box::new(Jq::Identity), 0).eval(input)?,
+        ("last", Some(f)) => f.eval(input)?.into_iter().last().into_iter().collect(),
+        ("last", None) => Jq::Index(Box::new(Jq::Identity), -1).eval(input)?,
+        ("not", None) => vec![boolean(!truthy(input))],
+        ("length", None) => vec![Node::Scalar(match input {
+            Node::List(items) => items.len().into(),
+            Node::Map(entries) => entries.len().into(),
+            Node::Scalar(Value::String(s)) => s.chars().count().into(),
+            Node::Scalar(Value::Null) => 0.into(),
+            Node::Scalar(Value::Number(n)) => n.as_f64().map(f64::abs).into(),
+            other => bail!("{} has no length", type_name(other)),
+        })],
+        ("keys", None) => match input {
+            Node::Map(_) => {
+                let mut keys: Vec<&str> = input.keys().collect();
+                keys.sort_unstable();
+                vec![Node::List(
+                    keys.into_iter().map(|k| Node::Scalar(k.into())).collect(),
+                )]
+            }
+            Node::List(items) => vec![Node::List(
+                (0..items.len()).map(|i| Node::Scalar(i.into())).collect(),
+            )],
+            other => bail!("{} has no keys", type_name(other)),
+        },
+        _ => unreachable!("checked by the parser"),
+    })
+}
+
+/// The functions, with whether each takes an argument: `first` and `last`
+/// work either way.
+const FUNCTIONS: [(&str, Option<bool>); 7] = [
+    ("select", Some(true)),
+    ("map", Some(true)),
+    ("first", None),
+    ("last", None),
+    ("not", Some(false)),
+    ("length", Some(false)),
+    ("keys", Some(false)),
+];
+
+fn compare(op: &str, l: &Node, r: &Node) -> bool {
+    use std::cmp::Ordering::*;
+    let ordering = match (l, r) {
+        (Node::Scalar(a), Node::Scalar(b)) => {
+            if a.is_null() || b.is_null() {
+                (a.is_null() && b.is_null()).then_some(Equal)
+            } else {
+                filter::compare(a, b)
+            }
+        }
+        _ => (l == r).then_some(Equal),
+    };
+    match op {
+        "==" => ordering == Some(Equal),
+        "!=" => ordering != Some(Equal),
+        "<" => ordering == Some(Less),
+        "<=" => matches!(ordering, Some(Less | Equal)),
+        ">" => ordering == Some(Greater),
+        _ => matches!(ordering, Some(Greater | Equal)),
+    }
+}
+
+/// jq truthiness: only `false` and `null` are false.
+fn truthy(node: &Node) -> bool {
+    !matches!(node, Node::Scalar(Value::Null | Value::Bool(false)))
+}
+
+fn boolean(b: bool) -> Node {
+    Node::Scalar(Value::Bool(b))
+}
+
+fn type_name(node: &Node) -> &'static str {
+    match node {
+        Node::Map(_) => "object",
+        Node::List(_) => "array",
+        Node::Scalar(Value::String(_)) => "string",
+        Node::Scalar(Value::Number(_)) => "number",
+        Node::Scalar(Value::Bool(_)) => "boolean",
+        Node::Scalar(_) => "null",
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// `.name` or `."name"`.
+    Field(String),
+    Dot,
+    DotDot,
+    Ident(String),
+    Str(String),
+    Number(Value),
+    Punct(&'static str),
+}
+
+/// Punctuation and operators, longest first.
+const PUNCTUATION: [&str; 18] = [
+    "==", "!=", "<=", ">=", "//", "<", ">", "|", ",", ":", "?", "(", ")", "[", "]", "{", "}", ";",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if let Some(after) = rest.strip_prefix("..") {
+            tokens.push(Token::DotDot);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('.') {
+            if after.starts_with('"') {
+                let (s, after) = string(after)?;
+                tokens.push(Token::Field(s));
+                rest = after;
+            } else {
+                let (name, after) = ident(after);
+                tokens.push(if name.is_empty() {
+                    Token::Dot
+                } else {
+                    Token::Field(name.to_string())
+                });
+                rest = after;
+            }
+        } else if c == '"' {
+            let (s, after) = string(rest)?;
+            tokens.push(Token::Str(s));
+            rest = after;
+        } else if c.is_ascii_digit()
+            || c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit())
+        {
+            let end = rest[1..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '+' || c == '-'))
+                .map_or(rest.len(), |i| i + 1);
+            let value = serde_json::from_str(&rest[..end])
+                .map_err(|_| format!("`{}` is not a number", &rest[..end]))?;
+            tokens.push(Token::Number(value));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let (name, after) = ident(rest);
+            tokens.push(Token::Ident(name.to_string()));
+            rest = after;
+        } else if let Some(p) = PUNCTUATION.iter().find(|p| rest.starts_with(*p)) {
+            tokens.push(Token::Punct(p));
+            rest = &rest[p.len()..];
+        } else {
+            return Err(format!("unexpected `{c}` in the query"));
+        }
+    }
+    Ok(tokens)
+}
+
+fn ident(s: &str) -> (&str, &str) {
+    let end = s
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(s.len());
+    s.split_at(end)
+}
+
+/// A double-quoted JSON string at the start of `s`, and what follows it.
+fn string(s: &str) -> Result<(String, &str), String> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => {
+                let value = serde_json::from_str(&s[..=i]).map_err(|e| e.to_string())?;
+                return Ok((value, &s[i + 1..]));
+            }
+            _ => escaped = false,
+        }
+    }
+    Err("a string in the query is never closed".to_string())
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn pipe(&mut self) -> Result<Jq, String> {
+        let mut left = self.comma()?;
+        while self.eat("|") {
+            left = Jq::Pipe(Box::new(left), Box::new(self.comma()?));
+        }
+        Ok(left)
+    }
+
+    fn comma(&mut self) -> Result<Jq, String> {
+        let mut left = self.alternative()?;
+        while self.eat(",") {
+            left = Jq::Comma(Box::new(left), Box::new(self.alternative()?));
+        }
+        Ok(left)
+    }
+
+    fn alternative(&mut self) -> Result<Jq, String> {
+        let mut left = self.or()?;
+        while self.eat("//") {
+            left = Jq::Alternative(Box::new(left), Box::new(self.or()?));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Jq, String> {
+        let mut left = self.and()?;
+        while self.eat_word("or") {
+            left = Jq::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Jq, String> {
+        let mut left = self.comparison()?;
+        while self.eat_word("and") {
+            left = Jq::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Jq, String> {
+        let left = self.postfix()?;
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat(op) {
+                return Ok(Jq::Compare(op, Box::new(left), Box::new(self.postfix()?)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn postfix(&mut self) -> Result<Jq, String> {
+        let mut term = self.term()?;
+        loop {
+            if let Some(Token::Field(name)) = self.peek().cloned() {
+                self.pos += 1;
+                term = Jq::Key(Box::new(term), name);
+            } else if self.peek() == Some(&Token::Dot)
+                && self.tokens.get(self.pos + 1) == Some(&Token::Punct("["))
+            {
+                // `.a.[0]` reads as `.a[0]`.
+                self.pos += 1;
+            } else if self.eat("[") {
+                term = self.bracket(term)?;
+            } else if self.eat("?") {
+                term = Jq::Optional(Box::new(term));
+            } else {
+                return Ok(term);
+            }
+        }
+    }
+
+    /// What follows a `[` after a term: `]`, an index, a key or a slice.
+    fn bracket(&mut self, target: Jq) -> Result<Jq, String> {
+        let target = Box::new(target);
+        if self.eat("]") {
+            return Ok(Jq::Iterate(target));
+        }
+        if let Some(Token::Str(key)) = self.peek().cloned() {
+            self.pos += 1;
+            self.expect("]")?;
+            return Ok(Jq::Key(target, key));
+        }
+        let from = self.integer()?;
+        if self.eat(":") {
+            let to = self.integer()?;
+            self.expect("]")?;
+            return Ok(Jq::Slice(target, from, to));
+        }
+        self.expect("]")?;
+        match from {
+            Some(i) => Ok(Jq::Index(target, i)),
+            None => Err("`[` needs an index, a key, a slice or nothing".to_string()),
+        }
+    }
+
+    fn integer(&mut self) -> Result<Option<i64>, String> {
+        let Some(Token::Number(n)) = self.peek().cloned() else {
+            return Ok(None);
+        };
+        self.pos += 1;
+        n.as_i64()
+            .map(Some)
+            .ok_or_else(|| format!("`{n}` is not a whole number"))
+    }
+
+    fn term(&mut self) -> Result<Jq, String> {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            return Err("the query ends too soon".to_string());
+        };
+        self.pos += 1;
+        match token {
+            Token::Dot => Ok(Jq::Identity),
+            Token::DotDot => Ok(Jq::Recurse),
+            Token::Field(name) => Ok(Jq::Key(Box::new(Jq::Identity), name)),
+            Token::Str(s) => Ok(Jq::Literal(Node::Scalar(s.into()))),
+            Token::Number(n) => Ok(Jq::Literal(Node::Scalar(n))),
+            Token::Ident(word) => self.word(word),
+            Token::Punct("(") => {
+                let inner = self.pipe()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Token::Punct("[") => {
+                if self.eat("]") {
+                    return Ok(Jq::Array(None));
+                }
+                let inner = self.pipe()?;
+                self.expect("]")?;
+                Ok(Jq::Array(Some(Box::new(inner))))
+            }
+            Token::Punct("{") => self.object(),
+            other => Err(format!("unexpected {other:?} in the query")),
+        }
+    }
+
+    fn word(&mut self, word: String) -> Result<Jq, String> {
+        match word.as_str() {
+            "true" => return Ok(Jq::Literal(boolean(true))),
+            "false" => return Ok(Jq::Literal(boolean(false))),
+            "null" => return Ok(Jq::Literal(Node::NULL)),
+            _ => {}
+        }
+        let Some((_, takes_arg)) = FUNCTIONS.iter().find(|(name, _)| *name == word) else {
+            return Err(format!("unknown function `{word}`"));
+        };
+        let arg = if self.eat("(") {
+            let arg = self.pipe()?;
+            self.expect(")")?;
+            Some(Box::new(arg))
+        } else {
+            None
+        };
+        match (takes_arg, &arg) {
+            (Some(true), None) => Err(format!("`{word}` needs an argument: {word}(...)")),
+            (Some(false), Some(_)) => Err(format!("`{word}` takes no argument")),
+            _ => Ok(Jq::Call(word, arg)),
+        }
+    }
+
+    /// `{a, "b": .x, c: .y | f}` after the `{`.
+    fn object(&mut self) -> Result<Jq, String> {
+        let mut entries = Vec::new();
+        if self.eat("}") {
+            return Ok(Jq::Object(entries));
+        }
+        loop {
+            let key = match self.tokens.get(self.pos).cloned() {
+                Some(Token::Ident(k) | Token::Str(k)) => k,
+                _ => return Err("an object key must be a name or a string".to_string()),
+            };
+            self.pos += 1;
+            let value = if self.eat(":") {
+                self.alternative()?
+            } else {
+                Jq::Key(Box::new(Jq::Identity), key.clone())
+            };
+            entries.push((key, value));
+            if self.eat("}") {
+                return Ok(Jq::Object(entries));
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Punct(p)) if *p == punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Ident(w)) if w == word);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(format!("expected `{punct}` in the query"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc() -> Node {
+        serde_json::from_str(
+            r#"{"data": {"results": [
+                {"id": "a", "price_usd": 2.5, "liquidity_usd": 5e6, "tokens": [{"symbol": "WETH"}]},
+                {"id": "b", "price_usd": 1, "liquidity_usd": 10, "tokens": [{"symbol": "USDC"}]}
+            ], "next_cursor": null}, "_meta": {"source": "DexPaprika"}}"#,
+        )
+        .unwrap()
+    }
+
+    fn run(query: &str) -> String {
+        Query::parse(query)
+            .unwrap_or_else(|e| panic!("{query}: {e}"))
+            .run(&doc())
+            .unwrap()
+            .iter()
+            .map(|node| serde_json::to_string(node).unwrap())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn jq_paths_pipes_and_object_construction() {
+        assert_eq!(
+            run(".data.results[] | {id, price_usd}"),
+            r#"{"id":"a","price_usd":2.5} {"id":"b","price_usd":1}"#
+        );
+        assert_eq!(run(".data.results[-1].tokens[0].symbol"), r#""USDC""#);
+        assert_eq!(run(r#".["_meta"].source"#), r#""DexPaprika""#);
+        assert_eq!(run(".data.results[1:] | length"), "1");
+        assert_eq!(run("[.data.results[].id]"), r#"["a","b"]"#);
+        assert_eq!(run(".data.next_cursor // \"none\""), r#""none""#);
+        assert_eq!(
+            run("{cursor: .data.next_cursor, n: (.data.results | length)}"),
+            r#"{"cursor":null,"n":2}"#
+        );
+    }
+
+    #[test]
+    fn jq_select_map_and_functions() {
+        assert_eq!(
+            run(".data.results | map(select(.liquidity_usd > 1e6 and .price_usd != 1) | .id)"),
+            r#"["a"]"#
+        );
+        assert_eq!(
+            run(".data.results[] | select(.tokens[0].symbol == \"USDC\") | .id"),
+            r#""b""#
+        );
+        assert_eq!(
+            run(".data.results | first | keys"),
+            r#"["id","liquidity_usd","price_usd","tokens"]"#
+        );
+        assert_eq!(run("[..] | length"), "20");
+        assert_eq!(run(".data.results[0].id.x?"), "");
+    }
+
+    #[test]
+    fn jsonpath_collects_every_match() {
+        assert_eq!(run("$.data.results[*].id"), r#"["a","b"]"#);
+        assert_eq!(run("$..symbol"), r#"["WETH","USDC"]"#);
+        assert_eq!(run("$['_meta'].source"), r#"["DexPaprika"]"#);
+        assert_eq!(run("$.data.results[5].id"), "[]");
+    }
+
+    #[test]
+    fn errors_name_the_problem() {
+        assert_eq!(
+            Query::parse(".data | sort").unwrap_err(),
+            "unknown function `sort`"
+        );
+        assert_eq!(
+            Query::parse(".data[").unwrap_err(),
+            "expected `]` in the query"
+        );
+        assert!(Query::parse("$.data[x]").is_err());
+        let err = Query::parse(".data.results[0].id.x")
+            .unwrap()
+            .run(&doc())
+            .unwrap_err();
+        assert_eq!(err.to_string(), r#"Cannot index string with "x""#);
+    }
+}
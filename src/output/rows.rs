@@ -0,0 +1,445 @@
+//! Rows written out page by page, for `--all` and `--max-pages` crawls.
+//!
+//! A table is printed per page as the page arrives, with one footer at the
+//! end. JSON is still a single document, but its `data` array is written a
+//! row at a time, so a long crawl shows progress and never holds more than one
+//! page in memory. Where the crawl stopped goes into `_meta.pagination`.
+//! CSV and TSV get one header and then every page's rows, and NDJSON its
+//! `_meta` line and then the rows. Neither has anywhere to say where the
+//! crawl stopped once the rows have started, so that goes to stderr. A page
+//! that fails to arrive ends the crawl the same way, with the failed page's
+//! cursor to resume from, and the error in `_meta.error` under JSON.
+//!
+//! Pages go through `--where` on the way in, so a crawl writes only the rows
+//! that match, and can stop once it has written enough of them. Under
+//! `--sort-local` nothing can be written until the last page is in, so the
+//! rows are held and written, sorted, by `finish`. The same goes for JSON
+//! under `--query`, which needs the whole document.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::output::context;
+use crate::output::delimited::Delimited;
+use crate::output::ndjson;
+use crate::output::node::Node;
+use crate::output::view::View;
+use crate::output::{
+    eprint_more_results_hint, print_json_wrapped, print_list_footer, OutputFormat, Pagination,
+    ResponseMeta,
+};
+
+/// An error a crawl already wrote into the JSON document it closed, as
+/// `_meta.error`. Printed again on stdout it would follow that document with
+/// a second one, so `main` sends it to stderr instead.
+#[derive(Debug)]
+pub struct Reported(pub anyhow::Error);
+
+impl fmt::Display for Reported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for Reported {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+pub struct RowWriter<T, W: Write = io::Stdout> {
+    output: OutputFormat,
+    raw: bool,
+    meta: ResponseMeta,
+    table: fn(&[T]),
+    view: View,
+    csv: Delimited,
+    out: W,
+    pages: usize,
+    rows: usize,
+    max_rows: Option<usize>,
+    held: Vec<T>,
+    queried: Option<Vec<Node>>,
+}
+
+impl<T: Serialize> RowWriter<T> {
+    /// `table` prints one page of rows as a table, without the footer.
+    pub fn new(output: OutputFormat, raw: bool, meta: ResponseMeta, table: fn(&[T])) -> Self {
+        Self::with_writer(output, raw, meta, table, io::stdout())
+    }
+}
+
+impl<T: Serialize, W: Write> RowWriter<T, W> {
+    pub(crate) fn with_writer(
+        output: OutputFormat,
+        raw: bool,
+        meta: ResponseMeta,
+        table: fn(&[T]),
+        out: W,
+    ) -> Self {
+        Self {
+            output,
+            raw,
+            meta,
+            table,
+            view: View::default(),
+            csv: Delimited::new(output),
+            out,
+            pages: 0,
+            rows: 0,
+            max_rows: None,
+            held: Vec::new(),
+            queried: context::query().map(|_| Vec::new()),
+        }
+    }
+
+    /// Print only the `--fields` of `view`.
+    pub fn with_view(mut self, view: View) -> Self {
+        self.view = view;
+        self
+    }
+
+    /// Write no more than `rows` rows in all.
+    pub fn stop_after(&mut self, rows: usize) {
+        self.max_rows = Some(rows);
+    }
+
+    /// Whether the rows allowed by `stop_after` have all been taken.
+    pub fn is_full(&self) -> bool {
+        self.max_rows
+            .is_some_and(|max| self.rows + self.held.len() >= max)
+    }
+
+    /// Write the rows of one page that pass `--where`. A page with none still
+    /// counts toward the page total but prints nothing.
+    pub fn page(&mut self, mut rows: Vec<T>) -> Result<()> {
+        self.pages += 1;
+        if self.pages == 1 && matches!(self.output, OutputFormat::Ndjson) && !self.raw {
+            ndjson::write_meta(&mut self.out, &self.meta)?;
+        }
+        self.view.retain(&mut rows)?;
+        if let Some(max) = self.max_rows {
+            rows.truncate(max.saturating_sub(self.rows + self.held.len()));
+        }
+        if self.view.sorts() {
+            self.held.append(&mut rows);
+            return Ok(());
+        }
+        self.write(&rows)
+    }
+
+    fn write(&mut self, rows: &[T]) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        match self.output {
+            OutputFormat::Table => {
+                self.view.print_table(rows, self.table)?;
+                self.rows += rows.len();
+            }
+            OutputFormat::Json if self.queried.is_some() => {
+                let rows = self.view.project(rows)?;
+                self.rows += rows.len();
+                self.queried.get_or_insert_with(Vec::new).extend(rows);
+            }
+            OutputFormat::Json => {
+                let depth = if self.raw { 2 } else { 4 };
+                for row in &self.view.project(rows)? {
+                    if self.rows == 0 {
+                        self.open()?;
+                    } else {
+                        writeln!(self.out, ",")?;
+                    }
+                    let json = serde_json::to_string_pretty(row)?;
+                    write!(self.out, "{}", indent(&json, depth))?;
+                    self.rows += 1;
+                }
+                self.out.flush()?;
+            }
+            OutputFormat::Ndjson => {
+                ndjson::write_rows(&mut self.out, &self.view.project(rows)?)?;
+                self.rows += rows.len();
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                self.csv.write(&mut self.out, &self.view.project(rows)?)?;
+                self.rows += rows.len();
+            }
+        }
+        Ok(())
+    }
+
+    /// Close the output. `next_cursor` is the cursor of the first page not
+    /// fetched, when the crawl stopped before the end.
+    pub fn finish(mut self, next_cursor: Option<&str>) -> Result<()> {
+        let mut held = std::mem::take(&mut self.held);
+        self.view.sort(&mut held)?;
+        self.write(&held)?;
+        match self.output {
+            OutputFormat::Table => {
+                // Nothing matched: an empty table says so more plainly than
+                // a bare footer.
+                if self.rows == 0 {
+                    self.view.print_table(&[], self.table)?;
+                }
+                print_list_footer(next_cursor.map(|_| true), next_cursor);
+            }
+            OutputFormat::Json if self.queried.is_some() => {
+                let rows = self.queried.take().unwrap_or_default();
+                let meta = self.meta.with_pagination(Pagination {
+                    pages: self.pages,
+                    rows: self.rows,
+                    next_cursor: next_cursor.map(str::to_string),
+                });
+                print_json_wrapped(&rows, meta, self.raw)?;
+            }
+            OutputFormat::Json => {
+                if self.rows == 0 {
+                    self.open()?;
+                } else {
+                    writeln!(self.out)?;
+                }
+                if self.raw {
+                    writeln!(self.out, "]")?;
+                } else {
+                    let meta = self.meta.with_pagination(Pagination {
+                        pages: self.pages,
+                        rows: self.rows,
+                        next_cursor: next_cursor.map(str::to_string),
+                    });
+                    let meta = serde_json::to_string_pretty(&meta)?;
+                    writeln!(self.out, "  ],")?;
+                    writeln!(self.out, "  \"_meta\": {}", indent(&meta, 2).trim_start())?;
+                    writeln!(self.out, "}}")?;
+                }
+                self.out.flush()?;
+            }
+            OutputFormat::Ndjson | OutputFormat::Csv | OutputFormat::Tsv => {
+                eprint_more_results_hint(next_cursor.map(|_| true), next_cursor);
+            }
+        }
+        Ok(())
+    }
+
+    /// Close the output after a page failed to arrive, with the rows written
+    /// so far and `next_cursor`, the page that failed, to resume from. Returns
+    /// the error to pass on.
+    pub fn fail(mut self, next_cursor: Option<&str>, error: anyhow::Error) -> anyhow::Error {
+        let in_document = matches!(self.output, OutputFormat::Json);
+        self.meta.error = Some(error.to_string());
+        match self.finish(next_cursor) {
+            Err(e) => e,
+            Ok(()) if in_document => Reported(error).into(),
+            Ok(()) => error,
+        }
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        if self.raw {
+            writeln!(self.out, "[")
+        } else {
+            writeln!(self.out, "{{\n  \"data\": [")
+        }
+    }
+}
+
+/// Indent every line of `text` by `spaces`.
+fn indent(text: &str, spaces: usize) -> String {
+    let pad = " ".repeat(spaces);
+    text.lines()
+        .map(|line| format!("{pad}{line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_table(_: &[serde_json::Value]) {}
+
+    fn crawl(raw: bool, pages: &[&[serde_json::Value]], next: Option<&str>) -> serde_json::Value {
+        let mut buf = Vec::new();
+        let mut writer = RowWriter::with_writer(
+            OutputFormat::Json,
+            raw,
+            ResponseMeta::dexpaprika("/networks/ethereum/pools/search"),
+            no_table,
+            &mut buf,
+        );
+        for page in pages {
+            writer.page(page.to_vec()).unwrap();
+        }
+        writer.finish(next).unwrap();
+        serde_json::from_slice(&buf).expect("a crawl must print one valid JSON document")
+    }
+
+    #[test]
+    fn pages_stream_into_one_wrapped_document() {
+        let a = [
+            serde_json::json!({"id": "a"}),
+            serde_json::json!({"id": "b"}),
+        ];
+        let b = [serde_json::json!({"id": "c"})];
+        let doc = crawl(false, &[&a, &b], Some("next"));
+        assert_eq!(doc["data"].as_array().unwrap().len(), 3);
+        assert_eq!(doc["data"][2]["id"], "c");
+        assert_eq!(doc["_meta"]["pagination"]["pages"], 2);
+        assert_eq!(doc["_meta"]["pagination"]["next_cursor"], "next");
+    }
+
+    #[test]
+    fn raw_and_empty_crawls_are_still_valid_json() {
+        let a = [serde_json::json!({"id": "a"})];
+        assert_eq!(crawl(true, &[&a], None), serde_json::json!([{"id": "a"}]));
+        assert_eq!(crawl(true, &[&[]], None), serde_json::json!([]));
+        let doc = crawl(false, &[&[]], None);
+        assert_eq!(doc["data"], serde_json::json!([]));
+        assert!(doc["_meta"]["pagination"]["next_cursor"].is_null());
+    }
+
+    #[test]
+    fn csv_crawls_write_one_header() {
+        let mut buf = Vec::new();
+        let mut writer = RowWriter::with_writer(
+            OutputFormat::Csv,
+            false,
+            ResponseMeta::dexpaprika("/networks/ethereum/pools/search"),
+            no_table,
+            &mut buf,
+        );
+        writer.page(vec![serde_json::json!({"id": "a"})]).unwrap();
+        writer.page(vec![serde_json::json!({"id": "b"})]).unwrap();
+        writer.finish(None).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "id\na\nb\n");
+    }
+
+    #[test]
+    fn ndjson_crawls_write_meta_once_then_a_line_per_row() {
+        let mut buf = Vec::new();
+        let mut writer = RowWriter::with_writer(
+            OutputFormat::Ndjson,
+            false,
+            ResponseMeta::dexpaprika("/networks/ethereum/pools/search"),
+            no_table,
+            &mut buf,
+        );
+        writer.page(vec![]).unwrap();
+        writer.page(vec![serde_json::json!({"id": "a"})]).unwrap();
+        writer.page(vec![serde_json::json!({"id": "b"})]).unwrap();
+        writer.finish(None).unwrap();
+        let lines: Vec<serde_json::Value> = String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["_meta"]["source"], "DexPaprika");
+        assert_eq!(lines[2], serde_json::json!({"id": "b"}));
+    }
+
+    #[test]
+    fn sorted_crawls_write_every_page_in_order_at_the_end() {
+        let mut buf = Vec::new();
+        let mut writer = RowWriter::with_writer(
+            OutputFormat::Ndjson,
+            true,
+            ResponseMeta::dexpaprika("/pool/ethereum/0xabc/transactions"),
+            no_table,
+            &mut buf,
+        )
+        .with_view(View {
+            sort: Some(crate::output::view::LocalSort::parse("usd:asc").unwrap()),
+            ..View::default()
+        });
+        writer.page(vec![serde_json::json!({"usd": 3})]).unwrap();
+        writer
+            .page(vec![
+                serde_json::json!({"usd": 1}),
+                serde_json::json!({"usd": 2}),
+            ])
+            .unwrap();
+        writer.finish(None).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "{\"usd\":1}\n{\"usd\":2}\n{\"usd\":3}\n"
+        );
+    }
+
+    #[test]
+    fn filtered_crawls_stop_once_enough_rows_match() {
+        let mut buf = Vec::new();
+        let mut writer = RowWriter::with_writer(
+            OutputFormat::Csv,
+            false,
+            ResponseMeta::dexpaprika("/networks/ethereum/pools/search"),
+            no_table,
+            &mut buf,
+        )
+        .with_view(View {
+            filter: Some(crate::output::filter::Filter::parse("fee < 1").unwrap()),
+            ..View::default()
+        });
+        writer.stop_after(2);
+        let row = |id, fee| serde_json::json!({"id": id, "fee": fee});
+        writer.page(vec![row("a", 0), row("b", 5)]).unwrap();
+        assert!(!writer.is_full());
+        writer
+            .page(vec![row("c", 9), row("d", 0), row("e", 0)])
+            .unwrap();
+        assert!(writer.is_full());
+        writer.finish(None).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "fee,id\n0,a\n0,d\n");
+    }
+
+    #[tokio::test]
+    async fn a_failed_page_still_closes_the_document_with_its_cursor() {
+        let mut buf = Vec::new();
+        let writer = RowWriter::with_writer(
+            OutputFormat::Json,
+            false,
+            ResponseMeta::dexpaprika("/networks/ethereum/pools/search"),
+            no_table,
+            &mut buf,
+        );
+        let pages = crate::commands::paging::PageArgs {
+            all: true,
+            ..Default::default()
+        };
+        let err = crate::commands::paging::crawl(
+            pages,
+            None,
+            |cursor| async move {
+                match cursor.as_deref() {
+                    None => Ok(Page(vec![serde_json::json!({"id": "a"})], Some("p2"))),
+                    _ => Err(anyhow::anyhow!("server error")),
+                }
+            },
+            writer,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.is::<Reported>());
+        let doc: serde_json::Value =
+            serde_json::from_slice(&buf).expect("a failed crawl must still print valid JSON");
+        assert_eq!(doc["data"], serde_json::json!([{"id": "a"}]));
+        assert_eq!(doc["_meta"]["pagination"]["next_cursor"], "p2");
+        assert_eq!(doc["_meta"]["error"], "server error");
+    }
+
+    struct Page(Vec<serde_json::Value>, Option<&'static str>);
+
+    impl dexpaprika::pages::CursorPage for Page {
+        type Item = serde_json::Value;
+
+        fn next_cursor(&self) -> Option<&str> {
+            self.1
+        }
+
+        fn into_items(self) -> Vec<serde_json::Value> {
+            self.0
+        }
+    }
+}
@@ -1,7 +1,26 @@
-use crate::commands::search::DexSearchResult;
 use crate::output::{
     format_percent, format_price, format_usd, print_dexpaprika_footer, truncate_address,
 };
+use dexpaprika::models::{DexSearchDex, DexSearchPool, DexSearchResult, DexSearchToken};
+use serde::Serialize;
+
+/// One hit of a search, of any kind, for the formats that want a single list.
+/// `kind` rather than `type`: tokens already have a `type` field.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SearchRow<'a> {
+    Token(&'a DexSearchToken),
+    Pool(&'a DexSearchPool),
+    Dex(&'a DexSearchDex),
+}
+
+/// Tokens, then pools, then DEXes, as the table lists them.
+pub fn search_rows(result: &DexSearchResult) -> Vec<SearchRow<'_>> {
+    let tokens = result.tokens.iter().flatten().map(SearchRow::Token);
+    let pools = result.pools.iter().flatten().map(SearchRow::Pool);
+    let dexes = result.dexes.iter().flatten().map(SearchRow::Dex);
+    tokens.chain(pools).chain(dexes).collect()
+}
 
 pub fn print_dex_search(result: &DexSearchResult) {
     if let Some(tokens) = &result.tokens {
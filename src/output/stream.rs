@@ -1,19 +1,43 @@
-use crate::commands::stream::StreamEvent;
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::output::delimited::Delimited;
 use crate::output::OutputFormat;
+use crate::output::{context, ndjson};
+use dexpaprika::stream::StreamEvent;
 
-pub fn print_stream_event(event: &StreamEvent, output: OutputFormat) {
+/// Print one event. `csv` carries the CSV/TSV header from the first event to
+/// the rest; the other formats leave it alone. An error, such as a reader
+/// that went away, should end the stream.
+pub fn print_stream_event(
+    event: &StreamEvent,
+    output: OutputFormat,
+    csv: &mut Delimited,
+) -> Result<()> {
+    let mut out = std::io::stdout().lock();
     match output {
         OutputFormat::Table => {
             let time = chrono::DateTime::from_timestamp(event.timestamp, 0)
                 .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
                 .unwrap_or_else(|| event.timestamp.to_string());
             let addr = crate::output::truncate_address(&event.address);
-            println!("{}  {}  {}  ${}", time, event.chain, addr, event.price_usd);
+            writeln!(
+                out,
+                "{}  {}  {}  ${}",
+                time, event.chain, addr, event.price_usd
+            )?;
         }
-        OutputFormat::Json => {
-            if let Ok(json) = serde_json::to_string(event) {
-                println!("{json}");
-            }
+        // Events are one compact object per line already, so NDJSON is the
+        // same thing, and `--query` runs on each of them.
+        OutputFormat::Json | OutputFormat::Ndjson => ndjson::write_lines(
+            &mut out,
+            std::slice::from_ref(event),
+            context::query().as_ref(),
+        )?,
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            csv.write(&mut out, std::slice::from_ref(event))?
         }
     }
+    Ok(())
 }
@@ -1,7 +1,19 @@
-use crate::commands::stream_reserves::ReserveEvent;
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::output::delimited::Delimited;
 use crate::output::OutputFormat;
+use crate::output::{context, ndjson};
+use dexpaprika::stream::ReserveEvent;
 
-pub fn print_reserve_event(event: &ReserveEvent, output: OutputFormat) {
+/// Print one event, as `print_stream_event` does.
+pub fn print_reserve_event(
+    event: &ReserveEvent,
+    output: OutputFormat,
+    csv: &mut Delimited,
+) -> Result<()> {
+    let mut out = std::io::stdout().lock();
     match output {
         OutputFormat::Table => {
             // pool_reserves carries a pool id; token_reserves does not, so fall
@@ -32,7 +44,8 @@ pub fn print_reserve_event(event: &ReserveEvent, output: OutputFormat) {
                 .request_id
                 .map(|r| format!("  req {r}"))
                 .unwrap_or_default();
-            println!(
+            writeln!(
+                out,
                 "{}  {}  block {}  {}  pool {}  pair {}  Δ ${:.2}  TVL ${:.2}{}",
                 time,
                 event.method,
@@ -43,12 +56,16 @@ pub fn print_reserve_event(event: &ReserveEvent, output: OutputFormat) {
                 event.total_delta_usd,
                 event.total_reserve_usd,
                 rid,
-            );
+            )?;
         }
-        OutputFormat::Json => {
-            if let Ok(json) = serde_json::to_string(event) {
-                println!("{json}");
-            }
+        OutputFormat::Json | OutputFormat::Ndjson => ndjson::write_lines(
+            &mut out,
+            std::slice::from_ref(event),
+            context::query().as_ref(),
+        )?,
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            csv.write(&mut out, std::slice::from_ref(event))?
         }
     }
+    Ok(())
 }
@@ -0,0 +1,156 @@
+//! `--table-style`: how tables are drawn.
+//!
+//! The rounded box drawing stays the default. The other styles are for
+//! places it doesn't survive: `ascii` and `plain` for CI logs and screen
+//! readers, `markdown` for GitHub-flavored tables to paste into notes, and
+//! `html` for pages and mail. The style for a run lives in `output::context`.
+
+use serde::{Deserialize, Serialize};
+use tabled::settings::object::Segment;
+use tabled::settings::style::HorizontalLine;
+use tabled::settings::{Format, Modify, Style};
+use tabled::Table;
+
+use crate::output::context;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    /// Box drawing with rounded corners
+    #[default]
+    Rounded,
+    /// The same frame drawn with + - and |
+    Ascii,
+    /// Columns padded with spaces, no lines at all
+    Plain,
+    /// A GitHub-flavored Markdown table
+    Markdown,
+    /// An HTML <table>
+    Html,
+}
+
+impl TableStyle {
+    /// Whether the first row must be a header. Markdown and HTML tables have
+    /// one, so the two-column detail tables get a `Field | Value` row.
+    pub fn needs_header(self) -> bool {
+        matches!(self, TableStyle::Markdown | TableStyle::Html)
+    }
+
+    /// Whether long cells may be wrapped onto several lines. A line break in
+    /// a Markdown row ends the row, and HTML reflows the text by itself.
+    pub fn wraps(self) -> bool {
+        !self.needs_header()
+    }
+
+    /// Render `table` in this style. The first row is taken as the header.
+    pub fn render(self, mut table: Table) -> String {
+        match self {
+            TableStyle::Rounded => table.with(Style::rounded()).to_string(),
+            TableStyle::Ascii => table
+                .with(
+                    Style::ascii()
+                        .remove_horizontal()
+                        .horizontals([(1, HorizontalLine::full('-', '+', '+', '+'))]),
+                )
+                .to_string(),
+            TableStyle::Plain => table.with(Style::blank()).to_string(),
+            TableStyle::Markdown => table
+                .with(Modify::new(Segment::all()).with(Format::content(markdown_cell)))
+                .with(Style::markdown())
+                .to_string(),
+            TableStyle::Html => html(&table),
+        }
+    }
+}
+
+/// Render `table` in the style of this run.
+pub fn render(table: Table) -> String {
+    context::style().render(table)
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn html(table: &Table) -> String {
+    let mut rows = table.get_records().iter().map(|row| {
+        row.iter()
+            .map(|cell| escape_html(cell.as_ref()))
+            .collect::<Vec<_>>()
+    });
+    let mut out = String::from("<table>\n");
+    if let Some(header) = rows.next() {
+        out.push_str("  <thead>\n    <tr>");
+        for cell in header {
+            out.push_str(&format!("<th>{cell}</th>"));
+        }
+        out.push_str("</tr>\n  </thead>\n");
+    }
+    out.push_str("  <tbody>\n");
+    for row in rows {
+        out.push_str("    <tr>");
+        for cell in row {
+            out.push_str(&format!("<td>{cell}</td>"));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("  </tbody>\n</table>");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tabled::builder::Builder;
+
+    fn table() -> Table {
+        let mut builder = Builder::default();
+        builder.push_record(["Pair", "Price"]);
+        builder.push_record(["WETH/USDC", "$1 <2>"]);
+        builder.push_record(["a|b", "x"]);
+        builder.build()
+    }
+
+    #[test]
+    fn each_style_renders_its_own_syntax() {
+        assert_eq!(
+            TableStyle::Markdown.render(table()),
+            "| Pair      | Price  |\n\
+             |-----------|--------|\n\
+             | WETH/USDC | $1 <2> |\n\
+             | a\\|b      | x      |"
+        );
+
+        assert_eq!(
+            TableStyle::Html.render(table()),
+            "<table>\n  <thead>\n    <tr><th>Pair</th><th>Price</th></tr>\n  </thead>\n  \
+             <tbody>\n    <tr><td>WETH/USDC</td><td>$1 &lt;2&gt;</td></tr>\n    \
+             <tr><td>a|b</td><td>x</td></tr>\n  </tbody>\n</table>"
+        );
+
+        assert_eq!(
+            TableStyle::Ascii.render(table()),
+            "+-----------+--------+\n\
+             | Pair      | Price  |\n\
+             +-----------+--------+\n\
+             | WETH/USDC | $1 <2> |\n\
+             | a|b       | x      |\n\
+             +-----------+--------+"
+        );
+
+        assert!(TableStyle::Plain
+            .render(table())
+            .lines()
+            .all(|l| !l.contains('|') || l.contains("a|b")));
+
+        assert!(TableStyle::Rounded.render(table()).starts_with('╭'));
+    }
+}
@@ -1,11 +1,10 @@
-use tabled::settings::Style;
 use tabled::{Table, Tabled};
 
-use crate::commands::tokens::{TokenDetail, TokenPrice, TokenSearchItem};
 use crate::output::{
-    format_percent, format_price, format_usd, print_detail_table, print_dexpaprika_footer,
-    truncate_address,
+    context, drop_wide_columns, format_percent, format_price, format_usd, print_detail_table,
+    print_dexpaprika_footer, print_windows_table, table_style, truncate_address,
 };
+use dexpaprika::models::{TokenDetail, TokenPrice, TokenSearchItem, TokenSummary};
 
 pub fn print_token_detail(token: &TokenDetail) {
     let mut rows: Vec<[String; 2]> = Vec::new();
@@ -98,56 +97,59 @@ pub fn print_token_detail(token: &TokenDetail) {
                 .unwrap_or_else(|| "-".into())
         );
 
-        if let Some(h24) = &summary.h24 {
-            detail_field!(
-                rows,
-                "Volume (24h)",
-                h24.volume_usd.map(format_usd).unwrap_or_else(|| "-".into())
-            );
-            detail_field!(
-                rows,
-                "Change (24h)",
-                h24.last_price_usd_change
-                    .map(format_percent)
-                    .unwrap_or_else(|| "-".into())
-            );
-            detail_field!(
-                rows,
-                "Buys/Sells (24h)",
-                format!("{}/{}", h24.buys.unwrap_or(0), h24.sells.unwrap_or(0))
-            );
-            detail_field!(
-                rows,
-                "Txns (24h)",
-                h24.txns
-                    .map(|t| t.to_string())
-                    .unwrap_or_else(|| "-".into())
-            );
-        }
+        // --wide puts every window in a table of its own below.
+        if !context::wide() {
+            if let Some(h24) = &summary.h24 {
+                detail_field!(
+                    rows,
+                    "Volume (24h)",
+                    h24.volume_usd.map(format_usd).unwrap_or_else(|| "-".into())
+                );
+                detail_field!(
+                    rows,
+                    "Change (24h)",
+                    h24.last_price_usd_change
+                        .map(format_percent)
+                        .unwrap_or_else(|| "-".into())
+                );
+                detail_field!(
+                    rows,
+                    "Buys/Sells (24h)",
+                    format!("{}/{}", h24.buys.unwrap_or(0), h24.sells.unwrap_or(0))
+                );
+                detail_field!(
+                    rows,
+                    "Txns (24h)",
+                    h24.txns
+                        .map(|t| t.to_string())
+                        .unwrap_or_else(|| "-".into())
+                );
+            }
 
-        if let Some(h1) = &summary.h1 {
-            detail_field!(
-                rows,
-                "Volume (1h)",
-                h1.volume_usd.map(format_usd).unwrap_or_else(|| "-".into())
-            );
-            detail_field!(
-                rows,
-                "Change (1h)",
-                h1.last_price_usd_change
-                    .map(format_percent)
-                    .unwrap_or_else(|| "-".into())
-            );
-        }
+            if let Some(h1) = &summary.h1 {
+                detail_field!(
+                    rows,
+                    "Volume (1h)",
+                    h1.volume_usd.map(format_usd).unwrap_or_else(|| "-".into())
+                );
+                detail_field!(
+                    rows,
+                    "Change (1h)",
+                    h1.last_price_usd_change
+                        .map(format_percent)
+                        .unwrap_or_else(|| "-".into())
+                );
+            }
 
-        if let Some(m5) = &summary.m5 {
-            detail_field!(
-                rows,
-                "Change (5m)",
-                m5.last_price_usd_change
-                    .map(format_percent)
-                    .unwrap_or_else(|| "-".into())
-            );
+            if let Some(m5) = &summary.m5 {
+                detail_field!(
+                    rows,
+                    "Change (5m)",
+                    m5.last_price_usd_change
+                        .map(format_percent)
+                        .unwrap_or_else(|| "-".into())
+                );
+            }
         }
     }
 
@@ -170,9 +172,61 @@ pub fn print_token_detail(token: &TokenDetail) {
     }
 
     print_detail_table(rows);
+    if let (true, Some(summary)) = (context::wide(), &token.summary) {
+        print_windows_table(
+            &[
+                "Volume (USD)",
+                "Volume",
+                "Change",
+                "Buys",
+                "Sells",
+                "Buy (USD)",
+                "Sell (USD)",
+                "Txns",
+            ],
+            token_windows(summary),
+        );
+    }
     print_dexpaprika_footer();
 }
 
+/// The windows a token reports, each as its column of the `--wide` table.
+fn token_windows(summary: &TokenSummary) -> Vec<(&'static str, Vec<String>)> {
+    let count = |n: Option<i64>| n.map(|n| n.to_string()).unwrap_or_else(|| "-".into());
+    let usd = |n: Option<f64>| n.map(format_usd).unwrap_or_else(|| "-".into());
+    [
+        ("24h", &summary.h24),
+        ("6h", &summary.h6),
+        ("1h", &summary.h1),
+        ("30m", &summary.m30),
+        ("15m", &summary.m15),
+        ("5m", &summary.m5),
+        ("1m", &summary.m1),
+    ]
+    .into_iter()
+    .filter_map(|(window, period)| {
+        let p = period.as_ref()?;
+        Some((
+            window,
+            vec![
+                usd(p.volume_usd),
+                p.volume
+                    .map(|v| format!("{v:.2}"))
+                    .unwrap_or_else(|| "-".into()),
+                p.last_price_usd_change
+                    .map(format_percent)
+                    .unwrap_or_else(|| "-".into()),
+                count(p.buys),
+                count(p.sells),
+                usd(p.buy_usd),
+                usd(p.sell_usd),
+                count(p.txns),
+            ],
+        ))
+    })
+    .collect()
+}
+
 #[derive(Tabled)]
 struct PriceRow {
     #[tabled(rename = "Token")]
@@ -195,6 +249,10 @@ struct TokenSearchRow {
     price: String,
     #[tabled(rename = "Volume (24h)")]
     volume_24h: String,
+    #[tabled(rename = "Volume (7d)")]
+    volume_7d: String,
+    #[tabled(rename = "Volume (30d)")]
+    volume_30d: String,
     #[tabled(rename = "24h Change")]
     change: String,
     #[tabled(rename = "Liquidity")]
@@ -205,10 +263,15 @@ struct TokenSearchRow {
     txns: String,
 }
 
-/// Render token rows from the unified search endpoint. The flat search payload
-/// carries no name/symbol/buys/sells/pools, so only the available fields are
-/// shown. Used by both the top-tokens and token-filter commands.
+/// Render token rows from the unified search endpoint. Used by both the
+/// top-tokens and token-filter commands.
 pub fn print_token_search_table(tokens: &[TokenSearchItem]) {
+    println!("{}", table_style::render(token_search_table(tokens)));
+}
+
+/// The token list table, unstyled. The flat search payload carries no
+/// name/symbol/buys/sells/pools, so only the available fields are shown.
+pub fn token_search_table(tokens: &[TokenSearchItem]) -> Table {
     let rows: Vec<TokenSearchRow> = tokens
         .iter()
         .map(|t| TokenSearchRow {
@@ -223,6 +286,14 @@ pub fn print_token_search_table(tokens: &[TokenSearchItem]) {
                 .volume_usd_24h
                 .map(format_usd)
                 .unwrap_or_else(|| "-".into()),
+            volume_7d: t
+                .volume_usd_7d
+                .map(format_usd)
+                .unwrap_or_else(|| "-".into()),
+            volume_30d: t
+                .volume_usd_30d
+                .map(format_usd)
+                .unwrap_or_else(|| "-".into()),
             change: t
                 .price_change_percentage_24h
                 .map(format_percent)
@@ -239,9 +310,9 @@ pub fn print_token_search_table(tokens: &[TokenSearchItem]) {
         })
         .collect();
 
-    let table = Table::new(rows).with(Style::rounded()).to_string();
-    println!("{table}");
-    print_dexpaprika_footer();
+    let mut table = Table::new(rows);
+    drop_wide_columns(&mut table, &["Volume (7d)", "Volume (30d)"]);
+    table
 }
 
 pub fn print_prices_table(prices: &[TokenPrice]) {
@@ -258,7 +329,7 @@ pub fn print_prices_table(prices: &[TokenPrice]) {
         })
         .collect();
 
-    let table = Table::new(rows).with(Style::rounded()).to_string();
+    let table = table_style::render(Table::new(rows));
     println!("{table}");
     print_dexpaprika_footer();
 }
@@ -0,0 +1,396 @@
+//! `--fields`, `--where` and `--sort-local`: which rows of a list are shown,
+//! in what order, and which of their fields.
+//!
+//! Without `--fields` every list keeps its own table and full rows. With it
+//! the table has one column per field, and JSON, NDJSON, CSV and TSV keep only
+//! those keys, in the order given. A field is named as in `--output json`; a
+//! dotted path such as `tokens.0.symbol` reaches into a nested value and
+//! becomes a key of its own. `--where` drops the rows its expression rejects
+//! before any of that happens, and `--sort-local` orders the rest on any
+//! field, whatever the API could sort on.
+
+use std::cmp::Ordering;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use tabled::builder::Builder;
+use tabled::Table;
+
+use crate::output::filter::{self, Filter};
+use crate::output::node::Node;
+use crate::output::table_style;
+use crate::output::{format_percent, format_price, format_usd};
+
+#[derive(Debug, Clone, Default)]
+pub struct View {
+    pub fields: Option<Vec<String>>,
+    pub filter: Option<Filter>,
+    pub sort: Option<LocalSort>,
+}
+
+/// A `--sort-local` key: `field`, `field:asc` or `field:desc`.
+#[derive(Debug, Clone)]
+pub struct LocalSort {
+    pub field: String,
+    pub descending: bool,
+}
+
+impl LocalSort {
+    /// Parse `field[:asc|desc]`. Without a direction the order is descending,
+    /// like the API sorts.
+    pub fn parse(spec: &str) -> Result<LocalSort, String> {
+        let (field, direction) = spec.rsplit_once(':').unwrap_or((spec, "desc"));
+        let descending = match direction.to_ascii_lowercase().as_str() {
+            "desc" => true,
+            "asc" => false,
+            other => return Err(format!("`{other}` is not a direction. Use asc or desc")),
+        };
+        let field = field.trim();
+        if field.is_empty() {
+            return Err("name a field to sort on".to_string());
+        }
+        Ok(LocalSort {
+            field: field.to_string(),
+            descending,
+        })
+    }
+}
+
+impl View {
+    /// `retain`, then `sort`: the rows of a single response as they should be
+    /// printed.
+    pub fn apply<T: Serialize>(&self, rows: &mut Vec<T>) -> Result<()> {
+        self.retain(rows)?;
+        self.sort(rows)
+    }
+
+    /// Order rows by `--sort-local`. Rows without a value go last either way,
+    /// and rows that tie keep their order.
+    pub fn sort<T: Serialize>(&self, rows: &mut Vec<T>) -> Result<()> {
+        let Some(sort) = &self.sort else {
+            return Ok(());
+        };
+        let nodes = rows
+            .iter()
+            .map(Node::from_serialize)
+            .collect::<Result<Vec<_>>>()?;
+        check_fields([sort.field.as_str()], &nodes)?;
+        let mut keyed: Vec<_> = nodes
+            .iter()
+            .map(|row| filter::field_value(row, &sort.field))
+            .zip(std::mem::take(rows))
+            .collect();
+        keyed.sort_by(|(a, _), (b, _)| match (a.is_null(), b.is_null()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            _ => {
+                let ordering = filter::compare(a, b).unwrap_or(Ordering::Equal);
+                if sort.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }
+        });
+        rows.extend(keyed.into_iter().map(|(_, row)| row));
+        Ok(())
+    }
+
+    /// Whether `--sort-local` is set, so a crawl has to see every row before
+    /// it can print the first.
+    pub fn sorts(&self) -> bool {
+        self.sort.is_some()
+    }
+
+    /// Drop the rows `--where` rejects.
+    pub fn retain<T: Serialize>(&self, rows: &mut Vec<T>) -> Result<()> {
+        let Some(filter) = &self.filter else {
+            return Ok(());
+        };
+        let nodes = rows
+            .iter()
+            .map(Node::from_serialize)
+            .collect::<Result<Vec<_>>>()?;
+        check_fields(filter.fields(), &nodes)?;
+        let mut keep = nodes.iter().map(|row| filter.matches(row));
+        rows.retain(|_| keep.next().unwrap_or(false));
+        Ok(())
+    }
+
+    /// `rows` reduced to the chosen fields. With no `--fields` the rows come
+    /// back whole.
+    pub fn project<T: Serialize>(&self, rows: &[T]) -> Result<Vec<Node>> {
+        let rows = rows
+            .iter()
+            .map(Node::from_serialize)
+            .collect::<Result<Vec<_>>>()?;
+        let Some(fields) = &self.fields else {
+            return Ok(rows);
+        };
+        check_fields(fields.iter().map(String::as_str), &rows)?;
+        Ok(rows.iter().map(|row| project_row(row, fields)).collect())
+    }
+
+    /// `doc` with the array of rows under `key` projected, for the JSON
+    /// envelope of a single page: `results` of a search, say, next to its
+    /// cursor.
+    pub fn project_in<T: Serialize>(&self, doc: &T, key: &str) -> Result<Node> {
+        let mut doc = Node::from_serialize(doc)?;
+        if let Node::Map(entries) = &mut doc {
+            for (k, value) in entries.iter_mut() {
+                if k == key {
+                    if let Node::List(rows) = value {
+                        *rows = self.project(rows)?;
+                    }
+                }
+            }
+        }
+        Ok(doc)
+    }
+
+    /// Print `rows` as a table: the command's own `table`, or a column per
+    /// field under `--fields`.
+    pub fn print_table<T: Serialize>(&self, rows: &[T], table: fn(&[T])) -> Result<()> {
+        match &self.fields {
+            Some(fields) => println!("{}", table_style::render(self.fields_table(rows, fields)?)),
+            None => table(rows),
+        }
+        Ok(())
+    }
+
+    /// `print_table` for a caller that adds to the table before it is drawn.
+    pub fn table<T: Serialize>(&self, rows: &[T], table: fn(&[T]) -> Table) -> Result<Table> {
+        match &self.fields {
+            Some(fields) => self.fields_table(rows, fields),
+            None => Ok(table(rows)),
+        }
+    }
+
+    fn fields_table<T: Serialize>(&self, rows: &[T], fields: &[String]) -> Result<Table> {
+        let rows = self.project(rows)?;
+        let mut builder = Builder::default();
+        builder.push_record(fields.iter().cloned());
+        for row in &rows {
+            builder.push_record(fields.iter().map(|f| cell(f, row.get(f))));
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Every field must name something the rows have, or a typo would quietly
+/// print an empty column. A dotted path has to resolve all the way down in at
+/// least one row: `tokens.0.symbl` and `tokens.5.symbol` fail even though
+/// `tokens` is there. An empty page has nothing to check against and passes.
+fn check_fields<'a>(fields: impl IntoIterator<Item = &'a str>, rows: &[Node]) -> Result<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+    for field in fields {
+        if rows.iter().any(|row| row.path(field).is_some()) {
+            continue;
+        }
+        // Name the deepest part of the path some row has, and what is there.
+        let (depth, node) = rows
+            .iter()
+            .rev()
+            .map(|row| reach(row, field))
+            .max_by_key(|(depth, _)| *depth)
+            .unwrap_or((0, &rows[0]));
+        let found: Vec<&str> = field.split('.').take(depth).collect();
+        let found = found.join(".");
+        match node {
+            Node::Map(_) if depth == 0 => {
+                let known = rows.iter().find(|row| row.keys().next().is_some());
+                let known: Vec<&str> = known.map(|row| row.keys().collect()).unwrap_or_default();
+                bail!("Unknown field `{field}`. Fields here: {}", known.join(", "));
+            }
+            Node::Map(_) => {
+                let known: Vec<&str> = node.keys().collect();
+                bail!(
+                    "Unknown field `{field}`. Fields under `{found}`: {}",
+                    known.join(", ")
+                );
+            }
+            Node::List(_) => {
+                let longest = rows
+                    .iter()
+                    .filter_map(|row| match row.path(&found) {
+                        Some(Node::List(items)) => Some(items.len()),
+                        _ => None,
+                    })
+                    .max()
+                    .unwrap_or(0);
+                bail!(
+                    "Unknown field `{field}`. `{found}` is a list of at most {longest} item(s), \
+                     indexed from 0."
+                );
+            }
+            Node::Scalar(_) => {
+                bail!(
+                    "Unknown field `{field}`. `{found}` is a single value, with nothing under it."
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How many segments of `path` resolve in `row`, and the value the last of
+/// them reaches.
+fn reach<'a>(row: &'a Node, path: &str) -> (usize, &'a Node) {
+    let mut node = row;
+    for (depth, segment) in path.split('.').enumerate() {
+        let next = match node {
+            Node::Map(_) => node.get(segment),
+            Node::List(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            Node::Scalar(_) => None,
+        };
+        match next {
+            Some(next) => node = next,
+            None => return (depth, node),
+        }
+    }
+    (path.split('.').count(), node)
+}
+
+fn project_row(row: &Node, fields: &[String]) -> Node {
+    Node::Map(
+        fields
+            .iter()
+            .map(|f| (f.clone(), row.path(f).cloned().unwrap_or(Node::NULL)))
+            .collect(),
+    )
+}
+
+/// A table cell, formatted the way the built-in tables format the same
+/// field: dollars for `*_usd`, a signed percentage for price changes.
+fn cell(field: &str, value: Option<&Node>) -> String {
+    let Some(value) = value else {
+        return "-".into();
+    };
+    let text = value.text();
+    if text.is_empty() {
+        return "-".into();
+    }
+    let name = field.rsplit('.').next().unwrap_or(field);
+    let Ok(n) = text.parse::<f64>() else {
+        return text;
+    };
+    if name == "price_usd" {
+        format_price(n)
+    } else if name.contains("_usd") {
+        format_usd(n)
+    } else if name.starts_with("price_change_percentage") {
+        format_percent(n)
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(fields: &[&str]) -> View {
+        View {
+            fields: Some(fields.iter().map(|f| f.to_string()).collect()),
+            ..View::default()
+        }
+    }
+
+    #[test]
+    fn keeps_the_chosen_fields_in_the_order_given() {
+        let rows = [serde_json::json!({
+            "id": "0xabc",
+            "liquidity_usd": 5.0,
+            "tokens": [{"symbol": "WETH"}],
+        })];
+        let projected = view(&["tokens.0.symbol", "id"]).project(&rows).unwrap();
+        assert_eq!(
+            serde_json::to_string(&projected[0]).unwrap(),
+            r#"{"tokens.0.symbol":"WETH","id":"0xabc"}"#
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_an_error_naming_the_known_ones() {
+        let rows = [serde_json::json!({"id": "0xabc", "liquidity_usd": 5.0})];
+        let err = view(&["liquidty_usd"]).project(&rows).unwrap_err();
+        assert!(err.to_string().contains("liquidity_usd"), "{err}");
+        assert!(view(&["nope"]).project::<serde_json::Value>(&[]).is_ok());
+    }
+
+    #[test]
+    fn a_dotted_field_has_to_resolve_all_the_way_down() {
+        let rows = [
+            serde_json::json!({"id": "a", "tokens": [{"symbol": "WETH"}, {"symbol": "USDC"}]}),
+            serde_json::json!({"id": "b", "tokens": [{"symbol": "DAI"}]}),
+        ];
+        let err = view(&["tokens.0.symbl"]).project(&rows).unwrap_err();
+        assert!(
+            err.to_string().contains("under `tokens.0`: symbol"),
+            "{err}"
+        );
+        let err = view(&["tokens.5.symbol"]).project(&rows).unwrap_err();
+        assert!(err.to_string().contains("at most 2 item(s)"), "{err}");
+        let err = view(&["id.x"]).project(&rows).unwrap_err();
+        assert!(err.to_string().contains("`id` is a single value"), "{err}");
+        // One row having it is enough.
+        let projected = view(&["tokens.1.symbol"]).project(&rows).unwrap();
+        assert_eq!(projected[1].get("tokens.1.symbol"), Some(&Node::NULL));
+    }
+
+    #[test]
+    fn where_drops_rows_and_checks_its_fields() {
+        let filter = |source| View {
+            filter: Some(Filter::parse(source).unwrap()),
+            ..View::default()
+        };
+        let mut rows = vec![
+            serde_json::json!({"id": "a", "fee": 0.003}),
+            serde_json::json!({"id": "b", "fee": 0.01}),
+        ];
+        filter("fee < 0.005").retain(&mut rows).unwrap();
+        assert_eq!(rows, [serde_json::json!({"id": "a", "fee": 0.003})]);
+        let err = filter("fees < 0.005").retain(&mut rows).unwrap_err();
+        assert!(err.to_string().contains("Unknown field `fees`"), "{err}");
+    }
+
+    #[test]
+    fn sorts_locally_with_missing_values_last() {
+        let sort = |spec| View {
+            sort: Some(LocalSort::parse(spec).unwrap()),
+            ..View::default()
+        };
+        let mut rows = vec![
+            serde_json::json!({"id": "a", "amount": "9.5"}),
+            serde_json::json!({"id": "b", "amount": null}),
+            serde_json::json!({"id": "c", "amount": "10"}),
+        ];
+        sort("amount").sort(&mut rows).unwrap();
+        let ids = |rows: &[serde_json::Value]| -> Vec<String> {
+            rows.iter()
+                .map(|r| r["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(ids(&rows), ["c", "a", "b"]);
+        sort("amount:asc").sort(&mut rows).unwrap();
+        assert_eq!(ids(&rows), ["a", "c", "b"]);
+        assert!(LocalSort::parse("amount:up").is_err());
+        assert!(sort("amont").sort(&mut rows).is_err());
+    }
+
+    #[test]
+    fn the_envelope_keeps_its_cursor() {
+        let doc = serde_json::json!({
+            "results": [{"id": "a", "fee": 1}],
+            "next_cursor": "c2",
+        });
+        let projected = view(&["id"]).project_in(&doc, "results").unwrap();
+        assert_eq!(
+            serde_json::to_value(&projected).unwrap(),
+            serde_json::json!({"results": [{"id": "a"}], "next_cursor": "c2"})
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Following `next_cursor` across the cursor-paginated endpoints.
+//!
+//! `paginate` turns "fetch the page at this cursor" into a stream of pages,
+//! one request per page, that ends when the API stops handing out cursors.
+//! Bound it with `StreamExt::take`: a broad search can run to thousands of
+//! pages, each one a credit.
+//!
+//! ```no_run
+//! use dexpaprika::pages::{paginate, CursorPage};
+//! use dexpaprika::query::PoolSearchQuery;
+//! use dexpaprika::ApiClient;
+//! use futures::StreamExt;
+//!
+//! # async fn demo() -> anyhow::Result<()> {
+//! let client = ApiClient::with_api_key(None);
+//! let query = PoolSearchQuery::new().limit(100).order_by("liquidity_usd");
+//! let mut pages = paginate(None, |cursor| {
+//!     let query = query.clone().cursor(cursor.as_deref());
+//!     let client = &client;
+//!     async move { client.pool_search("ethereum", &query).await }
+//! })
+//! .take(5);
+//! while let Some(page) = pages.next().await {
+//!     println!("{} pools", page?.into_items().len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::Result;
+use futures::Stream;
+
+use crate::models::{
+    PoolSearchItem, PoolSearchResponse, PoolTransaction, TokenSearchItem, TokenSearchResponse,
+    TransactionsResponse,
+};
+
+/// A response from a cursor-paginated endpoint.
+pub trait CursorPage {
+    type Item;
+
+    /// The cursor for the page after this one, or `None` on the last page.
+    fn next_cursor(&self) -> Option<&str>;
+
+    /// The rows on this page.
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+/// The search envelope says "no more" two ways, `has_next_page: false` and a
+/// missing cursor. Either ends the walk.
+fn search_cursor(has_next_page: Option<bool>, next_cursor: Option<&str>) -> Option<&str> {
+    if has_next_page == Some(false) {
+        return None;
+    }
+    next_cursor.filter(|c| !c.is_empty())
+}
+
+impl CursorPage for PoolSearchResponse {
+    type Item = PoolSearchItem;
+
+    fn next_cursor(&self) -> Option<&str> {
+        search_cursor(self.has_next_page, self.next_cursor.as_deref())
+    }
+
+    fn into_items(self) -> Vec<PoolSearchItem> {
+        self.results
+    }
+}
+
+impl CursorPage for TokenSearchResponse {
+    type Item = TokenSearchItem;
+
+    fn next_cursor(&self) -> Option<&str> {
+        search_cursor(self.has_next_page, self.next_cursor.as_deref())
+    }
+
+    fn into_items(self) -> Vec<TokenSearchItem> {
+        self.results
+    }
+}
+
+impl CursorPage for TransactionsResponse {
+    type Item = PoolTransaction;
+
+    fn next_cursor(&self) -> Option<&str> {
+        if self.has_next_page == Some(false) {
+            return None;
+        }
+        TransactionsResponse::next_cursor(self)
+    }
+
+    fn into_items(self) -> Vec<PoolTransaction> {
+        self.transactions
+    }
+}
+
+/// A stream of pages. An `Err` ends it.
+pub type PageStream<'a, P> = Pin<Box<dyn Stream<Item = Result<P>> + Send + 'a>>;
+
+/// Fetch the page at `first_cursor` (or the first page, for `None`), then keep
+/// following `next_cursor` until the API stops returning one.
+///
+/// A cursor that comes back a second time also ends the walk: the API would
+/// hand back the same pages again, forever.
+pub fn paginate<'a, P, F, Fut>(first_cursor: Option<String>, fetch: F) -> PageStream<'a, P>
+where
+    P: CursorPage + Send + 'a,
+    F: FnMut(Option<String>) -> Fut + Send + 'a,
+    Fut: Future<Output = Result<P>> + Send + 'a,
+{
+    struct Walk<F> {
+        fetch: F,
+        cursor: Option<String>,
+        seen: Vec<String>,
+        done: bool,
+    }
+
+    let walk = Walk {
+        fetch,
+        cursor: first_cursor,
+        seen: Vec::new(),
+        done: false,
+    };
+    Box::pin(futures::stream::unfold(walk, |mut walk| async move {
+        if walk.done {
+            return None;
+        }
+        let page = match (walk.fetch)(walk.cursor.take()).await {
+            Ok(page) => page,
+            Err(e) => {
+                walk.done = true;
+                return Some((Err(e), walk));
+            }
+        };
+        match page.next_cursor() {
+            Some(next) if !walk.seen.iter().any(|c| c == next) => {
+                walk.seen.push(next.to_string());
+                walk.cursor = Some(next.to_string());
+            }
+            _ => walk.done = true,
+        }
+        Some((Ok(page), walk))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn page(n: usize, next: Option<&str>) -> PoolSearchResponse {
+        serde_json::from_value(serde_json::json!({
+            "results": [{"id": format!("pool-{n}")}],
+            "has_next_page": next.is_some(),
+            "next_cursor": next,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn follows_cursors_until_the_last_page() {
+        let mut asked = Vec::new();
+        let pages: Vec<_> = paginate(None, |cursor| {
+            asked.push(cursor.clone());
+            let page = match cursor.as_deref() {
+                None => page(1, Some("c2")),
+                Some("c2") => page(2, Some("c3")),
+                _ => page(3, None),
+            };
+            async move { Ok(page) }
+        })
+        .collect()
+        .await;
+        assert_eq!(pages.len(), 3);
+        assert_eq!(
+            asked,
+            [None, Some("c2".to_string()), Some("c3".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_repeated_cursor_ends_the_walk() {
+        let pages: Vec<_> = paginate(None, |_| async { Ok(page(1, Some("same"))) })
+            .collect()
+            .await;
+        assert_eq!(pages.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn an_error_is_the_last_item() {
+        let mut calls = 0;
+        let pages: Vec<Result<PoolSearchResponse>> = paginate(None, |_| {
+            calls += 1;
+            let result = if calls == 1 {
+                Ok(page(1, Some("c2")))
+            } else {
+                Err(anyhow::anyhow!("boom"))
+            };
+            async move { result }
+        })
+        .collect()
+        .await;
+        assert_eq!(pages.len(), 2);
+        assert!(pages[1].is_err());
+    }
+
+    #[test]
+    fn transactions_read_a_cursor_nested_under_page_info() {
+        let resp: TransactionsResponse = serde_json::from_str(
+            r#"{"transactions": [], "page_info": {"next_cursor": "abc", "limit": 10}}"#,
+        )
+        .unwrap();
+        assert_eq!(CursorPage::next_cursor(&resp), Some("abc"));
+        let bare: TransactionsResponse = serde_json::from_str(r#"{"transactions": []}"#).unwrap();
+        assert_eq!(CursorPage::next_cursor(&bare), None);
+    }
+}
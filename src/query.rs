@@ -0,0 +1,571 @@
+//! Request builders for the search-backed list endpoints and the pool
+//! history endpoints.
+//!
+//! Each builder collects what the caller set and turns it into query
+//! parameters with the names the API expects. Setters take anything that
+//! converts into an `Option`, so a plain value and an optional CLI flag read
+//! the same:
+//!
+//! ```
+//! use dexpaprika::query::PoolSearchQuery;
+//!
+//! let query = PoolSearchQuery::new()
+//!     .limit(20)
+//!     .order_by("liquidity_usd")
+//!     .liquidity_usd_min(1_000_000.0)
+//!     .volume_24h_max(None);
+//! assert!(query.params().contains(&("liquidity_usd_min", "1000000".to_string())));
+//! ```
+//!
+//! Sort fields go through `search_mapping`, so the legacy names the old list
+//! endpoints took still work.
+
+use crate::search_mapping::{map_pool_sort_field, map_token_sort_field};
+
+/// The four price-change windows that `/networks/{network}/pools/search` accepts
+/// as bounds, carried together so the CLI flags cannot get transposed on the way
+/// through. Values are percentages and negatives are ordinary input: a max of
+/// -20 means "down 20% or more".
+///
+/// Only the three short windows are pools-only. tokens/search takes the 24h
+/// bounds as well, and silently ignores the 6h, 1h and 5m ones. Checked on
+/// 2026-08-07 against an unfiltered baseline, because an unknown bound comes
+/// back as HTTP 200 with a full result set: on ethereum tokens/search,
+/// `price_change_percentage_24h_min=20` returned 36.6, 33.2, 1251.0 where the
+/// baseline had -0.07, 0.24, -0.01, while `price_change_percentage_6h_min=20`
+/// returned the baseline unchanged.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PriceChangeBounds {
+    pub price_change_24h_min: Option<f64>,
+    pub price_change_24h_max: Option<f64>,
+    pub price_change_6h_min: Option<f64>,
+    pub price_change_6h_max: Option<f64>,
+    pub price_change_1h_min: Option<f64>,
+    pub price_change_1h_max: Option<f64>,
+    pub price_change_5m_min: Option<f64>,
+    pub price_change_5m_max: Option<f64>,
+}
+
+/// Pair each set bound with the query parameter name the API expects.
+///
+/// This lives outside `PoolSearchQuery::params` so the eight names can be pinned by
+/// a test. Nothing at runtime can catch a typo in them: pools/search answers 200
+/// and hands back a full unfiltered set for a parameter it does not recognise,
+/// so `price_change_percentage_1hr_min` looks exactly like a working query. The
+/// live wire is the authority for what these names are, the tests below are the
+/// lock that stops them drifting afterwards.
+///
+/// f64's Display gives the shortest round-trip form, so a bound of -20.0 goes
+/// out as "-20".
+pub fn price_change_params(bounds: &PriceChangeBounds) -> Vec<(&'static str, String)> {
+    [
+        (
+            "price_change_percentage_24h_min",
+            bounds.price_change_24h_min,
+        ),
+        (
+            "price_change_percentage_24h_max",
+            bounds.price_change_24h_max,
+        ),
+        ("price_change_percentage_6h_min", bounds.price_change_6h_min),
+        ("price_change_percentage_6h_max", bounds.price_change_6h_max),
+        ("price_change_percentage_1h_min", bounds.price_change_1h_min),
+        ("price_change_percentage_1h_max", bounds.price_change_1h_max),
+        ("price_change_percentage_5m_min", bounds.price_change_5m_min),
+        ("price_change_percentage_5m_max", bounds.price_change_5m_max),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.map(|v| (name, v.to_string())))
+    .collect()
+}
+
+/// Wire names for the token price-change bounds.
+///
+/// Pulled out of `TokenSearchQuery::params` so it can be tested. That matters more
+/// than usual here: `/networks/{network}/tokens/search` ignores query
+/// parameters it does not recognise and still answers `200` with the full
+/// unfiltered page, so a misspelled name produces a plausible result set and no
+/// error anywhere. A unit test on the literal strings is the only cheap place
+/// that catches it.
+///
+/// 24h is the only window tokens carry. The 6h, 1h and 5m bounds that
+/// `pool-filter` takes are deliberately absent: token rows have no such fields,
+/// and ordering tokens by any of the three is a `400`.
+pub fn token_price_change_params(
+    min: Option<f64>,
+    max: Option<f64>,
+) -> Vec<(&'static str, String)> {
+    let mut params = Vec::new();
+    if let Some(v) = min {
+        params.push(("price_change_percentage_24h_min", v.to_string()));
+    }
+    if let Some(v) = max {
+        params.push(("price_change_percentage_24h_max", v.to_string()));
+    }
+    params
+}
+
+/// Generate a setter per optional field: `name(value)` stores `value`, which
+/// may be a plain value or an `Option`.
+macro_rules! setters {
+    ($($name:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $name(mut self, value: impl Into<Option<$ty>>) -> Self {
+                self.$name = value.into();
+                self
+            }
+        )*
+    };
+}
+
+/// The same for text fields, taking `&str` or `Option<&str>`.
+macro_rules! str_setters {
+    ($($name:ident),* $(,)?) => {
+        $(
+            pub fn $name<'a>(mut self, value: impl Into<Option<&'a str>>) -> Self {
+                self.$name = value.into().map(str::to_string);
+                self
+            }
+        )*
+    };
+}
+
+/// Push `(name, value)` for each field that is set.
+fn push_set<T: ToString>(
+    params: &mut Vec<(&'static str, String)>,
+    name: &'static str,
+    value: &Option<T>,
+) {
+    if let Some(v) = value {
+        params.push((name, v.to_string()));
+    }
+}
+
+/// Query for `/networks/{network}/pools/search`, which backs `pools`,
+/// `pool-filter`, `dex-pools` and `token-pools`.
+///
+/// The endpoint is cursor-paginated: there is no page number, and the
+/// `next_cursor` of one response is the `cursor` of the next request.
+#[derive(Debug, Default, Clone)]
+pub struct PoolSearchQuery {
+    pub limit: Option<usize>,
+    /// Sort field, legacy or canonical. Mapped before it goes out.
+    pub order_by: Option<String>,
+    /// `asc` or `desc`.
+    pub sort: Option<String>,
+    pub cursor: Option<String>,
+    /// Restrict to one DEX. Matches the dex id ("uniswap_v3"), not its
+    /// display name, despite the parameter's name on the wire.
+    pub dex_name: Option<String>,
+    /// Restrict to pools containing this token.
+    pub token_address: Option<String>,
+    pub volume_24h_min: Option<f64>,
+    pub volume_24h_max: Option<f64>,
+    pub volume_7d_min: Option<f64>,
+    pub volume_7d_max: Option<f64>,
+    pub liquidity_usd_min: Option<f64>,
+    pub liquidity_usd_max: Option<f64>,
+    pub txns_24h_min: Option<u64>,
+    pub price_change: PriceChangeBounds,
+    /// UNIX seconds.
+    pub created_after: Option<u64>,
+    /// UNIX seconds.
+    pub created_before: Option<u64>,
+}
+
+impl PoolSearchQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    str_setters!(cursor, dex_name, token_address);
+
+    setters! {
+        limit: usize,
+        volume_24h_min: f64,
+        volume_24h_max: f64,
+        volume_7d_min: f64,
+        volume_7d_max: f64,
+        liquidity_usd_min: f64,
+        liquidity_usd_max: f64,
+        txns_24h_min: u64,
+        created_after: u64,
+        created_before: u64,
+    }
+
+    pub fn order_by(mut self, field: impl Into<String>) -> Self {
+        self.order_by = Some(field.into());
+        self
+    }
+
+    pub fn sort(mut self, direction: impl Into<String>) -> Self {
+        self.sort = Some(direction.into());
+        self
+    }
+
+    pub fn price_change(mut self, bounds: PriceChangeBounds) -> Self {
+        self.price_change = bounds;
+        self
+    }
+
+    /// The query parameters, in wire names.
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        push_set(&mut params, "limit", &self.limit);
+        if let Some(field) = &self.order_by {
+            params.push(("order_by", map_pool_sort_field(field).to_string()));
+        }
+        push_set(&mut params, "sort", &self.sort);
+        push_set(&mut params, "cursor", &self.cursor);
+        push_set(&mut params, "dex_name", &self.dex_name);
+        push_set(&mut params, "token_address", &self.token_address);
+        push_set(&mut params, "volume_usd_24h_min", &self.volume_24h_min);
+        push_set(&mut params, "volume_usd_24h_max", &self.volume_24h_max);
+        push_set(&mut params, "volume_usd_7d_min", &self.volume_7d_min);
+        push_set(&mut params, "volume_usd_7d_max", &self.volume_7d_max);
+        push_set(&mut params, "liquidity_usd_min", &self.liquidity_usd_min);
+        push_set(&mut params, "liquidity_usd_max", &self.liquidity_usd_max);
+        push_set(&mut params, "txns_24h_min", &self.txns_24h_min);
+        params.extend(price_change_params(&self.price_change));
+        push_set(&mut params, "created_after", &self.created_after);
+        push_set(&mut params, "created_before", &self.created_before);
+        params
+    }
+}
+
+/// Query for `/networks/{network}/tokens/search`, which backs `top-tokens` and
+/// `filter-tokens`. Cursor-paginated like pools.
+#[derive(Debug, Default, Clone)]
+pub struct TokenSearchQuery {
+    pub limit: Option<usize>,
+    /// Sort field, legacy or canonical. Mapped before it goes out.
+    pub order_by: Option<String>,
+    /// `asc` or `desc`.
+    pub sort: Option<String>,
+    pub cursor: Option<String>,
+    pub volume_24h_min: Option<f64>,
+    pub volume_24h_max: Option<f64>,
+    pub liquidity_usd_min: Option<f64>,
+    pub fdv_min: Option<f64>,
+    pub fdv_max: Option<f64>,
+    pub txns_24h_min: Option<u64>,
+    pub price_change_24h_min: Option<f64>,
+    pub price_change_24h_max: Option<f64>,
+    /// UNIX seconds.
+    pub created_after: Option<u64>,
+    /// UNIX seconds.
+    pub created_before: Option<u64>,
+}
+
+impl TokenSearchQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    str_setters!(cursor);
+
+    setters! {
+        limit: usize,
+        volume_24h_min: f64,
+        volume_24h_max: f64,
+        liquidity_usd_min: f64,
+        fdv_min: f64,
+        fdv_max: f64,
+        txns_24h_min: u64,
+        price_change_24h_min: f64,
+        price_change_24h_max: f64,
+        created_after: u64,
+        created_before: u64,
+    }
+
+    pub fn order_by(mut self, field: impl Into<String>) -> Self {
+        self.order_by = Some(field.into());
+        self
+    }
+
+    pub fn sort(mut self, direction: impl Into<String>) -> Self {
+        self.sort = Some(direction.into());
+        self
+    }
+
+    /// The query parameters, in wire names.
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        push_set(&mut params, "limit", &self.limit);
+        if let Some(field) = &self.order_by {
+            params.push(("order_by", map_token_sort_field(field).to_string()));
+        }
+        push_set(&mut params, "sort", &self.sort);
+        push_set(&mut params, "cursor", &self.cursor);
+        push_set(&mut params, "volume_usd_24h_min", &self.volume_24h_min);
+        push_set(&mut params, "volume_usd_24h_max", &self.volume_24h_max);
+        push_set(&mut params, "liquidity_usd_min", &self.liquidity_usd_min);
+        push_set(&mut params, "fdv_min", &self.fdv_min);
+        push_set(&mut params, "fdv_max", &self.fdv_max);
+        params.extend(token_price_change_params(
+            self.price_change_24h_min,
+            self.price_change_24h_max,
+        ));
+        push_set(&mut params, "txns_24h_min", &self.txns_24h_min);
+        push_set(&mut params, "created_after", &self.created_after);
+        push_set(&mut params, "created_before", &self.created_before);
+        params
+    }
+}
+
+/// Query for `/networks/{network}/pools/{pool}/transactions`.
+#[derive(Debug, Default, Clone)]
+pub struct TransactionsQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    /// UNIX seconds.
+    pub from: Option<i64>,
+    /// UNIX seconds.
+    pub to: Option<i64>,
+}
+
+impl TransactionsQuery {
+    /// The most transactions the endpoint returns per page.
+    pub const MAX_LIMIT: usize = 100;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    str_setters!(cursor);
+
+    setters! {
+        limit: usize,
+        from: i64,
+        to: i64,
+    }
+
+    /// The query parameters, in wire names.
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        push_set(&mut params, "limit", &self.limit);
+        push_set(&mut params, "cursor", &self.cursor);
+        push_set(&mut params, "from", &self.from);
+        push_set(&mut params, "to", &self.to);
+        params
+    }
+}
+
+/// Query for `/networks/{network}/pools/{pool}/ohlcv`.
+#[derive(Debug, Clone)]
+pub struct OhlcvQuery {
+    /// `yyyy-mm-dd`, UNIX seconds, or RFC 3339.
+    pub start: String,
+    pub end: Option<String>,
+    /// e.g. `1m`, `15m`, `1h`, `24h`.
+    pub interval: Option<String>,
+    pub limit: Option<usize>,
+    /// Price the pair the other way round.
+    pub inversed: bool,
+}
+
+impl OhlcvQuery {
+    pub fn new(start: impl Into<String>) -> Self {
+        Self {
+            start: start.into(),
+            end: None,
+            interval: None,
+            limit: None,
+            inversed: false,
+        }
+    }
+
+    str_setters!(end, interval);
+
+    setters! {
+        limit: usize,
+    }
+
+    pub fn inversed(mut self, inversed: bool) -> Self {
+        self.inversed = inversed;
+        self
+    }
+
+    /// The query parameters, in wire names.
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("start", self.start.clone())];
+        push_set(&mut params, "interval", &self.interval);
+        push_set(&mut params, "limit", &self.limit);
+        push_set(&mut params, "end", &self.end);
+        if self.inversed {
+            params.push(("inversed", "true".to_string()));
+        }
+        params
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dex_pools_default_sort_maps_to_the_canonical_search_field() {
+        // The dex-pools --order-by default is still the legacy "volume_usd";
+        // pools/search rejects it, so it has to be mapped before it goes out.
+        assert_eq!(
+            crate::search_mapping::map_pool_sort_field("volume_usd"),
+            "volume_usd_24h"
+        );
+    }
+
+    /// Every bound set, each to a different value, so a transposed pair shows up
+    /// as a wrong value next to the right name.
+    fn all_bounds() -> PriceChangeBounds {
+        PriceChangeBounds {
+            price_change_24h_min: Some(1.0),
+            price_change_24h_max: Some(2.0),
+            price_change_6h_min: Some(3.0),
+            price_change_6h_max: Some(4.0),
+            price_change_1h_min: Some(5.0),
+            price_change_1h_max: Some(6.0),
+            price_change_5m_min: Some(7.0),
+            price_change_5m_max: Some(8.0),
+        }
+    }
+
+    #[test]
+    fn price_change_params_carry_the_names_the_api_expects() {
+        let params = price_change_params(&all_bounds());
+        let pairs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("price_change_percentage_24h_min", "1"),
+                ("price_change_percentage_24h_max", "2"),
+                ("price_change_percentage_6h_min", "3"),
+                ("price_change_percentage_6h_max", "4"),
+                ("price_change_percentage_1h_min", "5"),
+                ("price_change_percentage_1h_max", "6"),
+                ("price_change_percentage_5m_min", "7"),
+                ("price_change_percentage_5m_max", "8"),
+            ]
+        );
+    }
+
+    /// The same eight names one layer further out, read off the URL reqwest
+    /// would actually send. Verified against api.dexpaprika.com on 2026-08-07:
+    /// each of these bounds changes the result set, and a misspelling of any of
+    /// them returns the unfiltered baseline at HTTP 200.
+    #[test]
+    fn price_change_bounds_reach_the_query_string() {
+        let params = price_change_params(&all_bounds());
+        let refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let request = reqwest::Client::new()
+            .get("https://api.dexpaprika.com/networks/ethereum/pools/search")
+            .query(&refs)
+            .build()
+            .expect("the request should build");
+
+        assert_eq!(
+            request.url().query().expect("a query string"),
+            "price_change_percentage_24h_min=1&price_change_percentage_24h_max=2\
+             &price_change_percentage_6h_min=3&price_change_percentage_6h_max=4\
+             &price_change_percentage_1h_min=5&price_change_percentage_1h_max=6\
+             &price_change_percentage_5m_min=7&price_change_percentage_5m_max=8"
+        );
+    }
+
+    #[test]
+    fn negative_bounds_survive_url_encoding() {
+        // "down 20% or more over 24h" has to leave as -20, not as %2D20 or 20.
+        let bounds = PriceChangeBounds {
+            price_change_24h_max: Some(-20.0),
+            price_change_5m_min: Some(-1.5),
+            ..PriceChangeBounds::default()
+        };
+        let params = price_change_params(&bounds);
+        let refs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let request = reqwest::Client::new()
+            .get("https://api.dexpaprika.com/networks/ethereum/pools/search")
+            .query(&refs)
+            .build()
+            .expect("the request should build");
+
+        assert_eq!(
+            request.url().query().expect("a query string"),
+            "price_change_percentage_24h_max=-20&price_change_percentage_5m_min=-1.5"
+        );
+    }
+
+    #[test]
+    fn unset_bounds_send_nothing() {
+        assert!(price_change_params(&PriceChangeBounds::default()).is_empty());
+    }
+
+    /// Verified against api.dexpaprika.com on 2026-08-07. Baseline 24h changes
+    /// on ethereum were [95.7, -0.04, -0.02, -0.0, 0.61]; with
+    /// --price-change-24h-min 20 the same command returned
+    /// [95.7, 36.36, 923.6, 41.72, 2764.61], and with --price-change-24h-max -20
+    /// it returned [-99.32, -23.58, -90.79, -54.22, -21.12].
+    #[test]
+    fn token_price_change_params_carry_the_names_the_api_expects() {
+        let params = token_price_change_params(Some(20.0), Some(-20.0));
+        let pairs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("price_change_percentage_24h_min", "20"),
+                ("price_change_percentage_24h_max", "-20"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unset_token_bounds_send_nothing() {
+        assert!(token_price_change_params(None, None).is_empty());
+    }
+
+    #[test]
+    fn pool_search_params_map_the_sort_field_and_skip_unset_filters() {
+        let params = PoolSearchQuery::new()
+            .limit(5)
+            .order_by("volume_usd")
+            .sort("desc")
+            .dex_name("uniswap_v3")
+            .volume_24h_min(None)
+            .liquidity_usd_min(1e6)
+            .params();
+        let pairs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("limit", "5"),
+                ("order_by", "volume_usd_24h"),
+                ("sort", "desc"),
+                ("dex_name", "uniswap_v3"),
+                ("liquidity_usd_min", "1000000"),
+            ]
+        );
+    }
+
+    #[test]
+    fn token_search_params_map_legacy_filter_names() {
+        let params = TokenSearchQuery::new()
+            .order_by("fdv")
+            .volume_24h_min(10.0)
+            .fdv_max(5.0)
+            .params();
+        let pairs: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("order_by", "fdv_usd"),
+                ("volume_usd_24h_min", "10"),
+                ("fdv_max", "5"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ohlcv_params_send_inversed_only_when_set() {
+        let plain = OhlcvQuery::new("2026-01-01").interval("1h").params();
+        assert!(!plain.iter().any(|(k, _)| *k == "inversed"));
+        let inversed = OhlcvQuery::new("2026-01-01").inversed(true).params();
+        assert!(inversed.contains(&("inversed", "true".to_string())));
+    }
+}
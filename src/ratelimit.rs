@@ -0,0 +1,162 @@
+//! Client-side pacing so multi-request commands stay under the per-minute
+//! limit instead of discovering it through 429s.
+//!
+//! One token bucket per pace for the whole process, shared by every REST call
+//! and every stream connection attempt of every `ApiClient` at that pace. The
+//! shell builds a client for each line it runs, and those lines are still paced
+//! against each other. A small burst keeps a single command as fast as before;
+//! anything that fires many requests settles into the steady rate.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Pace used when neither a flag, the config, nor a recorded plan says
+/// otherwise. Deliberately conservative: it only ever slows a crawl down, and
+/// `--rate-limit` lifts it.
+pub const DEFAULT_RATE_PER_MINUTE: u32 = 60;
+
+/// Requests that may go out back to back before pacing starts.
+const MAX_BURST: u32 = 5;
+
+/// Per-minute pace for a plan name as `/usage` reports it. Unknown plans get the
+/// default rather than an error: a new plan name should not break the CLI.
+pub fn plan_rate_per_minute(plan: &str) -> u32 {
+    match plan {
+        "pro" => 300,
+        _ => DEFAULT_RATE_PER_MINUTE,
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Tokens added per second.
+    rate: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// May go negative: each caller reserves its token up front and waits off
+    /// the debt, so concurrent callers queue in order instead of racing.
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    /// The process-wide limiter for `requests` per minute, made on first use.
+    pub fn shared(requests: u32) -> Arc<RateLimiter> {
+        static SHARED: OnceLock<Mutex<HashMap<u32, Arc<RateLimiter>>>> = OnceLock::new();
+        let mut shared = SHARED
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        shared
+            .entry(requests)
+            .or_insert_with(|| Arc::new(RateLimiter::per_minute(requests)))
+            .clone()
+    }
+
+    pub fn per_minute(requests: u32) -> Self {
+        let requests = requests.max(1);
+        let burst = f64::from(requests.min(MAX_BURST));
+        Self {
+            rate: f64::from(requests) / 60.0,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                last: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until one more request may go out.
+    pub async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token as of `now` and return how long the caller has to wait
+    /// before using it.
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = now.saturating_duration_since(bucket.last).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.last = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_burst_goes_out_immediately_then_requests_are_spaced() {
+        let limiter = RateLimiter::per_minute(60);
+        let now = Instant::now();
+        for _ in 0..MAX_BURST {
+            assert_eq!(limiter.reserve(now), Duration::ZERO);
+        }
+        // At 60/minute each further request owes one more second.
+        let first = limiter.reserve(now);
+        let second = limiter.reserve(now);
+        assert!((first.as_secs_f64() - 1.0).abs() < 0.01, "{first:?}");
+        assert!((second.as_secs_f64() - 2.0).abs() < 0.01, "{second:?}");
+    }
+
+    #[test]
+    fn idle_time_refills_the_bucket_up_to_the_burst() {
+        let limiter = RateLimiter::per_minute(60);
+        let start = Instant::now();
+        for _ in 0..MAX_BURST {
+            limiter.reserve(start);
+        }
+        let later = start + Duration::from_secs(600);
+        for _ in 0..MAX_BURST {
+            assert_eq!(limiter.reserve(later), Duration::ZERO);
+        }
+        assert!(!limiter.reserve(later).is_zero());
+    }
+
+    #[test]
+    fn a_slow_rate_never_bursts_past_itself() {
+        let limiter = RateLimiter::per_minute(2);
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+        let wait = limiter.reserve(now);
+        assert!((wait.as_secs_f64() - 30.0).abs() < 0.01, "{wait:?}");
+    }
+
+    #[test]
+    fn one_limiter_per_pace_for_the_whole_process() {
+        let first = RateLimiter::shared(17);
+        let now = Instant::now();
+        for _ in 0..MAX_BURST {
+            first.reserve(now);
+        }
+        // A second client at the same pace gets no burst of its own.
+        assert!(!RateLimiter::shared(17).reserve(now).is_zero());
+        assert!(Arc::ptr_eq(&first, &RateLimiter::shared(17)));
+        assert!(!Arc::ptr_eq(&first, &RateLimiter::shared(18)));
+    }
+
+    #[test]
+    fn plans_map_to_their_pace() {
+        assert_eq!(plan_rate_per_minute("pro"), 300);
+        assert_eq!(plan_rate_per_minute("keyless"), DEFAULT_RATE_PER_MINUTE);
+        assert_eq!(
+            plan_rate_per_minute("something-new"),
+            DEFAULT_RATE_PER_MINUTE
+        );
+    }
+}
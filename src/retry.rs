@@ -0,0 +1,185 @@
+//! Retry policy for transient REST failures.
+//!
+//! Only failures that can succeed on a second attempt are retried: HTTP 429,
+//! any 5xx, and requests that never got an answer (connect errors, timeouts).
+//! Every other 4xx is a statement about the request itself, and sending the
+//! same request again cannot change it.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+/// Retries `dexpaprika_get` makes after the first attempt unless configured
+/// otherwise.
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// The longest `Retry-After` the client will sit through. A server asking for
+/// more than this is not describing a blip, and a CLI hanging for ten minutes
+/// without output looks exactly like a CLI that has crashed.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts after the first one. Zero disables retrying.
+    pub max_retries: u32,
+    /// Backoff before the first retry; doubled for each one after it.
+    pub base_delay: Duration,
+    /// Ceiling on a computed backoff. `Retry-After` is not clamped to it.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn with_max_retries(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+
+    /// Jittered exponential backoff before retry number `retry` (0-based).
+    ///
+    /// Equal jitter: half of the exponential step is fixed and the other half
+    /// random. Keeping a floor means a retry never fires immediately, and the
+    /// random half spreads out scripts that all failed on the same 502.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let step = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let half = step / 2;
+        half + half.mul_f64(jitter())
+    }
+
+    /// How long to wait before retry number `retry`, or `None` when the server
+    /// asked for a longer pause than the client is willing to take.
+    pub fn delay_for(&self, retry: u32, headers: &HeaderMap) -> Option<Duration> {
+        match retry_after(headers) {
+            Some(wait) if wait > MAX_RETRY_AFTER => None,
+            Some(wait) => Some(wait),
+            None => Some(self.backoff(retry)),
+        }
+    }
+}
+
+/// Whether a response status is worth another attempt.
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Whether a transport error is worth another attempt: the request either never
+/// reached the server or never got an answer back.
+pub fn is_retryable_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout() || err.is_request()
+}
+
+/// Read `Retry-After`, which is either delay-seconds or an HTTP-date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    // A date already in the past means "now".
+    Some(
+        (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// A number in [0, 1). RandomState is seeded per instance from the OS, which is
+/// all the randomness spreading retries out needs, without a dependency for it.
+fn jitter() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers_with_retry_after(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn only_429_and_5xx_are_retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        for status in [
+            StatusCode::BAD_REQUEST,
+            StatusCode::UNAUTHORIZED,
+            StatusCode::FORBIDDEN,
+            StatusCode::NOT_FOUND,
+            StatusCode::GONE,
+        ] {
+            assert!(!is_retryable_status(status), "{status} must not be retried");
+        }
+    }
+
+    #[test]
+    fn retry_after_reads_delay_seconds() {
+        assert_eq!(
+            retry_after(&headers_with_retry_after("7")),
+            Some(Duration::from_secs(7))
+        );
+    }
+
+    #[test]
+    fn retry_after_reads_an_http_date() {
+        let at = chrono::Utc::now() + chrono::Duration::seconds(30);
+        let value = at.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        let wait = retry_after(&headers_with_retry_after(&value)).expect("a delay");
+        assert!(wait <= Duration::from_secs(30));
+        assert!(wait >= Duration::from_secs(28));
+    }
+
+    #[test]
+    fn retry_after_in_the_past_means_now_and_garbage_means_absent() {
+        assert_eq!(
+            retry_after(&headers_with_retry_after("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after(&headers_with_retry_after("soon")), None);
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn backoff_doubles_within_its_jitter_band_and_stops_at_the_ceiling() {
+        let policy = RetryPolicy::default();
+        for retry in 0..4 {
+            let step = Duration::from_millis(500) * 2u32.pow(retry);
+            let wait = policy.backoff(retry);
+            assert!(wait >= step / 2 && wait <= step, "retry {retry}: {wait:?}");
+        }
+        assert!(policy.backoff(30) <= policy.max_delay);
+    }
+
+    #[test]
+    fn a_retry_after_beyond_the_ceiling_gives_up() {
+        let policy = RetryPolicy::default();
+        assert_eq!(
+            policy.delay_for(0, &headers_with_retry_after("3")),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(policy.delay_for(0, &headers_with_retry_after("3600")), None);
+    }
+}
@@ -5,7 +5,9 @@
 //! endpoints return HTTP 400 for the legacy sort-field names that the old
 //! list/filter endpoints accepted. Every incoming sort field must therefore be
 //! mapped to its canonical value before it is sent upstream. Unknown values fall
-//! back to the default, "volume_usd_24h".
+//! back to the default, "volume_usd_24h"; `pool_sort_fallback` and
+//! `token_sort_fallback` say when that happened, so callers can warn rather
+//! than hand back rows in an order nobody asked for.
 
 /// Default sort field shared by both pools and tokens search.
 const DEFAULT_SORT_FIELD: &str = "volume_usd_24h";
@@ -16,7 +18,11 @@ const DEFAULT_SORT_FIELD: &str = "volume_usd_24h";
 /// The 6h, 1h and 5m price-change windows are pools-only. tokens/search rejects
 /// them with 400, so they must never be copied into `map_token_sort_field`.
 pub fn map_pool_sort_field(field: &str) -> &'static str {
-    match field {
+    canonical_pool_sort_field(field).unwrap_or(DEFAULT_SORT_FIELD)
+}
+
+fn canonical_pool_sort_field(field: &str) -> Option<&'static str> {
+    Some(match field {
         // Canonical values pass through unchanged.
         "volume_usd_24h" => "volume_usd_24h",
         "volume_usd_7d" => "volume_usd_7d",
@@ -37,8 +43,8 @@ pub fn map_pool_sort_field(field: &str) -> &'static str {
         "transactions" => "txns_24h",
         "last_price_change_usd_24h" => "price_change_percentage_24h",
         "liquidity" => "liquidity_usd",
-        _ => DEFAULT_SORT_FIELD,
-    }
+        _ => return None,
+    })
 }
 
 /// Map a token sort field (legacy or canonical) to the canonical value accepted
@@ -47,7 +53,11 @@ pub fn map_pool_sort_field(field: &str) -> &'static str {
 /// 6h, 1h and 5m price-change windows, which pools/search accepts: token rows do
 /// not carry those fields, so those values stay out of this list on purpose.
 pub fn map_token_sort_field(field: &str) -> &'static str {
-    match field {
+    canonical_token_sort_field(field).unwrap_or(DEFAULT_SORT_FIELD)
+}
+
+fn canonical_token_sort_field(field: &str) -> Option<&'static str> {
+    Some(match field {
         // Canonical values pass through unchanged.
         "volume_usd_24h" => "volume_usd_24h",
         "volume_usd_7d" => "volume_usd_7d",
@@ -64,10 +74,35 @@ pub fn map_token_sort_field(field: &str) -> &'static str {
         "txns" => "txns_24h",
         "price_change" => "price_change_percentage_24h",
         "fdv" => "fdv_usd",
-        // tokens/search 400s on price ordering: fall back to volume.
-        "price_usd" => "volume_usd_24h",
-        _ => DEFAULT_SORT_FIELD,
+        // tokens/search 400s on price ordering, so "price_usd" is left to
+        // fall back to volume like any unknown value.
+        _ => return None,
+    })
+}
+
+/// Why pools/search won't be ordered by `field`, when `map_pool_sort_field`
+/// had to fall back to the default for it.
+pub fn pool_sort_fallback(field: &str) -> Option<String> {
+    canonical_pool_sort_field(field)
+        .is_none()
+        .then(|| format!("`{field}` is not a pool sort field the API knows"))
+}
+
+/// Why tokens/search won't be ordered by `field`, when `map_token_sort_field`
+/// had to fall back to the default for it.
+pub fn token_sort_fallback(field: &str) -> Option<String> {
+    if canonical_token_sort_field(field).is_some() {
+        return None;
     }
+    Some(match field {
+        "price_usd" => "tokens/search can't order by price".to_string(),
+        "price_change_percentage_6h"
+        | "price_change_percentage_1h"
+        | "price_change_percentage_5m" => {
+            format!("`{field}` exists for pools only")
+        }
+        _ => format!("`{field}` is not a token sort field the API knows"),
+    })
 }
 
 #[cfg(test)]
@@ -167,4 +202,20 @@ mod tests {
         assert_eq!(map_token_sort_field("nonsense"), "volume_usd_24h");
         assert_eq!(map_token_sort_field(""), "volume_usd_24h");
     }
+
+    #[test]
+    fn fallbacks_are_reported_and_known_fields_are_not() {
+        assert_eq!(pool_sort_fallback("volume_usd"), None);
+        assert_eq!(pool_sort_fallback("price_change_percentage_5m"), None);
+        assert!(pool_sort_fallback("fee").is_some());
+        assert_eq!(token_sort_fallback("fdv"), None);
+        assert_eq!(
+            token_sort_fallback("price_usd").as_deref(),
+            Some("tokens/search can't order by price")
+        );
+        assert!(token_sort_fallback("price_change_percentage_1h")
+            .unwrap()
+            .contains("pools only"));
+        assert!(token_sort_fallback("nonsense").is_some());
+    }
 }
@@ -33,7 +33,11 @@ pub async fn run_shell() {
 
                 match crate::Cli::try_parse_from(&full_args) {
                     Ok(cli) => {
-                        if let Err(e) = crate::run(cli).await {
+                        let result = async {
+                            let output = cli.output_format()?;
+                            crate::run(cli, output).await
+                        };
+                        if let Err(e) = result.await {
                             eprintln!("Error: {e}");
                         }
                     }
@@ -0,0 +1,68 @@
+//! Streaming (SSE) feeds as `futures::Stream`s of typed events.
+//!
+//! Each feed has a single-subscription form (a GET with the subscription in
+//! the query string) and a batch form (a POST carrying up to
+//! `MAX_SUBSCRIPTIONS_PER_POST` subscriptions). Both go through
+//! `ApiClient::open_stream`, so they are paced, authenticated, recorded and
+//! replayed like any REST call.
+//!
+//! A stream ends when the server closes it. Events the feed sends besides
+//! data (ping, warning, error) are skipped, as are payloads that do not
+//! decode.
+
+pub mod prices;
+pub mod reserves;
+pub mod sse;
+
+use std::pin::Pin;
+
+use anyhow::Result;
+use futures::{Stream, StreamExt};
+
+use crate::client::ByteStream;
+use sse::SseBuffer;
+
+pub use prices::{PriceSubscription, StreamEvent};
+pub use reserves::{ReserveEvent, ReserveMethod, ReserveSubscription, TokenReserveStat};
+
+/// Hard cap on subscriptions in a POST body. The server enforces the same
+/// value and rejects oversized batches with HTTP 400.
+pub const MAX_SUBSCRIPTIONS_PER_POST: usize = 25;
+
+/// A feed of decoded events. An `Err` item means the connection broke; the
+/// stream ends after it.
+pub type EventStream<T> = Pin<Box<dyn Stream<Item = Result<T>> + Send>>;
+
+/// Split `bytes` into SSE messages and keep the ones `parse` understands.
+pub(crate) fn sse_events<T: Send + 'static>(
+    bytes: ByteStream,
+    parse: fn(&str) -> Option<T>,
+) -> EventStream<T> {
+    let messages = futures::stream::unfold(
+        (bytes, SseBuffer::default(), false),
+        |(mut bytes, mut buffer, done)| async move {
+            if done {
+                return None;
+            }
+            loop {
+                if let Some(message) = buffer.next_message() {
+                    return Some((Ok(message), (bytes, buffer, false)));
+                }
+                match bytes.next().await {
+                    Some(Ok(chunk)) => buffer.push(&chunk),
+                    Some(Err(e)) => {
+                        let err = anyhow::anyhow!("Stream error: {e}");
+                        return Some((Err(err), (bytes, buffer, true)));
+                    }
+                    None => return None,
+                }
+            }
+        },
+    );
+    Box::pin(messages.filter_map(move |message| async move {
+        match message {
+            Ok(message) => parse(&message).map(Ok),
+            Err(e) => Some(Err(e)),
+        }
+    }))
+}
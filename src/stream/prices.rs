@@ -0,0 +1,136 @@
+//! The `token_price` feed on `/sse/prices`.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::{sse_events, EventStream};
+use crate::client::ApiClient;
+
+/// Raw `token_price` SSE payload. Wire field names are mapped to readable
+/// CLI struct names via serde renames.
+#[derive(Debug, Deserialize)]
+pub struct RawStreamEvent {
+    pub address: String,
+    pub chain: String,
+    #[serde(rename = "price")]
+    pub price_usd: String,
+    pub timestamp: i64,
+    #[serde(rename = "timestamp_price")]
+    pub price_timestamp: i64,
+}
+
+/// CLI-facing event with readable field names.
+#[derive(Debug, Serialize)]
+pub struct StreamEvent {
+    pub address: String,
+    pub chain: String,
+    pub price_usd: String,
+    pub timestamp: i64,
+    pub price_timestamp: i64,
+}
+
+impl From<RawStreamEvent> for StreamEvent {
+    fn from(raw: RawStreamEvent) -> Self {
+        Self {
+            address: raw.address,
+            chain: raw.chain,
+            price_usd: raw.price_usd,
+            timestamp: raw.timestamp,
+            price_timestamp: raw.price_timestamp,
+        }
+    }
+}
+
+/// One token to follow in a batch price stream.
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceSubscription {
+    pub chain: String,
+    pub address: String,
+    method: &'static str,
+}
+
+impl PriceSubscription {
+    pub fn new(chain: impl Into<String>, address: impl Into<String>) -> Self {
+        Self {
+            chain: chain.into(),
+            address: address.into(),
+            method: "token_price",
+        }
+    }
+}
+
+impl ApiClient {
+    /// Follow the USD price of one token.
+    pub async fn price_stream(
+        &self,
+        chain: &str,
+        address: &str,
+    ) -> Result<EventStream<StreamEvent>> {
+        let target = format!("/sse/prices?method=token_price&chain={chain}&address={address}");
+        let bytes = self.open_stream(&target, None).await?;
+        Ok(sse_events(bytes, parse_token_price_message))
+    }
+
+    /// Follow several tokens over one connection. The server takes at most
+    /// `MAX_SUBSCRIPTIONS_PER_POST` of them.
+    pub async fn price_stream_batch(
+        &self,
+        subscriptions: &[PriceSubscription],
+    ) -> Result<EventStream<StreamEvent>> {
+        let body = serde_json::to_string(subscriptions)?;
+        let bytes = self.open_stream("/sse/prices", Some(body)).await?;
+        Ok(sse_events(bytes, parse_token_price_message))
+    }
+}
+
+/// Parse one SSE message into a `StreamEvent`. Returns `None` for
+/// non-`token_price` events (ping, warning, error) or malformed payloads.
+///
+/// Both `event:`/`data:` line orderings are valid and the server has emitted
+/// either during rollout, so the whole message is read before dispatching.
+pub fn parse_token_price_message(message: &str) -> Option<StreamEvent> {
+    let mut event_name: Option<&str> = None;
+    let mut data: Option<&str> = None;
+
+    for line in message.lines() {
+        if let Some(rest) = line.strip_prefix("event:") {
+            event_name = Some(rest.trim());
+        } else if let Some(rest) = line.strip_prefix("data:") {
+            data = Some(rest.trim_start());
+        }
+    }
+
+    if event_name != Some("token_price") {
+        return None;
+    }
+    let data = data?;
+    serde_json::from_str::<RawStreamEvent>(data)
+        .ok()
+        .map(StreamEvent::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &str = r#"{"address":"0xc02a","chain":"ethereum","price":"1876.4","timestamp":1780487749,"timestamp_price":1780487747}"#;
+
+    #[test]
+    fn either_line_order_parses_and_other_events_are_skipped() {
+        let event =
+            parse_token_price_message(&format!("event: token_price\ndata: {DATA}")).unwrap();
+        assert_eq!(event.price_usd, "1876.4");
+        assert_eq!(event.price_timestamp, 1780487747);
+        assert!(parse_token_price_message(&format!("data: {DATA}\nevent: token_price")).is_some());
+        assert!(parse_token_price_message("event: ping\ndata: {}").is_none());
+    }
+
+    #[test]
+    fn a_batch_subscription_carries_the_method_on_the_wire() {
+        let body = serde_json::to_string(&[PriceSubscription::new("ethereum", "0xc02a")]).unwrap();
+        assert_eq!(
+            body,
+            r#"[{"chain":"ethereum","address":"0xc02a","method":"token_price"}]"#
+        );
+    }
+}
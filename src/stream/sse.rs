@@ -0,0 +1,72 @@
+//! Server-Sent Events framing, shared by both streaming commands.
+//!
+//! Only the framing lives here: splitting a byte stream into messages. Each
+//! command reads the `event:`/`data:` lines of a message itself, since the two
+//! feeds carry different extra lines (`request_id:` on reserves).
+
+/// Accumulates stream chunks and hands back complete messages.
+///
+/// Chunks split wherever the network splits them, including inside a message
+/// or a multi-byte character, so bytes are buffered and only decoded once a
+/// whole message is in. A message ends at a blank line; `\r\n` line endings are
+/// accepted as well as `\n`.
+#[derive(Debug, Default)]
+pub struct SseBuffer {
+    buffer: Vec<u8>,
+}
+
+impl SseBuffer {
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// The next complete message, without its terminating blank line.
+    pub fn next_message(&mut self) -> Option<String> {
+        let (end, sep) = [&b"\r\n\r\n"[..], b"\n\n"]
+            .into_iter()
+            .filter_map(|sep| find(&self.buffer, sep).map(|at| (at, sep.len())))
+            .min()?;
+        let message = String::from_utf8_lossy(&self.buffer[..end]).replace("\r\n", "\n");
+        self.buffer.drain(..end + sep);
+        Some(message)
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_message_split_across_chunks_comes_out_whole() {
+        let mut buf = SseBuffer::default();
+        buf.push(b"event: token_price\nda");
+        assert!(buf.next_message().is_none());
+        buf.push(b"ta: {\"a\":1}\n\nevent: ping\n\n");
+        assert_eq!(
+            buf.next_message().as_deref(),
+            Some("event: token_price\ndata: {\"a\":1}")
+        );
+        assert_eq!(buf.next_message().as_deref(), Some("event: ping"));
+        assert!(buf.next_message().is_none());
+    }
+
+    #[test]
+    fn crlf_framing_is_accepted() {
+        let mut buf = SseBuffer::default();
+        buf.push(b"event: ping\r\ndata: {}\r\n\r\n");
+        assert_eq!(buf.next_message().as_deref(), Some("event: ping\ndata: {}"));
+    }
+
+    #[test]
+    fn a_character_split_between_chunks_is_not_mangled() {
+        let mut buf = SseBuffer::default();
+        let text = "data: \u{00e9}\n\n".as_bytes();
+        buf.push(&text[..7]);
+        buf.push(&text[7..]);
+        assert_eq!(buf.next_message().as_deref(), Some("data: \u{00e9}"));
+    }
+}
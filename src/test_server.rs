@@ -0,0 +1,58 @@
+//! A stand-in HTTP server for tests: a local socket that answers each
+//! connection on a thread of its own. The library's tests and the CLI's both
+//! include this file, and each uses only part of it.
+#![allow(dead_code)]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver};
+
+/// Answer each connection with `respond(request)` until it returns `None`,
+/// and hand back the base URL to point a client at. Each request, as the
+/// server read it, comes back through the receiver before it is answered.
+pub fn serve_with(
+    mut respond: impl FnMut(&str) -> Option<String> + Send + 'static,
+) -> (String, Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut buf = [0u8; 4096];
+            let n = stream.read(&mut buf).unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+            let Some(response) = respond(&request) else {
+                break;
+            };
+            let _ = tx.send(request);
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    (format!("http://{addr}"), rx)
+}
+
+/// One canned response per connection, in order, then nothing.
+pub fn serve_and_record(responses: Vec<&'static str>) -> (String, Receiver<String>) {
+    let mut responses = responses.into_iter();
+    serve_with(move |_| responses.next().map(str::to_string))
+}
+
+/// `serve_and_record` for a test that doesn't look at the requests.
+pub fn serve(responses: Vec<&'static str>) -> String {
+    serve_and_record(responses).0
+}
+
+/// A 200 answer carrying `body` as JSON.
+pub fn json(body: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// The path and query of a request: `/stats?limit=5`.
+pub fn target(request: &str) -> &str {
+    request.split(' ').nth(1).unwrap_or_default()
+}